@@ -0,0 +1,82 @@
+use ink::primitives::AccountId;
+use ink::storage::traits::StorageKey;
+use ink::storage::Mapping;
+
+/// Roles the admin can grant to delegate privileged operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum Role {
+    /// Can withdraw the collected funds
+    Treasurer,
+    /// Can manage reserved names & the rules deciding which names can be registered
+    ReservationManager,
+    /// Can manage prices & fees
+    PricingManager,
+    /// Can upgrade the contract code
+    Upgrader,
+    /// Can pause & unpause operations
+    Pauser,
+    /// Can update the protocol configuration (e.g. records size limit, router registries)
+    ConfigManager,
+    /// Can consume (i.e. mark as claimed) the leaves of merkle campaigns
+    ClaimConsumer,
+}
+
+impl Role {
+    pub const ALL: [Role; 7] = [
+        Role::Treasurer,
+        Role::ReservationManager,
+        Role::PricingManager,
+        Role::Upgrader,
+        Role::Pauser,
+        Role::ConfigManager,
+        Role::ClaimConsumer,
+    ];
+}
+
+/// Bookkeeping of the roles granted to accounts, shared by the contracts.
+/// The admin implicitly holds every role, so that contracts deployed prior
+/// to the introduction of roles don't need any migration.
+pub trait AccessControl {
+    /// Whether the account holds the role
+    fn holds(&self, admin: AccountId, role: Role, account: AccountId) -> bool;
+    /// Grants the role to the account, returns `false` if it was granted already
+    fn grant(&mut self, role: Role, account: AccountId) -> bool;
+    /// Revokes the role from the account, returns `false` if it wasn't granted
+    fn revoke(&mut self, role: Role, account: AccountId) -> bool;
+}
+
+impl<KEY: StorageKey> AccessControl for Mapping<(Role, AccountId), (), KEY> {
+    fn holds(&self, admin: AccountId, role: Role, account: AccountId) -> bool {
+        account == admin || self.contains((role, account))
+    }
+
+    fn grant(&mut self, role: Role, account: AccountId) -> bool {
+        if self.contains((role, account)) {
+            return false;
+        }
+        self.insert((role, account), &());
+        true
+    }
+
+    fn revoke(&mut self, role: Role, account: AccountId) -> bool {
+        if !self.contains((role, account)) {
+            return false;
+        }
+        self.remove((role, account));
+        true
+    }
+}
+
+/// Replaces the code of the calling contract (as done by zink's `Upgradable` coating)
+pub fn upgrade_contract(code_hash: [u8; 32]) {
+    ink::env::set_code_hash(&code_hash).unwrap_or_else(|err| {
+        panic!(
+            "Failed to `set_code_hash` to {:?} due to {:?}",
+            code_hash, err
+        )
+    });
+}
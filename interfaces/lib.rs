@@ -1,4 +1,6 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub mod access_control;
 pub mod art_zero_traits;
+pub mod psp22;
 pub mod psp34_standard;
@@ -2,6 +2,7 @@
 
 use ink::prelude::string::String;
 use ink::prelude::vec::Vec;
+use interfaces::access_control::{self, AccessControl, Role};
 
 #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
@@ -14,6 +15,10 @@ pub enum Error {
     ZeroLength,
     /// Zero price not allowed
     ZeroPrice,
+    /// Premium duration (or half-life) must be non-zero
+    InvalidPremiumConfig,
+    /// Caller is missing the role required for the operation
+    MissingRole(Role),
 }
 
 pub type Result<T> = core::result::Result<T, Error>;
@@ -29,21 +34,70 @@ macro_rules! ensure {
     }};
 }
 
-pub use self::azns_fee_calculator::{FeeCalculator, FeeCalculatorRef};
+pub use self::azns_fee_calculator::{
+    FeeCalculator, FeeCalculatorRef, PremiumDecay, ReleasePremium,
+};
 
 #[zink::coating(Ownable2Step[
     Error = Error::NotAdmin
 ])]
-#[zink::coating(Upgradable)]
 #[ink::contract]
 mod azns_fee_calculator {
     use super::*;
     use ink::storage::traits::ManualKey;
-    use ink::storage::Mapping;
+    use ink::storage::{Lazy, Mapping};
 
     // Length of name
     pub type Length = u8;
 
+    /// How the premium of a recently released name decays towards zero
+    #[derive(Debug, PartialEq, Eq, Clone, Copy, scale::Encode, scale::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum PremiumDecay {
+        Linear,
+        /// Premium halves every `half_life` (in ms)
+        Exponential {
+            half_life: u64,
+        },
+    }
+
+    /// Premium charged on top of the base price for names that were just released
+    #[derive(Debug, PartialEq, Eq, Clone, Copy, scale::Encode, scale::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct ReleasePremium {
+        /// Premium at the time of release
+        pub start_premium: Balance,
+        /// Period (in ms) over which the premium decays to zero
+        pub duration: u64,
+        pub decay: PremiumDecay,
+    }
+
+    /// Emitted whenever a role is granted to an account
+    #[ink(event)]
+    pub struct RoleGranted {
+        #[ink(topic)]
+        role: Role,
+        #[ink(topic)]
+        account: AccountId,
+        sender: AccountId,
+    }
+
+    /// Emitted whenever a role is revoked from (or renounced by) an account
+    #[ink(event)]
+    pub struct RoleRevoked {
+        #[ink(topic)]
+        role: Role,
+        #[ink(topic)]
+        account: AccountId,
+        sender: AccountId,
+    }
+
     #[ink(storage)]
     pub struct FeeCalculator {
         /// Account allowed to modify the variables
@@ -56,6 +110,10 @@ mod azns_fee_calculator {
         common_price: Balance,
         /// Set price for specific name length
         price_by_length: Mapping<Length, Balance, ManualKey<100>>,
+        /// Decaying premium for recently released names
+        release_premium: Lazy<Option<ReleasePremium>, ManualKey<101>>,
+        /// Roles granted to accounts
+        roles: Mapping<(Role, AccountId), (), ManualKey<102>>,
     }
 
     impl FeeCalculator {
@@ -75,6 +133,8 @@ mod azns_fee_calculator {
                 max_registration_duration,
                 common_price,
                 price_by_length: Default::default(),
+                release_premium: Default::default(),
+                roles: Default::default(),
             };
 
             price_points.iter().for_each(|(length, price)| {
@@ -91,24 +151,64 @@ mod azns_fee_calculator {
         }
 
         // (base_price, premium): (Balance, Balance)
+        // @note the premium includes the release premium if the name was released at `released_at`
         #[ink(message)]
-        pub fn get_name_price(&self, name: String, duration: u8) -> Result<(Balance, Balance)> {
+        pub fn get_name_price(
+            &self,
+            name: String,
+            duration: u8,
+            released_at: Option<u64>,
+        ) -> Result<(Balance, Balance)> {
             ensure!(
                 1 <= duration && duration <= self.max_registration_duration,
                 Error::InvalidDuration
             );
-            ensure!(name.len() != 0, Error::ZeroLength);
+            ensure!(!name.is_empty(), Error::ZeroLength);
 
             let base_price = self
                 .price_by_length
                 .get(name.len() as Length)
                 .unwrap_or(self.common_price);
 
-            let premium = (duration as u128 - 1) * base_price;
+            let mut premium = (duration as u128 - 1) * base_price;
+            if let Some(released_at) = released_at {
+                premium += self.get_release_premium(released_at);
+            }
 
             Ok((base_price, premium))
         }
 
+        /// Returns the premium for a name which has been released at `released_at`
+        #[ink(message)]
+        pub fn get_release_premium(&self, released_at: u64) -> Balance {
+            let Some(config) = self.get_release_premium_config() else {
+                return 0;
+            };
+
+            let now = self.env().block_timestamp();
+            if now < released_at || now - released_at >= config.duration {
+                return 0;
+            }
+
+            let elapsed = now - released_at;
+            match config.decay {
+                PremiumDecay::Linear => {
+                    let remaining = (config.duration - elapsed) as u128;
+                    config.start_premium * remaining / config.duration as u128
+                }
+                PremiumDecay::Exponential { half_life } => {
+                    // Offset by the premium left at the end, so it reaches zero
+                    let decayed = |time| Self::halve(config.start_premium, time, half_life);
+                    decayed(elapsed).saturating_sub(decayed(config.duration))
+                }
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_release_premium_config(&self) -> Option<ReleasePremium> {
+            self.release_premium.get().flatten()
+        }
+
         #[ink(message)]
         pub fn get_common_price(&self) -> Balance {
             self.common_price
@@ -116,19 +216,38 @@ mod azns_fee_calculator {
 
         #[ink(message)]
         pub fn get_price_by_length(&self, len: Length) -> Option<Balance> {
-            self.price_by_length.get(&len)
+            self.price_by_length.get(len)
         }
 
         #[ink(message)]
         pub fn set_max_registration_duration(&mut self, duration: u8) -> Result<()> {
-            self.ensure_admin()?;
+            self.ensure_role(Role::PricingManager)?;
             self.max_registration_duration = duration;
             Ok(())
         }
 
+        #[ink(message)]
+        pub fn set_release_premium(&mut self, config: Option<ReleasePremium>) -> Result<()> {
+            self.ensure_role(Role::PricingManager)?;
+
+            if let Some(config) = &config {
+                let half_life = match config.decay {
+                    PremiumDecay::Exponential { half_life } => half_life,
+                    PremiumDecay::Linear => 1,
+                };
+                ensure!(
+                    config.duration != 0 && half_life != 0,
+                    Error::InvalidPremiumConfig
+                );
+            }
+            self.release_premium.set(&config);
+
+            Ok(())
+        }
+
         #[ink(message)]
         pub fn set_common_price(&mut self, common_price: Balance) -> Result<()> {
-            self.ensure_admin()?;
+            self.ensure_role(Role::PricingManager)?;
 
             if common_price == 0 {
                 return Err(Error::ZeroPrice);
@@ -143,7 +262,7 @@ mod azns_fee_calculator {
             &mut self,
             price_points: Vec<(Length, Option<Balance>)>,
         ) -> Result<()> {
-            self.ensure_admin()?;
+            self.ensure_role(Role::PricingManager)?;
 
             for (length, price) in &price_points {
                 if let Some(price) = price {
@@ -158,6 +277,81 @@ mod azns_fee_calculator {
 
             Ok(())
         }
+
+        /// (ADMIN-OPERATION)
+        /// Grants a role to the given account
+        #[ink(message)]
+        pub fn grant_role(&mut self, role: Role, account: AccountId) -> Result<()> {
+            self.ensure_admin()?;
+
+            if self.roles.grant(role, account) {
+                self.env().emit_event(RoleGranted {
+                    role,
+                    account,
+                    sender: self.env().caller(),
+                });
+            }
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Revokes a role from the given account
+        #[ink(message)]
+        pub fn revoke_role(&mut self, role: Role, account: AccountId) -> Result<()> {
+            self.ensure_admin()?;
+            self.remove_role(role, account);
+            Ok(())
+        }
+
+        /// Gives up a role held by the caller
+        #[ink(message)]
+        pub fn renounce_role(&mut self, role: Role) -> Result<()> {
+            self.remove_role(role, self.env().caller());
+            Ok(())
+        }
+
+        /// @note the admin implicitly holds every role
+        #[ink(message)]
+        pub fn has_role(&self, role: Role, account: AccountId) -> bool {
+            self.roles.holds(self.admin, role, account)
+        }
+
+        /// Upgrades the contract code
+        #[ink(message)]
+        pub fn upgrade_contract(&mut self, code_hash: [u8; 32]) -> Result<()> {
+            self.ensure_role(Role::Upgrader)?;
+            access_control::upgrade_contract(code_hash);
+            Ok(())
+        }
+
+        fn ensure_role(&self, role: Role) -> Result<()> {
+            match self.has_role(role, self.env().caller()) {
+                true => Ok(()),
+                false => Err(Error::MissingRole(role)),
+            }
+        }
+
+        fn remove_role(&mut self, role: Role, account: AccountId) {
+            if self.roles.revoke(role, account) {
+                self.env().emit_event(RoleRevoked {
+                    role,
+                    account,
+                    sender: self.env().caller(),
+                });
+            }
+        }
+
+        // Halves `value` every `half_life`, interpolating linearly in-between
+        fn halve(value: Balance, time: u64, half_life: u64) -> Balance {
+            let halvings = time / half_life;
+            if halvings >= 128 {
+                return 0;
+            }
+
+            let value = value >> halvings;
+            let progress = (time % half_life) as u128;
+            value - (value / 2) * progress / half_life as u128
+        }
     }
 
     #[cfg(test)]
@@ -171,7 +365,7 @@ mod azns_fee_calculator {
         }
 
         fn get_test_fee_calculator() -> FeeCalculator {
-            let contract_addr: AccountId = AccountId::from([0xFF as u8; 32]);
+            let contract_addr: AccountId = AccountId::from([0xFF_u8; 32]);
             set_callee::<DefaultEnvironment>(contract_addr);
             FeeCalculator::new(
                 default_accounts().alice,
@@ -204,7 +398,7 @@ mod azns_fee_calculator {
             let contract = get_test_fee_calculator();
 
             assert_eq!(
-                contract.get_name_price("".to_string(), 1),
+                contract.get_name_price("".to_string(), 1, None),
                 Err(Error::ZeroLength)
             );
 
@@ -212,31 +406,31 @@ mod azns_fee_calculator {
 
             // Duration: 0
             assert_eq!(
-                contract.get_name_price(name.clone(), 0),
+                contract.get_name_price(name.clone(), 0, None),
                 Err(Error::InvalidDuration)
             );
 
             // Duration: 1
             assert_eq!(
-                contract.get_name_price(name.clone(), 1),
+                contract.get_name_price(name.clone(), 1, None),
                 Ok((6_u128 * 10_u128.pow(12), 0))
             );
 
             // Duration: 2
             assert_eq!(
-                contract.get_name_price(name.clone(), 2),
+                contract.get_name_price(name.clone(), 2, None),
                 Ok((6_u128 * 10_u128.pow(12), 6_u128 * 10_u128.pow(12)))
             );
 
             // Duration: 3
             assert_eq!(
-                contract.get_name_price(name.clone(), 3),
+                contract.get_name_price(name.clone(), 3, None),
                 Ok((6_u128 * 10_u128.pow(12), 12_u128 * 10_u128.pow(12)))
             );
 
             // Duration: 4
             assert_eq!(
-                contract.get_name_price(name.clone(), 4),
+                contract.get_name_price(name.clone(), 4, None),
                 Err(Error::InvalidDuration)
             );
         }
@@ -281,6 +475,71 @@ mod azns_fee_calculator {
             );
         }
 
+        #[ink::test]
+        fn release_premium_works() {
+            let mut contract = get_test_fee_calculator();
+            let released_at = 1000;
+            let day = 24 * 60 * 60 * 1000;
+
+            // No premium configured
+            set_block_timestamp::<DefaultEnvironment>(released_at);
+            assert_eq!(contract.get_release_premium(released_at), 0);
+
+            assert_eq!(
+                contract.set_release_premium(Some(ReleasePremium {
+                    start_premium: 1000,
+                    duration: 0,
+                    decay: PremiumDecay::Linear,
+                })),
+                Err(Error::InvalidPremiumConfig)
+            );
+
+            // Linear decay
+            contract
+                .set_release_premium(Some(ReleasePremium {
+                    start_premium: 1000,
+                    duration: 10 * day,
+                    decay: PremiumDecay::Linear,
+                }))
+                .unwrap();
+            assert_eq!(contract.get_release_premium(released_at), 1000);
+
+            set_block_timestamp::<DefaultEnvironment>(released_at + 5 * day);
+            assert_eq!(contract.get_release_premium(released_at), 500);
+            assert_eq!(
+                contract.get_name_price("alice".to_string(), 2, Some(released_at)),
+                Ok((6_u128 * 10_u128.pow(12), 6_u128 * 10_u128.pow(12) + 500))
+            );
+
+            set_block_timestamp::<DefaultEnvironment>(released_at + 10 * day);
+            assert_eq!(contract.get_release_premium(released_at), 0);
+
+            // Exponential decay (offset by the premium left after 10 half-lives)
+            contract
+                .set_release_premium(Some(ReleasePremium {
+                    start_premium: 1_024_000,
+                    duration: 10 * day,
+                    decay: PremiumDecay::Exponential { half_life: day },
+                }))
+                .unwrap();
+
+            set_block_timestamp::<DefaultEnvironment>(released_at);
+            assert_eq!(contract.get_release_premium(released_at), 1_024_000 - 1000);
+
+            set_block_timestamp::<DefaultEnvironment>(released_at + day);
+            assert_eq!(contract.get_release_premium(released_at), 512_000 - 1000);
+
+            set_block_timestamp::<DefaultEnvironment>(released_at + 3 * day / 2);
+            assert_eq!(contract.get_release_premium(released_at), 384_000 - 1000);
+
+            set_block_timestamp::<DefaultEnvironment>(released_at + 10 * day);
+            assert_eq!(contract.get_release_premium(released_at), 0);
+
+            // Premium can be disabled
+            contract.set_release_premium(None).unwrap();
+            assert_eq!(contract.get_release_premium_config(), None);
+        }
+
         #[ink::test]
         fn ownable_2_step_works() {
             let accounts = default_accounts();
@@ -301,19 +560,57 @@ mod azns_fee_calculator {
             let mut contract = get_test_fee_calculator();
             set_caller::<DefaultEnvironment>(default_accounts().bob);
 
+            let missing_role = Err(Error::MissingRole(Role::PricingManager));
+            assert_eq!(contract.set_max_registration_duration(5), missing_role);
+            assert_eq!(contract.set_common_price(100), missing_role);
+            assert_eq!(contract.set_prices_by_length(vec![(3, None)]), missing_role);
+            assert_eq!(contract.set_release_premium(None), missing_role);
             assert_eq!(
-                contract.set_max_registration_duration(5),
+                contract.transfer_ownership(Some(default_accounts().bob)),
                 Err(Error::NotAdmin)
             );
-            assert_eq!(contract.set_common_price(100), Err(Error::NotAdmin));
             assert_eq!(
-                contract.set_prices_by_length(vec![(3, None)]),
+                contract.grant_role(Role::PricingManager, default_accounts().bob),
                 Err(Error::NotAdmin)
             );
             assert_eq!(
-                contract.transfer_ownership(Some(default_accounts().bob)),
-                Err(Error::NotAdmin)
+                contract.upgrade_contract([0u8; 32]),
+                Err(Error::MissingRole(Role::Upgrader))
+            );
+        }
+
+        #[ink::test]
+        fn roles_works() {
+            let accounts = default_accounts();
+            let mut contract = get_test_fee_calculator();
+
+            // Admin holds all the roles initially
+            assert!(Role::ALL
+                .iter()
+                .all(|role| contract.has_role(*role, accounts.alice)));
+
+            contract
+                .grant_role(Role::PricingManager, accounts.bob)
+                .unwrap();
+            assert!(contract.has_role(Role::PricingManager, accounts.bob));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_common_price(100), Ok(()));
+            assert_eq!(contract.renounce_role(Role::PricingManager), Ok(()));
+            assert_eq!(
+                contract.set_common_price(100),
+                Err(Error::MissingRole(Role::PricingManager))
             );
+
+            // Admin holds all the roles implicitly
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract
+                .revoke_role(Role::PricingManager, accounts.alice)
+                .unwrap();
+            assert_eq!(contract.set_common_price(100), Ok(()));
+
+            // Only effective grants & revocations are emitted
+            assert_eq!(ink::env::test::recorded_events().count(), 2);
         }
     }
 }
@@ -1,16 +1,99 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
+// Storage mappings are keyed by tuples
+#![allow(clippy::type_complexity)]
 
-pub use self::merkle_verifier::{MerkleVerifier, MerkleVerifierRef};
+pub use self::merkle_verifier::{Campaign, MerkleVerifier, MerkleVerifierRef};
 
 #[zink::coating(Ownable2Step[
     Error = Error::NotAdmin
 ])]
-#[zink::coating(Upgradable)]
 #[ink::contract]
 mod merkle_verifier {
 
     use ink::env::hash::{CryptoHash, Keccak256};
+    use ink::prelude::string::String;
     use ink::prelude::vec::Vec;
+    use ink::storage::traits::ManualKey;
+    use ink::storage::Mapping;
+    use interfaces::access_control::{self, AccessControl, Role};
+
+    /// Merkle root of a campaign (e.g. whitelist, airdrop) along with its validity window
+    #[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Campaign {
+        pub root: [u8; 32],
+        /// Proofs are rejected before this timestamp (if set)
+        pub start: Option<u64>,
+        /// Proofs are rejected after this timestamp (if set)
+        pub end: Option<u64>,
+        pub description: String,
+    }
+
+    impl Campaign {
+        pub fn is_active(&self, current_time: u64) -> bool {
+            self.start.is_none_or(|start| start <= current_time)
+                && self.end.is_none_or(|end| current_time <= end)
+        }
+    }
+
+    /// Emitted whenever a campaign root is added
+    #[ink(event)]
+    pub struct RootAdded {
+        #[ink(topic)]
+        campaign: String,
+        root: [u8; 32],
+        start: Option<u64>,
+        end: Option<u64>,
+    }
+
+    /// Emitted whenever the root of a campaign is replaced
+    #[ink(event)]
+    pub struct RootRotated {
+        #[ink(topic)]
+        campaign: String,
+        old_root: [u8; 32],
+        new_root: [u8; 32],
+    }
+
+    /// Emitted whenever a campaign root is retired
+    #[ink(event)]
+    pub struct RootRetired {
+        #[ink(topic)]
+        campaign: String,
+    }
+
+    /// Emitted whenever a leaf of a campaign is consumed
+    #[ink(event)]
+    pub struct Claimed {
+        #[ink(topic)]
+        campaign: String,
+        #[ink(topic)]
+        leaf: [u8; 32],
+        consumer: AccountId,
+    }
+
+    /// Emitted whenever a role is granted to an account
+    #[ink(event)]
+    pub struct RoleGranted {
+        #[ink(topic)]
+        role: Role,
+        #[ink(topic)]
+        account: AccountId,
+        sender: AccountId,
+    }
+
+    /// Emitted whenever a role is revoked from (or renounced by) an account
+    #[ink(event)]
+    pub struct RoleRevoked {
+        #[ink(topic)]
+        role: Role,
+        #[ink(topic)]
+        account: AccountId,
+        sender: AccountId,
+    }
 
     #[ink(storage)]
     pub struct MerkleVerifier {
@@ -20,6 +103,14 @@ mod merkle_verifier {
         pending_admin: Option<AccountId>,
         /// Stores the merkle root hash
         root: [u8; 32],
+        /// Roles granted to accounts
+        roles: Mapping<(Role, AccountId), (), ManualKey<100>>,
+        /// Merkle roots of the campaigns
+        campaigns: Mapping<String, Campaign, ManualKey<101>>,
+        /// Leaves of the campaigns that have been consumed
+        claimed: Mapping<(String, [u8; 32]), (), ManualKey<102>>,
+        /// Ids of the retired campaigns, which cannot be reused as their leaves remain claimed
+        retired: Mapping<String, (), ManualKey<103>>,
     }
 
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
@@ -27,6 +118,18 @@ mod merkle_verifier {
     pub enum Error {
         /// Caller not allowed to call privileged calls.
         NotAdmin,
+        /// Caller is missing the role required for the operation
+        MissingRole(Role),
+        /// A campaign with the given id already exists (or existed)
+        CampaignAlreadyExists,
+        /// No campaign found with the given id
+        CampaignNotFound,
+        /// Campaign must not end before it starts
+        InvalidValidityWindow,
+        /// The merkle proof is invalid (or the campaign is inactive)
+        InvalidMerkleProof,
+        /// The leaf has been consumed already
+        AlreadyClaimed,
     }
 
     impl MerkleVerifier {
@@ -36,12 +139,16 @@ mod merkle_verifier {
                 admin,
                 pending_admin: None,
                 root,
+                roles: Default::default(),
+                campaigns: Default::default(),
+                claimed: Default::default(),
+                retired: Default::default(),
             }
         }
 
         #[ink(message)]
         pub fn update_root(&mut self, new_root: [u8; 32]) -> Result<(), Error> {
-            self.ensure_admin()?;
+            self.ensure_role(Role::ReservationManager)?;
             self.root = new_root;
             Ok(())
         }
@@ -56,10 +163,261 @@ mod merkle_verifier {
         // @dev leaf - It's the hashed version of the element
         #[ink(message)]
         pub fn verify_proof(&self, leaf: [u8; 32], proof: Vec<[u8; 32]>) -> bool {
-            let hash = proof
+            Self::process_proof(leaf, &proof) == self.root
+        }
+
+        /// Verifies inclusion of leaf in the merkle tree of a campaign active at present
+        #[ink(message)]
+        pub fn verify_proof_for(
+            &self,
+            campaign: String,
+            leaf: [u8; 32],
+            proof: Vec<[u8; 32]>,
+        ) -> bool {
+            self.get_active_campaign_ref(&campaign)
+                .is_some_and(|c| Self::process_proof(leaf, &proof) == c.root)
+        }
+
+        /// Verifies inclusion of leaf in the merkle tree of an active campaign & marks it as claimed.
+        /// @dev To be called by the consumer contracts sharing the claims of the campaign
+        #[ink(message)]
+        pub fn consume(
+            &mut self,
+            campaign: String,
+            leaf: [u8; 32],
+            proof: Vec<[u8; 32]>,
+        ) -> Result<(), Error> {
+            self.ensure_role(Role::ClaimConsumer)?;
+            if self.is_claimed(campaign.clone(), leaf) {
+                return Err(Error::AlreadyClaimed);
+            }
+            if !self.verify_proof_for(campaign.clone(), leaf, proof) {
+                return Err(Error::InvalidMerkleProof);
+            }
+
+            self.claimed.insert((&campaign, &leaf), &());
+            self.env().emit_event(Claimed {
+                campaign,
+                leaf,
+                consumer: self.env().caller(),
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_claimed(&self, campaign: String, leaf: [u8; 32]) -> bool {
+            self.claimed.contains((campaign, leaf))
+        }
+
+        #[ink(message)]
+        pub fn get_campaign(&self, campaign: String) -> Option<Campaign> {
+            self.campaigns.get(campaign)
+        }
+
+        /// Adds the merkle root of a new campaign, valid between the (optional) start & end timestamps
+        #[ink(message)]
+        pub fn add_root(
+            &mut self,
+            campaign: String,
+            root: [u8; 32],
+            start: Option<u64>,
+            end: Option<u64>,
+            description: String,
+        ) -> Result<(), Error> {
+            self.ensure_role(Role::ReservationManager)?;
+            if self.campaigns.contains(&campaign) || self.retired.contains(&campaign) {
+                return Err(Error::CampaignAlreadyExists);
+            }
+            if let (Some(start), Some(end)) = (start, end) {
+                if end < start {
+                    return Err(Error::InvalidValidityWindow);
+                }
+            }
+
+            let data = Campaign {
+                root,
+                start,
+                end,
+                description,
+            };
+            self.campaigns.insert(&campaign, &data);
+
+            self.env().emit_event(RootAdded {
+                campaign,
+                root,
+                start,
+                end,
+            });
+            Ok(())
+        }
+
+        /// Replaces the merkle root of a campaign, keeping its validity window
+        #[ink(message)]
+        pub fn rotate_root(&mut self, campaign: String, new_root: [u8; 32]) -> Result<(), Error> {
+            self.ensure_role(Role::ReservationManager)?;
+            let mut data = self
+                .campaigns
+                .get(&campaign)
+                .ok_or(Error::CampaignNotFound)?;
+
+            let old_root = data.root;
+            data.root = new_root;
+            self.campaigns.insert(&campaign, &data);
+
+            self.env().emit_event(RootRotated {
+                campaign,
+                old_root,
+                new_root,
+            });
+            Ok(())
+        }
+
+        /// Removes a campaign, after which none of its proofs are valid
+        /// @note the id of a retired campaign cannot be reused
+        #[ink(message)]
+        pub fn retire_root(&mut self, campaign: String) -> Result<(), Error> {
+            self.ensure_role(Role::ReservationManager)?;
+            if !self.campaigns.contains(&campaign) {
+                return Err(Error::CampaignNotFound);
+            }
+
+            self.campaigns.remove(&campaign);
+            self.retired.insert(&campaign, &());
+            self.env().emit_event(RootRetired { campaign });
+            Ok(())
+        }
+
+        /// Verifies inclusion of multiple leaves in the merkle tree at once
+        /// @dev Compatible with OpenZeppelin's `MerkleProof.multiProofVerify()`, i.e. the
+        /// `proof_flags` tell whether each hash combines two known nodes (leaves or computed hashes)
+        /// or a known node with the next `proof` element. Leaves must be ordered as in the tree.
+        #[ink(message)]
+        pub fn verify_multiproof(
+            &self,
+            leaves: Vec<[u8; 32]>,
+            proof: Vec<[u8; 32]>,
+            proof_flags: Vec<bool>,
+        ) -> bool {
+            Self::process_multiproof(&leaves, &proof, &proof_flags) == Some(self.root)
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Grants a role to the given account
+        #[ink(message)]
+        pub fn grant_role(&mut self, role: Role, account: AccountId) -> Result<(), Error> {
+            self.ensure_admin()?;
+
+            if self.roles.grant(role, account) {
+                self.env().emit_event(RoleGranted {
+                    role,
+                    account,
+                    sender: self.env().caller(),
+                });
+            }
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Revokes a role from the given account
+        #[ink(message)]
+        pub fn revoke_role(&mut self, role: Role, account: AccountId) -> Result<(), Error> {
+            self.ensure_admin()?;
+            self.remove_role(role, account);
+            Ok(())
+        }
+
+        /// Gives up a role held by the caller
+        #[ink(message)]
+        pub fn renounce_role(&mut self, role: Role) -> Result<(), Error> {
+            self.remove_role(role, self.env().caller());
+            Ok(())
+        }
+
+        /// @note the admin implicitly holds every role
+        #[ink(message)]
+        pub fn has_role(&self, role: Role, account: AccountId) -> bool {
+            self.roles.holds(self.admin, role, account)
+        }
+
+        /// Upgrades the contract code
+        #[ink(message)]
+        pub fn upgrade_contract(&mut self, code_hash: [u8; 32]) -> Result<(), Error> {
+            self.ensure_role(Role::Upgrader)?;
+            access_control::upgrade_contract(code_hash);
+            Ok(())
+        }
+
+        fn ensure_role(&self, role: Role) -> Result<(), Error> {
+            match self.has_role(role, self.env().caller()) {
+                true => Ok(()),
+                false => Err(Error::MissingRole(role)),
+            }
+        }
+
+        fn remove_role(&mut self, role: Role, account: AccountId) {
+            if self.roles.revoke(role, account) {
+                self.env().emit_event(RoleRevoked {
+                    role,
+                    account,
+                    sender: self.env().caller(),
+                });
+            }
+        }
+
+        fn get_active_campaign_ref(&self, campaign: &str) -> Option<Campaign> {
+            self.campaigns
+                .get(campaign)
+                .filter(|c| c.is_active(self.env().block_timestamp()))
+        }
+
+        // Returns the root reconstructed from the leaf & its proof
+        fn process_proof(leaf: [u8; 32], proof: &[[u8; 32]]) -> [u8; 32] {
+            proof
                 .iter()
-                .fold(leaf, |acc, node| Self::compute_hash(&acc, &node));
-            hash == self.root
+                .fold(leaf, |acc, node| Self::compute_hash(&acc, node))
+        }
+
+        // Returns the root reconstructed from the leaves & the multiproof (if well-formed)
+        fn process_multiproof(
+            leaves: &[[u8; 32]],
+            proof: &[[u8; 32]],
+            proof_flags: &[bool],
+        ) -> Option<[u8; 32]> {
+            let total_hashes = proof_flags.len();
+            if leaves.len() + proof.len() != total_hashes + 1 {
+                return None;
+            }
+
+            let mut hashes = Vec::with_capacity(total_hashes);
+            let (mut leaf_pos, mut hash_pos, mut proof_pos) = (0, 0, 0);
+
+            for &flag in proof_flags {
+                let mut next_node = |hashes: &Vec<[u8; 32]>| {
+                    if leaf_pos < leaves.len() {
+                        leaf_pos += 1;
+                        Some(leaves[leaf_pos - 1])
+                    } else {
+                        hash_pos += 1;
+                        hashes.get(hash_pos - 1).copied()
+                    }
+                };
+
+                let a = next_node(&hashes)?;
+                let b = match flag {
+                    true => next_node(&hashes)?,
+                    false => {
+                        proof_pos += 1;
+                        *proof.get(proof_pos - 1)?
+                    }
+                };
+                hashes.push(Self::compute_hash(&a, &b));
+            }
+
+            match (hashes.last(), leaves.first()) {
+                (Some(&root), _) => (proof_pos == proof.len()).then_some(root),
+                (None, Some(&leaf)) => Some(leaf),
+                (None, None) => proof.first().copied(),
+            }
         }
 
         // Sorts the node and then returns their Keccak256 hash
@@ -133,7 +491,19 @@ mod merkle_verifier {
             ink::env::test::set_caller::<DefaultEnvironment>(accounts.bob);
 
             let new_root = [0x00; 32];
-            assert_eq!(merkle_verifier.update_root(new_root), Err(Error::NotAdmin));
+            assert_eq!(
+                merkle_verifier.update_root(new_root),
+                Err(Error::MissingRole(Role::ReservationManager))
+            );
+
+            // Verify update_root works once the role is granted
+            ink::env::test::set_caller::<DefaultEnvironment>(accounts.alice);
+            merkle_verifier
+                .grant_role(Role::ReservationManager, accounts.bob)
+                .unwrap();
+
+            ink::env::test::set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(merkle_verifier.update_root(new_root), Ok(()));
         }
 
         // Test that the param ordering should not matter
@@ -192,12 +562,210 @@ mod merkle_verifier {
             // Case 1: Construct an invalid proof
             let proof = vec![leaves[0], internal_nodes[0]];
             let res = merkle_verifier.verify_proof(leaf, proof);
-            assert_eq!(res, false);
+            assert!(!res);
 
             // Case 2: Construct a valid proof
             let proof = vec![leaves[0], internal_nodes[1]];
             let res = merkle_verifier.verify_proof(leaf, proof);
-            assert_eq!(res, true);
+            assert!(res);
+        }
+
+        #[ink::test]
+        fn verify_multiproof_works() {
+            // Same tree as in `verify_proof_works()`
+            let leaves: Vec<[u8; 32]> = ["a", "b", "c", "d"]
+                .iter()
+                .map(|x| {
+                    let mut output = [0u8; 32];
+                    Sha2x256::hash(x.as_bytes(), &mut output);
+                    output
+                })
+                .collect();
+
+            let internal_nodes = [
+                MerkleVerifier::compute_hash(&leaves[0], &leaves[1]),
+                MerkleVerifier::compute_hash(&leaves[2], &leaves[3]),
+            ];
+            let root = MerkleVerifier::compute_hash(&internal_nodes[0], &internal_nodes[1]);
+
+            let alice = default_accounts::<DefaultEnvironment>().alice;
+            let merkle_verifier = MerkleVerifier::new(alice, root);
+
+            // Case 1: Prove A & B with H(CD)
+            let res = merkle_verifier.verify_multiproof(
+                vec![leaves[0], leaves[1]],
+                vec![internal_nodes[1]],
+                vec![true, false],
+            );
+            assert!(res);
+
+            // Case 2: Prove all the leaves without any proof
+            let res = merkle_verifier.verify_multiproof(leaves.clone(), vec![], vec![true; 3]);
+            assert!(res);
+
+            // Case 3: Prove a single leaf (same as `verify_proof()`)
+            let res = merkle_verifier.verify_multiproof(
+                vec![leaves[1]],
+                vec![leaves[0], internal_nodes[1]],
+                vec![false, false],
+            );
+            assert!(res);
+
+            // Case 4: Invalid leaf
+            let res = merkle_verifier.verify_multiproof(
+                vec![leaves[0], leaves[2]],
+                vec![internal_nodes[1]],
+                vec![true, false],
+            );
+            assert!(!res);
+
+            // Case 5: Malformed proofs
+            let res = merkle_verifier.verify_multiproof(
+                vec![leaves[0], leaves[1]],
+                vec![internal_nodes[1]],
+                vec![true, true],
+            );
+            assert!(!res);
+            let res = merkle_verifier.verify_multiproof(
+                vec![leaves[0], leaves[1]],
+                vec![internal_nodes[1], internal_nodes[0]],
+                vec![true, false, true],
+            );
+            assert!(!res);
+        }
+
+        #[ink::test]
+        fn campaigns_work() {
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut merkle_verifier = MerkleVerifier::new(accounts.alice, [0xff; 32]);
+            let campaign = "airdrop".to_string();
+
+            let leaves = [[0x01; 32], [0x02; 32]];
+            let root = MerkleVerifier::compute_hash(&leaves[0], &leaves[1]);
+
+            // Only the reservation managers can manage the roots
+            ink::env::test::set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                merkle_verifier.add_root(campaign.clone(), root, None, None, String::new()),
+                Err(Error::MissingRole(Role::ReservationManager))
+            );
+
+            ink::env::test::set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(
+                merkle_verifier.add_root(campaign.clone(), root, Some(20), Some(10), String::new()),
+                Err(Error::InvalidValidityWindow)
+            );
+            assert_eq!(
+                merkle_verifier.add_root(
+                    campaign.clone(),
+                    root,
+                    Some(10),
+                    Some(20),
+                    "Airdrop".to_string()
+                ),
+                Ok(())
+            );
+            assert_eq!(
+                merkle_verifier.add_root(campaign.clone(), root, None, None, String::new()),
+                Err(Error::CampaignAlreadyExists)
+            );
+            assert_eq!(
+                merkle_verifier.get_campaign(campaign.clone()),
+                Some(Campaign {
+                    root,
+                    start: Some(10),
+                    end: Some(20),
+                    description: "Airdrop".to_string(),
+                })
+            );
+
+            // Proofs are only valid within the validity window
+            let verify = |verifier: &MerkleVerifier| {
+                verifier.verify_proof_for(campaign.clone(), leaves[0], vec![leaves[1]])
+            };
+            assert!(!verify(&merkle_verifier));
+            ink::env::test::set_block_timestamp::<DefaultEnvironment>(10);
+            assert!(verify(&merkle_verifier));
+            assert!(!merkle_verifier.verify_proof(leaves[0], vec![leaves[1]]));
+            ink::env::test::set_block_timestamp::<DefaultEnvironment>(21);
+            assert!(!verify(&merkle_verifier));
+
+            ink::env::test::set_block_timestamp::<DefaultEnvironment>(15);
+            assert_eq!(
+                merkle_verifier.rotate_root(campaign.clone(), [0x00; 32]),
+                Ok(())
+            );
+            assert!(!verify(&merkle_verifier));
+            assert_eq!(merkle_verifier.rotate_root(campaign.clone(), root), Ok(()));
+            assert!(verify(&merkle_verifier));
+
+            assert_eq!(merkle_verifier.retire_root(campaign.clone()), Ok(()));
+            assert!(!verify(&merkle_verifier));
+            assert_eq!(merkle_verifier.get_campaign(campaign.clone()), None);
+            assert_eq!(
+                merkle_verifier.retire_root(campaign.clone()),
+                Err(Error::CampaignNotFound)
+            );
+            assert_eq!(
+                merkle_verifier.rotate_root(campaign.clone(), root),
+                Err(Error::CampaignNotFound)
+            );
+
+            // Retired ids cannot be reused
+            assert_eq!(
+                merkle_verifier.add_root(campaign, root, None, None, String::new()),
+                Err(Error::CampaignAlreadyExists)
+            );
+            assert_eq!(ink::env::test::recorded_events().count(), 4);
+        }
+
+        #[ink::test]
+        fn consume_works() {
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut merkle_verifier = MerkleVerifier::new(accounts.alice, [0xff; 32]);
+            let campaign = "airdrop".to_string();
+
+            let leaves = [[0x01; 32], [0x02; 32]];
+            let root = MerkleVerifier::compute_hash(&leaves[0], &leaves[1]);
+            merkle_verifier
+                .add_root(campaign.clone(), root, None, None, String::new())
+                .unwrap();
+
+            // Only the authorized consumers can consume leaves
+            ink::env::test::set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                merkle_verifier.consume(campaign.clone(), leaves[0], vec![leaves[1]]),
+                Err(Error::MissingRole(Role::ClaimConsumer))
+            );
+
+            ink::env::test::set_caller::<DefaultEnvironment>(accounts.alice);
+            merkle_verifier
+                .grant_role(Role::ClaimConsumer, accounts.bob)
+                .unwrap();
+
+            ink::env::test::set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                merkle_verifier.consume(campaign.clone(), leaves[0], vec![leaves[0]]),
+                Err(Error::InvalidMerkleProof)
+            );
+            assert_eq!(
+                merkle_verifier.consume("other".to_string(), leaves[0], vec![leaves[1]]),
+                Err(Error::InvalidMerkleProof)
+            );
+            assert!(!merkle_verifier.is_claimed(campaign.clone(), leaves[0]));
+
+            assert_eq!(
+                merkle_verifier.consume(campaign.clone(), leaves[0], vec![leaves[1]]),
+                Ok(())
+            );
+            assert!(merkle_verifier.is_claimed(campaign.clone(), leaves[0]));
+            assert!(!merkle_verifier.is_claimed(campaign.clone(), leaves[1]));
+
+            // A leaf can be consumed only once
+            assert_eq!(
+                merkle_verifier.consume(campaign, leaves[0], vec![leaves[1]]),
+                Err(Error::AlreadyClaimed)
+            );
         }
 
         #[ink::test]
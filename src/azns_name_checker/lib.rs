@@ -43,17 +43,38 @@ impl UnicodeRange {
 #[zink::coating(Ownable2Step[
     Error = Error::NotAdmin
 ])]
-#[zink::coating(Upgradable)]
 #[ink::contract]
 mod azns_name_checker {
     use crate::UnicodeRange;
     use ink::prelude::string::String;
-    use ink::prelude::vec;
     use ink::prelude::vec::Vec;
+    use ink::storage::traits::ManualKey;
+    use ink::storage::Mapping;
+    use interfaces::access_control::{self, AccessControl, Role};
 
     type Min = u8;
     type Max = u8;
 
+    /// Emitted whenever a role is granted to an account
+    #[ink(event)]
+    pub struct RoleGranted {
+        #[ink(topic)]
+        role: Role,
+        #[ink(topic)]
+        account: AccountId,
+        sender: AccountId,
+    }
+
+    /// Emitted whenever a role is revoked from (or renounced by) an account
+    #[ink(event)]
+    pub struct RoleRevoked {
+        #[ink(topic)]
+        role: Role,
+        #[ink(topic)]
+        account: AccountId,
+        sender: AccountId,
+    }
+
     #[ink(storage)]
     pub struct NameChecker {
         admin: AccountId,
@@ -61,6 +82,8 @@ mod azns_name_checker {
         allowed_length: (Min, Max),
         allowed_unicode_ranges: Vec<UnicodeRange>,
         disallowed_unicode_ranges_for_edges: Vec<UnicodeRange>,
+        /// Roles granted to accounts
+        roles: Mapping<(Role, AccountId), (), ManualKey<100>>,
     }
 
     pub type Result<T> = core::result::Result<T, Error>;
@@ -75,6 +98,8 @@ mod azns_name_checker {
         TooLong,
         ContainsDisallowedCharacters,
         InvalidRange,
+        /// Caller is missing the role required for the operation
+        MissingRole(Role),
     }
 
     impl NameChecker {
@@ -91,6 +116,7 @@ mod azns_name_checker {
                 allowed_unicode_ranges: Default::default(),
                 allowed_length: Default::default(),
                 disallowed_unicode_ranges_for_edges: Default::default(),
+                roles: Default::default(),
             };
 
             contract
@@ -119,9 +145,9 @@ mod azns_name_checker {
             }
 
             /* Check edges */
-            let edges = vec![
+            let edges = [
                 name.chars().next().unwrap(),
-                name.chars().rev().next().unwrap(),
+                name.chars().next_back().unwrap(),
             ];
 
             let illegal_edges = edges.iter().any(|char| {
@@ -172,7 +198,7 @@ mod azns_name_checker {
 
         #[ink(message)]
         pub fn set_allowed_unicode_ranges(&mut self, new_ranges: Vec<UnicodeRange>) -> Result<()> {
-            self.ensure_admin()?;
+            self.ensure_role(Role::ReservationManager)?;
 
             if !new_ranges.iter().all(UnicodeRange::is_valid) {
                 return Err(Error::InvalidRange);
@@ -186,7 +212,7 @@ mod azns_name_checker {
             &mut self,
             new_ranges: Vec<UnicodeRange>,
         ) -> Result<()> {
-            self.ensure_admin()?;
+            self.ensure_role(Role::ReservationManager)?;
 
             if new_ranges.iter().any(|rng| rng.lower > rng.upper) {
                 return Err(Error::InvalidRange);
@@ -197,7 +223,7 @@ mod azns_name_checker {
 
         #[ink(message)]
         pub fn set_allowed_length(&mut self, new_length: (Min, Max)) -> Result<()> {
-            self.ensure_admin()?;
+            self.ensure_role(Role::ReservationManager)?;
 
             if new_length.0 == 0 || new_length.0 > new_length.1 {
                 return Err(Error::InvalidRange);
@@ -205,6 +231,69 @@ mod azns_name_checker {
             self.allowed_length = new_length;
             Ok(())
         }
+
+        /// (ADMIN-OPERATION)
+        /// Grants a role to the given account
+        #[ink(message)]
+        pub fn grant_role(&mut self, role: Role, account: AccountId) -> Result<()> {
+            self.ensure_admin()?;
+
+            if self.roles.grant(role, account) {
+                self.env().emit_event(RoleGranted {
+                    role,
+                    account,
+                    sender: self.env().caller(),
+                });
+            }
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Revokes a role from the given account
+        #[ink(message)]
+        pub fn revoke_role(&mut self, role: Role, account: AccountId) -> Result<()> {
+            self.ensure_admin()?;
+            self.remove_role(role, account);
+            Ok(())
+        }
+
+        /// Gives up a role held by the caller
+        #[ink(message)]
+        pub fn renounce_role(&mut self, role: Role) -> Result<()> {
+            self.remove_role(role, self.env().caller());
+            Ok(())
+        }
+
+        /// @note the admin implicitly holds every role
+        #[ink(message)]
+        pub fn has_role(&self, role: Role, account: AccountId) -> bool {
+            self.roles.holds(self.admin, role, account)
+        }
+
+        /// Upgrades the contract code
+        #[ink(message)]
+        pub fn upgrade_contract(&mut self, code_hash: [u8; 32]) -> Result<()> {
+            self.ensure_role(Role::Upgrader)?;
+            access_control::upgrade_contract(code_hash);
+            Ok(())
+        }
+
+        fn ensure_role(&self, role: Role) -> Result<()> {
+            match self.has_role(role, self.env().caller()) {
+                true => Ok(()),
+                false => Err(Error::MissingRole(role)),
+            }
+        }
+
+        fn remove_role(&mut self, role: Role, account: AccountId) {
+            if self.roles.revoke(role, account) {
+                self.env().emit_event(RoleRevoked {
+                    role,
+                    account,
+                    sender: self.env().caller(),
+                });
+            }
+        }
     }
 }
 
@@ -216,6 +305,7 @@ mod tests {
     use ink::env::test::default_accounts;
     use ink::env::DefaultEnvironment;
     use ink::prelude::string::String;
+    use interfaces::access_control::Role;
 
     #[ink::test]
     fn checks_length() {
@@ -380,6 +470,34 @@ mod tests {
         assert_eq!(contract.get_admin(), accounts.bob);
     }
 
+    #[ink::test]
+    fn roles_works() {
+        let accounts = default_accounts::<DefaultEnvironment>();
+        let mut contract = NameChecker::new(accounts.alice, (2, 5), vec![], vec![]);
+
+        ink::env::test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(
+            contract.set_allowed_length((1, 5)),
+            Err(Error::MissingRole(Role::ReservationManager))
+        );
+        assert_eq!(
+            contract.grant_role(Role::ReservationManager, accounts.bob),
+            Err(Error::NotAdmin)
+        );
+
+        ink::env::test::set_caller::<DefaultEnvironment>(accounts.alice);
+        contract
+            .grant_role(Role::ReservationManager, accounts.bob)
+            .unwrap();
+
+        ink::env::test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(contract.set_allowed_length((1, 5)), Ok(()));
+        assert_eq!(contract.get_allowed_length(), (1, 5));
+        assert_eq!(contract.renounce_role(Role::ReservationManager), Ok(()));
+        assert!(!contract.has_role(Role::ReservationManager, accounts.bob));
+        assert_eq!(ink::env::test::recorded_events().count(), 2);
+    }
+
     #[ink::test]
     #[should_panic(expected = "invalid allowed-unicode-range(s)")]
     fn banned_characters_disallowed() {
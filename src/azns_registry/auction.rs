@@ -0,0 +1,38 @@
+use ink::primitives::AccountId;
+
+type Balance = u128;
+
+/// An English auction of a reserved or premium name
+#[derive(scale::Encode, scale::Decode, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout, Debug)
+)]
+pub struct Auction {
+    /// Minimum amount of the first bid
+    pub start_price: Balance,
+    /// Minimum amount by which a bid has to exceed the highest bid
+    pub min_increment: Balance,
+    /// Timestamp after which no more bids are accepted
+    pub end_time: u64,
+    /// Bids placed within this window before `end_time` extend the auction by the same window
+    pub extension: u64,
+    /// Number of years the name is registered to the winner for
+    pub years_to_register: u8,
+    /// Highest bidder and the escrowed amount of their bid
+    pub highest_bid: Option<(AccountId, Balance)>,
+}
+
+impl Auction {
+    /// Returns the minimum amount the next bid has to match
+    pub fn min_bid(&self) -> Balance {
+        match self.highest_bid {
+            Some((_, amount)) => amount + self.min_increment,
+            None => self.start_price,
+        }
+    }
+
+    pub fn has_ended(&self, current_time: u64) -> bool {
+        current_time > self.end_time
+    }
+}
@@ -0,0 +1,45 @@
+/// Coin type of a chain as per SLIP-0044 (and ENSIP-11 for EVM-compatible chains)
+pub type CoinType = u32;
+
+pub const COIN_TYPE_BTC: CoinType = 0;
+pub const COIN_TYPE_LTC: CoinType = 2;
+pub const COIN_TYPE_DOGE: CoinType = 3;
+pub const COIN_TYPE_ETH: CoinType = 60;
+pub const COIN_TYPE_DOT: CoinType = 354;
+pub const COIN_TYPE_KSM: CoinType = 434;
+
+/// EVM-compatible chains are identified by `EVM_COIN_TYPE_FLAG | chain_id` (ENSIP-11)
+pub const EVM_COIN_TYPE_FLAG: CoinType = 0x8000_0000;
+
+/// Address formats supported by the multi-chain address records
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum AddressFormat {
+    /// 32-byte AccountId of Substrate-based chains
+    Substrate,
+    /// 20-byte address of Ethereum & EVM-compatible chains
+    Evm,
+    /// scriptPubkey of Bitcoin-like chains (P2PKH, P2SH, P2WPKH, P2WSH, P2TR)
+    Bitcoin,
+}
+
+impl AddressFormat {
+    pub fn from_coin_type(coin_type: CoinType) -> Option<Self> {
+        match coin_type {
+            COIN_TYPE_BTC | COIN_TYPE_LTC | COIN_TYPE_DOGE => Some(Self::Bitcoin),
+            COIN_TYPE_ETH => Some(Self::Evm),
+            COIN_TYPE_DOT | COIN_TYPE_KSM => Some(Self::Substrate),
+            _ if coin_type & EVM_COIN_TYPE_FLAG != 0 => Some(Self::Evm),
+            _ => None,
+        }
+    }
+
+    pub fn is_valid_address(&self, address: &[u8]) -> bool {
+        match self {
+            Self::Substrate => address.len() == 32,
+            Self::Evm => address.len() == 20,
+            // P2WPKH (22 bytes) is the shortest, P2WSH & P2TR (34 bytes) are the longest
+            Self::Bitcoin => (22..=34).contains(&address.len()),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use ink::prelude::vec::Vec;
+
+/// Phases of a TLD launch
+#[derive(scale::Encode, scale::Decode, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout, Debug)
+)]
+pub enum LaunchPhase {
+    /// Reserved names are claimed by their designated accounts
+    ReservedClaim,
+    /// Only the accounts of the whitelist (see `start_whitelist_phase()`) register names
+    Allowlist,
+    /// Anyone registers names
+    PublicSale,
+}
+
+/// Registration entry points a phase can allow
+#[derive(scale::Encode, scale::Decode, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout, Debug)
+)]
+pub enum EntryPoint {
+    /// `claim_reserved_name()`
+    ClaimReservedName,
+    /// `register()`, `register_v2()`, `register_on_behalf_of()`, `reveal_and_register()` & `register_with_token()`
+    Register,
+    /// `batch_register()`
+    BatchRegister,
+    /// `register_with_voucher()`
+    RegisterWithVoucher,
+}
+
+/// A phase of the launch schedule
+#[derive(scale::Encode, scale::Decode, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout, Debug)
+)]
+pub struct PhaseConfig {
+    pub phase: LaunchPhase,
+    /// Timestamp at which the phase starts (and the previous one ends)
+    pub start: u64,
+    /// Multiplier applied to the base price of names during the phase (in basis points), premiums excluded
+    pub price_multiplier: u32,
+    /// Entry points open during the phase
+    pub allowed: Vec<EntryPoint>,
+}
+
+impl PhaseConfig {
+    pub fn allows(&self, entry: EntryPoint) -> bool {
+        self.allowed.contains(&entry)
+    }
+}
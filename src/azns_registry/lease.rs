@@ -0,0 +1,63 @@
+use crate::chain_address::CoinType;
+use ink::prelude::string::String;
+use ink::prelude::vec::Vec;
+use ink::primitives::AccountId;
+
+type Balance = u128;
+
+/// A time-bounded lease of a name's control to a tenant
+#[derive(scale::Encode, scale::Decode, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout, Debug)
+)]
+pub struct Lease {
+    /// Account that becomes the controller & resolved address during the lease
+    pub tenant: AccountId,
+    /// Timestamp after which the control reverts to the owner
+    pub until: u64,
+    /// Rent paid by the tenant to the owner
+    pub price: Balance,
+    /// Whether the tenant has accepted (and paid for) the lease
+    pub started: bool,
+    /// State of the name when the lease started, restored once it ends
+    pub snapshot: Option<LeaseSnapshot>,
+}
+
+/// Controller, resolved address & records of a name taken over by a tenant
+#[derive(scale::Encode, scale::Decode, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout, Debug)
+)]
+pub struct LeaseSnapshot {
+    pub controller: AccountId,
+    pub resolved: AccountId,
+    pub records: Vec<(String, String)>,
+    pub chain_addresses: Vec<(CoinType, Vec<u8>)>,
+}
+
+impl Lease {
+    pub fn new(tenant: AccountId, until: u64, price: Balance) -> Self {
+        Self {
+            tenant,
+            until,
+            price,
+            started: false,
+            snapshot: None,
+        }
+    }
+
+    pub fn start(&mut self, snapshot: LeaseSnapshot) {
+        self.started = true;
+        self.snapshot = Some(snapshot);
+    }
+
+    pub fn is_active(&self, current_time: u64) -> bool {
+        self.started && current_time <= self.until
+    }
+
+    pub fn has_ended(&self, current_time: u64) -> bool {
+        self.started && current_time > self.until
+    }
+}
@@ -1,20 +1,46 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
+// Storage mappings are keyed by tuples
+#![allow(clippy::type_complexity)]
 
 mod address_dict;
+mod auction;
+mod chain_address;
+mod launch_phase;
+mod lease;
+mod listing;
+mod meta_tx;
+#[cfg(test)]
+mod mock_psp22;
+mod offer;
+mod refund_policy;
+mod voucher;
+mod whitelist;
 
 #[zink::coating(Ownable2Step[
     Error = Error::NotAdmin
 ])]
-#[zink::coating(Upgradable)]
 #[ink::contract]
 mod azns_registry {
     use crate::address_dict::AddressDict;
+    use crate::auction::Auction;
+    use crate::chain_address::{AddressFormat, CoinType};
+    use crate::launch_phase::{EntryPoint, LaunchPhase, PhaseConfig};
+    use crate::lease::{Lease, LeaseSnapshot};
+    use crate::listing::Listing;
+    use crate::meta_tx::MetaAction;
+    use crate::offer::Offer;
+    use crate::refund_policy::RefundPolicy;
+    use crate::voucher::Voucher;
+    use crate::whitelist::WhitelistQuota;
     use ink::env::call::FromAccountId;
+    use ink::env::hash::{Blake2x256, Keccak256};
     use ink::prelude::string::{String, ToString};
     use ink::prelude::vec::Vec;
     use ink::storage::traits::ManualKey;
     use ink::storage::{Lazy, Mapping};
+    use interfaces::access_control::{self, AccessControl, Role};
     use interfaces::art_zero_traits::*;
+    use interfaces::psp22::PSP22Error;
     use interfaces::psp34_standard::*;
 
     use azns_fee_calculator::FeeCalculatorRef;
@@ -29,7 +55,21 @@ mod azns_registry {
     // 1st Oct 2024 00:00 (UTC)
     pub const GRACE_TIMESTAMP: u64 = 1727740800000;
 
+    // Denominator of the marketplace fee (in basis points)
+    pub const MAX_BPS: u16 = 10_000;
+
+    // Default (min, max) age of a commitment before it can be revealed (in milliseconds)
+    pub const DEFAULT_COMMITMENT_AGE: (u64, u64) = (60 * 1000, 24 * 60 * 60 * 1000);
+
     pub type Result<T> = core::result::Result<T, Error>;
+    /// A name along with its registration period
+    pub type NameWithPeriod = (String, (u64, u64));
+    /// Slot of the next page within the given collection of an address' names
+    pub type NamesCursor = (NameCollection, u128);
+    /// Minimum number of referrals along with the referral rate (in basis points)
+    pub type ReferralTier = (u128, u16);
+    /// Beneficiary of the protocol revenue along with its share (in basis points)
+    pub type RevenueShare = (AccountId, u16);
 
     /// Different states of a name
     #[derive(scale::Encode, scale::Decode)]
@@ -43,6 +83,37 @@ mod azns_registry {
         Available,
         /// Name has invalid characters/length
         Unavailable,
+        /// Name has expired, but can still be renewed by its previous owner until the given timestamp
+        Redemption(AccountId, u64),
+        /// Name has been released recently and is available at the given (decaying) premium
+        Premium(Balance),
+        /// Name is registered with the given AddressDict & its control is leased as per the given Lease
+        Leased(AddressDict, Lease),
+    }
+
+    /// Collections of names related to an address
+    #[derive(scale::Encode, scale::Decode, Clone, Copy, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum NameCollection {
+        Owned,
+        Controlled,
+        Resolving,
+    }
+
+    /// Groups of state-changing operations that can be paused independently
+    #[derive(scale::Encode, scale::Decode, Clone, Copy, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum PauseGroup {
+        /// Registration of names & subnames (incl. claims and auction settlements)
+        Registration,
+        /// Renewal of names & subnames
+        Renewal,
+        /// Transfer of names (incl. marketplace sales)
+        Transfers,
+        /// Updates to the address, controller & records of names
+        Records,
+        /// Listings, offers & auction bids
+        Marketplace,
     }
 
     /// Emitted whenever a new name is registered.
@@ -69,6 +140,22 @@ mod azns_registry {
         forwarded_referrer_fee: Balance,
     }
 
+    /// Same as `FeeReceived`, for fees paid in a PSP22 token
+    #[ink(event)]
+    pub struct TokenFeeReceived {
+        #[ink(topic)]
+        name: String,
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        referrer: Option<String>,
+        referrer_addr: Option<AccountId>,
+        received_fee: Balance,
+        forwarded_referrer_fee: Balance,
+        #[ink(topic)]
+        token: AccountId,
+    }
+
     /// Emitted whenever a name is released
     #[ink(event)]
     pub struct Release {
@@ -78,6 +165,37 @@ mod azns_registry {
         from: AccountId,
     }
 
+    /// Emitted whenever the release of a name is refunded
+    #[ink(event)]
+    pub struct ReleaseRefunded {
+        #[ink(topic)]
+        name: String,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+        as_credit: bool,
+    }
+
+    /// Emitted whenever a discount voucher is redeemed
+    #[ink(event)]
+    pub struct VoucherRedeemed {
+        #[ink(topic)]
+        name: String,
+        #[ink(topic)]
+        recipient: AccountId,
+        nonce: u128,
+    }
+
+    /// Emitted whenever a relayer executes an action on behalf of its signer
+    #[ink(event)]
+    pub struct MetaTxExecuted {
+        #[ink(topic)]
+        signer: AccountId,
+        #[ink(topic)]
+        relayer: AccountId,
+        nonce: u128,
+    }
+
     /// Emitted whenever an address changes.
     #[ink(event)]
     pub struct SetAddress {
@@ -102,6 +220,17 @@ mod azns_registry {
         new_controller: AccountId,
     }
 
+    /// Emitted whenever a multi-chain address changes.
+    #[ink(event)]
+    pub struct SetChainAddress {
+        #[ink(topic)]
+        name: String,
+        from: AccountId,
+        #[ink(topic)]
+        coin_type: CoinType,
+        new_address: Option<Vec<u8>>,
+    }
+
     #[ink(event)]
     pub struct SetPrimaryName {
         #[ink(topic)]
@@ -181,6 +310,187 @@ mod azns_registry {
         new_expiry: u64,
     }
 
+    /// Emitted whenever a name is listed for sale
+    #[ink(event)]
+    pub struct Listed {
+        #[ink(topic)]
+        name: String,
+        #[ink(topic)]
+        seller: AccountId,
+        price: Balance,
+        expiry: u64,
+    }
+
+    /// Emitted whenever a listing is cancelled by the seller
+    #[ink(event)]
+    pub struct ListingCancelled {
+        #[ink(topic)]
+        name: String,
+    }
+
+    /// Emitted whenever an owner offers to lease a name
+    #[ink(event)]
+    pub struct LeaseOffered {
+        #[ink(topic)]
+        name: String,
+        #[ink(topic)]
+        tenant: AccountId,
+        until: u64,
+        price: Balance,
+    }
+
+    /// Emitted whenever a tenant accepts a lease & takes over the control of the name
+    #[ink(event)]
+    pub struct LeaseStarted {
+        #[ink(topic)]
+        name: String,
+        #[ink(topic)]
+        tenant: AccountId,
+        until: u64,
+        price: Balance,
+    }
+
+    /// Emitted whenever a lease ends (or a pending lease is withdrawn)
+    #[ink(event)]
+    pub struct LeaseEnded {
+        #[ink(topic)]
+        name: String,
+        #[ink(topic)]
+        tenant: AccountId,
+    }
+
+    /// Emitted whenever a listed name is bought
+    #[ink(event)]
+    pub struct Sold {
+        #[ink(topic)]
+        name: String,
+        #[ink(topic)]
+        seller: AccountId,
+        #[ink(topic)]
+        buyer: AccountId,
+        price: Balance,
+        protocol_fee: Balance,
+    }
+
+    /// Emitted whenever an offer is made (or topped up) on a name
+    #[ink(event)]
+    pub struct OfferMade {
+        #[ink(topic)]
+        name: String,
+        #[ink(topic)]
+        bidder: AccountId,
+        amount: Balance,
+        expiry: u64,
+    }
+
+    /// Emitted whenever an offer is withdrawn and refunded to the bidder
+    #[ink(event)]
+    pub struct OfferWithdrawn {
+        #[ink(topic)]
+        name: String,
+        #[ink(topic)]
+        bidder: AccountId,
+        amount: Balance,
+    }
+
+    /// Emitted whenever an offer is accepted by the owner
+    #[ink(event)]
+    pub struct OfferAccepted {
+        #[ink(topic)]
+        name: String,
+        #[ink(topic)]
+        seller: AccountId,
+        #[ink(topic)]
+        bidder: AccountId,
+        amount: Balance,
+        protocol_fee: Balance,
+    }
+
+    /// Emitted whenever an auction is started for a name
+    #[ink(event)]
+    pub struct AuctionStarted {
+        #[ink(topic)]
+        name: String,
+        start_price: Balance,
+        min_increment: Balance,
+        end_time: u64,
+    }
+
+    /// Emitted whenever a bid is placed in an auction
+    #[ink(event)]
+    pub struct BidPlaced {
+        #[ink(topic)]
+        name: String,
+        #[ink(topic)]
+        bidder: AccountId,
+        amount: Balance,
+        end_time: u64,
+    }
+
+    /// Emitted whenever an auction is settled (or cancelled)
+    #[ink(event)]
+    pub struct AuctionSettled {
+        #[ink(topic)]
+        name: String,
+        #[ink(topic)]
+        winner: Option<AccountId>,
+        amount: Balance,
+    }
+
+    /// Emitted whenever a referrer claims their rewards
+    #[ink(event)]
+    pub struct ReferralRewardsClaimed {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        token: Option<AccountId>,
+        amount: Balance,
+    }
+
+    /// Emitted whenever a beneficiary claims its share of the revenue
+    #[ink(event)]
+    pub struct RevenueClaimed {
+        #[ink(topic)]
+        beneficiary: AccountId,
+        amount: Balance,
+    }
+
+    /// Emitted whenever a role is granted to an account
+    #[ink(event)]
+    pub struct RoleGranted {
+        #[ink(topic)]
+        role: Role,
+        #[ink(topic)]
+        account: AccountId,
+        sender: AccountId,
+    }
+
+    /// Emitted whenever a role is revoked from (or renounced by) an account
+    #[ink(event)]
+    pub struct RoleRevoked {
+        #[ink(topic)]
+        role: Role,
+        #[ink(topic)]
+        account: AccountId,
+        sender: AccountId,
+    }
+
+    /// Emitted whenever a group of operations is paused
+    #[ink(event)]
+    pub struct Paused {
+        #[ink(topic)]
+        group: PauseGroup,
+        sender: AccountId,
+    }
+
+    /// Emitted whenever a group of operations is unpaused
+    #[ink(event)]
+    pub struct Unpaused {
+        #[ink(topic)]
+        group: PauseGroup,
+        sender: AccountId,
+    }
+
     #[ink(storage)]
     pub struct Registry {
         /// Admin of the contract can perform root operations
@@ -205,6 +515,67 @@ mod azns_registry {
         reserved_names: Mapping<String, Option<AccountId>, ManualKey<100>>,
         /// Mapping from owner to operator approvals.
         operator_approvals: Mapping<(AccountId, AccountId, Option<Id>), u64, ManualKey<101>>,
+        /// Mapping from registration commitment to the time it was made
+        commitments: Mapping<[u8; 32], u64, ManualKey<102>>,
+        /// (min, max) age of a commitment before it can be revealed
+        commitment_age: Lazy<(u64, u64), ManualKey<103>>,
+        /// If set, names can only be registered through the commit-reveal flow (opt-in, unset by default)
+        commitment_required: Lazy<bool, ManualKey<104>>,
+        /// Period (in ms) after expiry during which only the previous owner can renew a name
+        redemption_period: Lazy<u64, ManualKey<105>>,
+        /// Protocol fee (in basis points) charged on marketplace sales
+        marketplace_fee: Lazy<u16, ManualKey<106>>,
+        /// Funds held in escrow on behalf of users (not withdrawable by the admin)
+        escrowed_balance: Lazy<Balance, ManualKey<107>>,
+        /// Mapping from accepted PSP22 token to its (numerator, denominator) rate against the native token
+        token_rates: Mapping<AccountId, (u128, u128), ManualKey<108>>,
+        /// Referral rate (in basis points), falls back to the legacy 5.005% if unset
+        referral_rate: Lazy<Option<u16>, ManualKey<109>>,
+        /// (min_referrals, rate) tiers overriding the referral rate for high-volume referrers
+        referral_tiers: Lazy<Vec<ReferralTier>, ManualKey<110>>,
+        /// Mapping from (referrer, payment token) to the claimable referral rewards
+        referral_rewards: Mapping<(AccountId, Option<AccountId>), Balance, ManualKey<111>>,
+        /// Mapping from PSP22 token to the tokens held on behalf of users
+        token_escrowed_balance: Mapping<AccountId, Balance, ManualKey<112>>,
+        /// Roles granted to accounts
+        roles: Mapping<(Role, AccountId), (), ManualKey<113>>,
+        /// Groups of operations currently paused
+        paused_groups: Mapping<PauseGroup, (), ManualKey<114>>,
+        /// Beneficiaries of the protocol revenue along with their shares
+        revenue_shares: Lazy<Vec<RevenueShare>, ManualKey<115>>,
+        /// Revenue claimable by each beneficiary
+        claimable_revenue: Mapping<AccountId, Balance, ManualKey<116>>,
+        /// Funds withdrawn by each beneficiary (incl. treasury withdrawals)
+        withdrawn_revenue: Mapping<AccountId, Balance, ManualKey<117>>,
+        /// Protocol revenue (net of referral payouts) received since deployment
+        lifetime_revenue: Lazy<Balance, ManualKey<118>>,
+        /// Referral rewards credited since deployment
+        referral_payouts: Lazy<Balance, ManualKey<119>>,
+        /// Sum of the revenue yet to be claimed by the beneficiaries
+        total_claimable_revenue: Lazy<Balance, ManualKey<120>>,
+        /// Sum of the funds withdrawn by the beneficiaries
+        total_withdrawn_revenue: Lazy<Balance, ManualKey<121>>,
+        /// Refund policy for the unused registration time of released names
+        refund_policy: Lazy<Option<RefundPolicy>, ManualKey<122>>,
+        /// Credit of each account usable towards registration & renewal fees
+        registration_credits: Mapping<AccountId, Balance, ManualKey<123>>,
+        /// Mapping from name to the (native) fees paid for it & the registration time they cover.
+        /// The release refund is prorated over them.
+        name_to_payment: Mapping<String, (Balance, u64), ManualKey<131>>,
+        /// Account of the ECDSA key signing the discount vouchers
+        voucher_signer: Lazy<Option<AccountId>, ManualKey<124>>,
+        /// Nonces of the vouchers already redeemed
+        used_voucher_nonces: Mapping<u128, (), ManualKey<125>>,
+        /// Next meta-transaction nonce of each signer
+        meta_tx_nonces: Mapping<AccountId, u128, ManualKey<126>>,
+        /// Next permit nonce of each owner
+        permit_nonces: Mapping<AccountId, u128, ManualKey<127>>,
+        /// Names registered by each account during each whitelist-phase
+        whitelist_usage: Mapping<(u32, AccountId), u32, ManualKey<128>>,
+        /// Number of whitelist-phases started so far
+        whitelist_round: Lazy<u32, ManualKey<129>>,
+        /// Launch phases (sorted by start) gating the registration entry points & pricing
+        launch_schedule: Lazy<Vec<PhaseConfig>, ManualKey<130>>,
 
         /// Mapping from name to addresses associated with it
         name_to_address_dict: Mapping<String, AddressDict, ManualKey<200>>,
@@ -214,21 +585,65 @@ mod azns_registry {
         name_to_period: Mapping<String, (u64, u64), ManualKey<202>>,
         /// Records
         records: Mapping<String, Vec<(String, String)>, ManualKey<203>>,
+        /// Mapping from (name, coin-type) to the address on the respective chain
+        chain_addresses: Mapping<(String, CoinType), Vec<u8>, ManualKey<204>>,
+        /// Mapping from name to the coin-types it has addresses set for
+        name_to_coin_types: Mapping<String, Vec<CoinType>, ManualKey<205>>,
+        /// Mapping from name to its fixed-price listing on the native marketplace
+        listings: Mapping<String, Listing, ManualKey<206>>,
+        /// Mapping from referrer name to its (referral count, earnings in the native token)
+        referrer_stats: Mapping<String, (u128, Balance), ManualKey<209>>,
+        /// Mapping from (name, bidder) to the escrowed offer
+        offers: Mapping<(String, AccountId), Offer, ManualKey<207>>,
+        /// Mapping from name to its ongoing auction
+        auctions: Mapping<String, Auction, ManualKey<208>>,
+        /// Mapping from name to its pending or ongoing lease
+        leases: Mapping<String, Lease, ManualKey<210>>,
+
+        /// All registered names
+        /// @dev Names registered prior to the index are added by `backfill_global_index()`
+        all_names: Mapping<u128, String, ManualKey<290>>,
+        name_to_global_index: Mapping<String, u128, ManualKey<291>>,
+        all_names_count: Lazy<u128, ManualKey<292>>,
 
         /// All names an address owns
+        /// @dev Removed names leave a gap, which is reused by the next added name, so that
+        /// the slots of the remaining names are stable (and usable as pagination cursors)
+        /// while the number of slots is bounded by the most names held at once
         owner_to_name_count: Mapping<AccountId, u128, ManualKey<300>>,
         owner_to_names: Mapping<(AccountId, u128), String, ManualKey<301>>,
         name_to_owner_index: Mapping<String, u128, ManualKey<302>>,
+        owner_to_name_slots: Mapping<AccountId, u128, ManualKey<303>>,
+        owner_to_free_slots: Mapping<(AccountId, u128), u128, ManualKey<304>>,
 
         /// All names an address controls
         controller_to_name_count: Mapping<AccountId, u128, ManualKey<310>>,
         controller_to_names: Mapping<(AccountId, u128), String, ManualKey<311>>,
         name_to_controller_index: Mapping<String, u128, ManualKey<312>>,
+        controller_to_name_slots: Mapping<AccountId, u128, ManualKey<313>>,
+        controller_to_free_slots: Mapping<(AccountId, u128), u128, ManualKey<314>>,
 
         /// All names that resolve to the given address
         resolving_to_name_count: Mapping<AccountId, u128, ManualKey<320>>,
         resolving_to_names: Mapping<(AccountId, u128), String, ManualKey<321>>,
         name_to_resolving_index: Mapping<String, u128, ManualKey<323>>,
+        resolving_to_name_slots: Mapping<AccountId, u128, ManualKey<324>>,
+        resolving_to_free_slots: Mapping<(AccountId, u128), u128, ManualKey<325>>,
+
+        /// All subnames issued under a parent name
+        parent_to_subname_count: Mapping<String, u128, ManualKey<330>>,
+        parent_to_subnames: Mapping<(String, u128), String, ManualKey<331>>,
+        subname_to_parent_index: Mapping<String, u128, ManualKey<332>>,
+
+        /// All bidders who made an offer on a name
+        name_to_bidder_count: Mapping<String, u128, ManualKey<340>>,
+        name_to_bidders: Mapping<(String, u128), AccountId, ManualKey<341>>,
+        offer_to_name_index: Mapping<(String, AccountId), u128, ManualKey<342>>,
+
+        /// All names an address made an offer on
+        bidder_to_name_count: Mapping<AccountId, u128, ManualKey<350>>,
+        bidder_to_names: Mapping<(AccountId, u128), String, ManualKey<351>>,
+        offer_to_bidder_index: Mapping<(String, AccountId), u128, ManualKey<352>>,
 
         /// Primary name record
         /// IMPORTANT NOTE: This mapping may be out-of-date, since we don't update it when a resolved address changes, or when a name is withdrawn.
@@ -242,6 +657,7 @@ mod azns_registry {
     /// Errors that can occur upon calling this contract.
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[allow(clippy::enum_variant_names)]
     pub enum Error {
         /// Caller not allowed to call privileged calls.
         NotAdmin,
@@ -267,7 +683,7 @@ mod azns_registry {
         InsufficientBalance,
         /// No resolved address found
         NoResolvedAddress,
-        /// A user can claim only one name during the whitelist-phase
+        /// A user has used up their quota of names during the whitelist-phase
         AlreadyClaimed,
         /// The merkle proof is invalid
         InvalidMerkleProof,
@@ -287,6 +703,104 @@ mod azns_registry {
         OnlyDuringWhitelistPhase,
         /// Given operation cannot be performed during the whitelist-phase
         RestrictedDuringWhitelistPhase,
+        /// Given operation is not supported for subnames
+        NotSupportedForSubnames,
+        /// Subname cannot outlive its parent name
+        ExpiryExceedsParent,
+        /// Given name is not a subname
+        NotASubname,
+        /// Given coin-type is not supported
+        UnsupportedCoinType,
+        /// Address is not valid for the given coin-type
+        InvalidChainAddress,
+        /// Registration must go through the commit-reveal flow
+        CommitmentRequired,
+        /// An active commitment with the same hash already exists
+        CommitmentAlreadyExists,
+        /// No commitment found for the revealed registration
+        CommitmentNotFound,
+        /// Commitment has to age before it can be revealed
+        CommitmentTooNew,
+        /// Commitment is too old to be revealed
+        CommitmentExpired,
+        /// Minimum commitment age must not exceed the maximum age
+        InvalidCommitmentAge,
+        /// Name has expired but can only be renewed by its previous owner for now
+        NameInRedemptionPeriod,
+        /// Name is not listed for sale (or the listing has expired)
+        NotListed,
+        /// Listing expiry must lie in the future
+        InvalidListingExpiry,
+        /// Marketplace fee cannot exceed 100%
+        InvalidMarketplaceFee,
+        /// Name transfer failed
+        TransferFailed(PSP34Error),
+        /// Offer expiry must lie in the future
+        InvalidOfferExpiry,
+        /// No offer found from the given bidder
+        OfferNotFound,
+        /// Offer has expired or was made to a previous owner (or registration) of the name
+        OfferExpired,
+        /// Name is being auctioned and cannot be registered otherwise
+        NameUnderAuction,
+        /// Only admin-reserved names & expired names at a premium can be auctioned
+        NotAuctionable,
+        /// Auction duration, minimum increment & registration years must be non-zero
+        InvalidAuctionConfig,
+        /// An auction for the given name already exists
+        AuctionAlreadyExists,
+        /// No auction found for the given name
+        AuctionNotFound,
+        /// Auction is over, no more bids are accepted
+        AuctionEnded,
+        /// Auction is still accepting bids
+        AuctionNotEnded,
+        /// Bid is below the start price or the minimum increment
+        BidTooLow,
+        /// Given PSP22 token is not accepted for payments
+        TokenNotAccepted,
+        /// Token rate must have a non-zero numerator & denominator
+        InvalidTokenRate,
+        /// Caller has not approved enough tokens to the contract
+        InsufficientAllowance,
+        /// PSP22 token transfer failed
+        TokenTransferFailed(PSP22Error),
+        /// Referral rates cannot exceed 100% & tiers must be sorted by their minimum referrals
+        InvalidReferralConfig,
+        /// No referral rewards to claim
+        NothingToClaim,
+        /// Caller is missing the role required for the operation
+        MissingRole(Role),
+        /// The group of operations is paused
+        GroupPaused(PauseGroup),
+        /// Revenue shares cannot exceed 100% & beneficiaries must be unique non-zero addresses
+        InvalidRevenueShares,
+        /// Refund share cannot exceed 100%
+        InvalidRefundPolicy,
+        /// Operation is not allowed while the name is leased
+        NameLeased,
+        /// Lease must end in the future, but not after the name expires
+        InvalidLeaseExpiry,
+        /// No lease found for the name
+        LeaseNotFound,
+        /// Voucher is not signed by the voucher signer or does not apply to the name
+        InvalidVoucher,
+        /// Voucher has expired
+        VoucherExpired,
+        /// Voucher has been redeemed already
+        VoucherAlreadyUsed,
+        /// Meta-transaction signature is invalid
+        InvalidSignature,
+        /// Meta-transaction nonce does not match the signer's next nonce
+        InvalidNonce,
+        /// Meta-transaction deadline has passed
+        DeadlineExpired,
+        /// Registration period exceeds the whitelist quota of the user
+        WhitelistYearsExceeded,
+        /// Phases of the launch schedule must start in order
+        InvalidLaunchSchedule,
+        /// Given operation is not allowed in the current launch phase
+        NotAllowedInCurrentPhase,
     }
 
     impl Registry {
@@ -300,13 +814,12 @@ mod azns_registry {
             base_uri: String,
         ) -> Self {
             // Initializing NameChecker
-            let name_checker = name_checker_addr.map(|addr| NameCheckerRef::from_account_id(addr));
+            let name_checker = name_checker_addr.map(NameCheckerRef::from_account_id);
 
             // Initializing FeeCalculator
-            let fee_calculator =
-                fee_calculator_addr.map(|addr| FeeCalculatorRef::from_account_id(addr));
+            let fee_calculator = fee_calculator_addr.map(FeeCalculatorRef::from_account_id);
 
-            Self::env().emit_event(PublicPhaseActivated {});
+            Self::emit_event(PublicPhaseActivated {});
 
             Self {
                 admin,
@@ -316,20 +829,75 @@ mod azns_registry {
                 name_to_address_dict: Mapping::default(),
                 name_to_lock: Mapping::default(),
                 name_to_period: Mapping::default(),
+                all_names: Default::default(),
+                name_to_global_index: Default::default(),
+                all_names_count: Default::default(),
                 owner_to_name_count: Default::default(),
                 owner_to_names: Default::default(),
+                owner_to_name_slots: Default::default(),
+                owner_to_free_slots: Default::default(),
                 name_to_owner_index: Default::default(),
                 records: Default::default(),
+                chain_addresses: Default::default(),
+                name_to_coin_types: Default::default(),
+                listings: Default::default(),
+                offers: Default::default(),
+                auctions: Default::default(),
+                leases: Default::default(),
                 address_to_primary_name: Default::default(),
                 controller_to_name_count: Default::default(),
                 controller_to_names: Default::default(),
+                controller_to_name_slots: Default::default(),
+                controller_to_free_slots: Default::default(),
                 name_to_controller_index: Default::default(),
                 resolving_to_name_count: Default::default(),
                 resolving_to_names: Default::default(),
+                resolving_to_name_slots: Default::default(),
+                resolving_to_free_slots: Default::default(),
                 name_to_resolving_index: Default::default(),
+                parent_to_subname_count: Default::default(),
+                parent_to_subnames: Default::default(),
+                subname_to_parent_index: Default::default(),
+                name_to_bidder_count: Default::default(),
+                name_to_bidders: Default::default(),
+                offer_to_name_index: Default::default(),
+                bidder_to_name_count: Default::default(),
+                bidder_to_names: Default::default(),
+                offer_to_bidder_index: Default::default(),
                 whitelisted_address_verifier: Default::default(),
                 reserved_names: Default::default(),
                 operator_approvals: Default::default(),
+                commitments: Default::default(),
+                commitment_age: Default::default(),
+                commitment_required: Default::default(),
+                redemption_period: Default::default(),
+                marketplace_fee: Default::default(),
+                escrowed_balance: Default::default(),
+                token_rates: Default::default(),
+                referral_rate: Default::default(),
+                referral_tiers: Default::default(),
+                referral_rewards: Default::default(),
+                token_escrowed_balance: Default::default(),
+                roles: Default::default(),
+                paused_groups: Default::default(),
+                revenue_shares: Default::default(),
+                claimable_revenue: Default::default(),
+                withdrawn_revenue: Default::default(),
+                lifetime_revenue: Default::default(),
+                referral_payouts: Default::default(),
+                total_claimable_revenue: Default::default(),
+                total_withdrawn_revenue: Default::default(),
+                refund_policy: Default::default(),
+                registration_credits: Default::default(),
+                name_to_payment: Default::default(),
+                voucher_signer: Default::default(),
+                used_voucher_nonces: Default::default(),
+                meta_tx_nonces: Default::default(),
+                permit_nonces: Default::default(),
+                whitelist_usage: Default::default(),
+                whitelist_round: Default::default(),
+                launch_schedule: Default::default(),
+                referrer_stats: Default::default(),
                 tld,
                 base_uri,
                 records_size_limit: None,
@@ -350,42 +918,189 @@ mod azns_registry {
             referrer: Option<String>,
             bonus_name: Option<String>,
         ) -> Result<()> {
-            if !self.is_name_allowed(&name) {
-                return Err(Error::NameNotAllowed);
+            if self.get_commitment_required() {
+                return Err(Error::CommitmentRequired);
             }
+            self.do_register(
+                name,
+                recipient,
+                years_to_register,
+                referrer,
+                bonus_name,
+                None,
+            )
+        }
 
-            // The name must not be a reserved name
-            if self.reserved_names.contains(&name) {
-                return Err(Error::CannotBuyReservedName);
+        /// Step 1 of the commit-reveal registration.
+        /// Stores the hash obtained from `make_commitment()` without revealing the name.
+        ///
+        /// NOTE: The flow is opt-in. Until the admin calls `set_commitment_required(true)`,
+        /// the direct `register*` messages stay open and can be front-run.
+        #[ink(message)]
+        pub fn commit(&mut self, commitment: [u8; 32]) -> Result<()> {
+            let now = self.env().block_timestamp();
+            if self
+                .commitments
+                .get(commitment)
+                .is_some_and(|timestamp| !self.has_commitment_expired(timestamp))
+            {
+                return Err(Error::CommitmentAlreadyExists);
+            }
+
+            self.commitments.insert(commitment, &now);
+            Ok(())
+        }
+
+        /// Step 2 of the commit-reveal registration.
+        /// Registers the committed name once the commitment is old enough (but not expired).
+        #[ink(message, payable)]
+        pub fn reveal_and_register(
+            &mut self,
+            name: String,
+            recipient: AccountId,
+            years_to_register: u8,
+            referrer: Option<String>,
+            bonus_name: Option<String>,
+            secret: [u8; 32],
+        ) -> Result<()> {
+            let commitment = self.make_commitment(name.clone(), recipient, secret);
+            let timestamp = self
+                .commitments
+                .get(commitment)
+                .ok_or(Error::CommitmentNotFound)?;
+
+            let (min_age, _) = self.get_commitment_age();
+            if self.env().block_timestamp() < timestamp + min_age {
+                return Err(Error::CommitmentTooNew);
+            }
+            if self.has_commitment_expired(timestamp) {
+                return Err(Error::CommitmentExpired);
+            }
+            self.commitments.remove(commitment);
+
+            self.do_register(
+                name,
+                recipient,
+                years_to_register,
+                referrer,
+                bonus_name,
+                None,
+            )
+        }
+
+        /// Removes expired commitments from storage
+        #[ink(message)]
+        pub fn clear_expired_commitments(&mut self, commitments: Vec<[u8; 32]>) -> Result<u128> {
+            let mut count = 0;
+            commitments.into_iter().for_each(|commitment| {
+                if let Some(timestamp) = self.commitments.get(commitment) {
+                    if self.has_commitment_expired(timestamp) {
+                        self.commitments.remove(commitment);
+                        count += 1;
+                    }
+                }
+            });
+            Ok(count)
+        }
+
+        fn do_register(
+            &mut self,
+            name: String,
+            recipient: AccountId,
+            years_to_register: u8,
+            referrer: Option<String>,
+            bonus_name: Option<String>,
+            token: Option<AccountId>,
+        ) -> Result<()> {
+            self.end_scheduled_whitelist_phase();
+            if self.is_whitelist_phase() {
+                return Err(Error::RestrictedDuringWhitelistPhase);
             }
+            self.ensure_allowed_in_phase(EntryPoint::Register)?;
+            self.ensure_registrable(&name)?;
 
             let (base_price, premium, discount, referrer_addr) =
                 self.get_name_price(name.clone(), recipient, years_to_register, referrer.clone())?;
-            let price = base_price + premium - discount;
+            let mut price = self.get_price_in(token, base_price + premium - discount)?;
+            let native_discount = discount;
+            let discount = self.get_price_in(token, discount)?;
 
             /* Make sure the register is paid for */
-            self.handle_payment(price)?;
+            // @dev The credit only covers the protocol's share, the referral reward is paid upfront
+            if token.is_none() {
+                price = price.min(discount)
+                    + self.redeem_registration_credit(price.saturating_sub(discount));
+            }
+            self.handle_payment_in(token, price, price.saturating_sub(discount))?;
 
             let expiry_time = self.env().block_timestamp() + YEAR * years_to_register as u64;
             self.register_name(&name, &recipient, expiry_time)?;
+            if token.is_none() {
+                self.record_payment(&name, price, 0);
+            }
 
-            // Pay the referrer_addr (if present) after successful registration
-            if let Some(usr) = referrer_addr {
-                if self.env().transfer(usr, discount).is_err() {
-                    return Err(Error::WithdrawFailed);
-                }
+            // Credit the referrer_addr (if present) after successful registration
+            if let (Some(usr), Some(referrer_name)) = (referrer_addr, &referrer) {
+                self.credit_referral_reward(referrer_name, usr, token, discount, native_discount);
+            }
+
+            let from = self.env().caller();
+            let received_fee = price.saturating_sub(discount);
+            match token {
+                None => Self::emit_event(FeeReceived {
+                    name,
+                    from,
+                    referrer,
+                    referrer_addr,
+                    received_fee,
+                    forwarded_referrer_fee: discount,
+                }),
+                Some(token) => Self::emit_event(TokenFeeReceived {
+                    name,
+                    from,
+                    referrer,
+                    referrer_addr,
+                    received_fee,
+                    forwarded_referrer_fee: discount,
+                    token,
+                }),
             }
 
-            self.env().emit_event(FeeReceived {
+            self.redeem_bonus_name(bonus_name, years_to_register, recipient)
+        }
+
+        /// Registers the name at a discount (in basis points) of its price, paid natively
+        fn register_discounted(
+            &mut self,
+            name: String,
+            recipient: AccountId,
+            years_to_register: u8,
+            discount: u16,
+        ) -> Result<()> {
+            self.ensure_registrable(&name)?;
+
+            let (base_price, premium, _, _) =
+                self.get_name_price(name.clone(), recipient, years_to_register, None)?;
+            let price = base_price + premium;
+            let discount = price * discount as Balance / MAX_BPS as Balance;
+
+            /* Make sure the register is paid for */
+            let price = self.redeem_registration_credit(price - discount);
+            self.handle_payment(price, price)?;
+
+            let expiry_time = self.env().block_timestamp() + YEAR * years_to_register as u64;
+            self.register_name(&name, &recipient, expiry_time)?;
+            self.record_payment(&name, price, 0);
+
+            Self::emit_event(FeeReceived {
                 name,
                 from: self.env().caller(),
-                referrer,
-                referrer_addr,
-                received_fee: price - discount,
-                forwarded_referrer_fee: discount,
+                referrer: None,
+                referrer_addr: None,
+                received_fee: price,
+                forwarded_referrer_fee: 0,
             });
-
-            self.redeem_bonus_name(bonus_name, years_to_register, recipient)
+            Ok(())
         }
 
         /// Register specific name with caller as owner.
@@ -412,42 +1127,310 @@ mod azns_registry {
         }
 
         /// register_v1
+        ///
+        /// NOTE: During the whitelist phase, the caller must provide their quota along with its merkle proof.
+        /// Whitelisted registrations are discounted as per the quota and do not reward referrers.
         #[ink(message, payable)]
         pub fn register(
             &mut self,
             name: String,
             years_to_register: u8,
             referrer: Option<String>,
-            _merkle_proof: Option<Vec<[u8; 32]>>,
+            whitelist_proof: Option<(WhitelistQuota, Vec<[u8; 32]>)>,
             set_as_primary_name: bool,
         ) -> Result<()> {
-            self.register_v2(name, years_to_register, referrer, None, set_as_primary_name)
+            if !self.is_whitelist_phase() {
+                return self.register_v2(
+                    name,
+                    years_to_register,
+                    referrer,
+                    None,
+                    set_as_primary_name,
+                );
+            }
+            if self.get_commitment_required() {
+                return Err(Error::CommitmentRequired);
+            }
+            self.ensure_allowed_in_phase(EntryPoint::Register)?;
+
+            let caller = self.env().caller();
+            let (quota, merkle_proof) = whitelist_proof.ok_or(Error::InvalidMerkleProof)?;
+            if !self.verify_whitelist_proof(caller, quota.clone(), merkle_proof) {
+                return Err(Error::InvalidMerkleProof);
+            }
+            if years_to_register > quota.max_years {
+                return Err(Error::WhitelistYearsExceeded);
+            }
+
+            let used = self.get_whitelist_usage(caller);
+            if used >= quota.max_names {
+                return Err(Error::AlreadyClaimed);
+            }
+            let round = self.whitelist_round.get_or_default();
+            self.whitelist_usage.insert((round, caller), &(used + 1));
+
+            self.register_discounted(name.clone(), caller, years_to_register, quota.discount)?;
+            if set_as_primary_name {
+                self.set_primary_name(Some(name))?;
+            }
+            Ok(())
         }
 
+        /// Register a name to the caller at the discount of a voucher signed (ECDSA) by the voucher signer.
+        /// The voucher must be issued to the caller & its signature must cover the hash returned by
+        /// `get_voucher_hash()`.
+        ///
+        /// NOTE: Only ECDSA signatures are accepted, vouchers cannot be signed with an sr25519 key.
         #[ink(message, payable)]
-        pub fn renew(
+        pub fn register_with_voucher(
             &mut self,
             name: String,
-            years_to_renew: u8,
-            bonus_name: Option<String>,
-        ) -> Result<Balance> {
-            let price = self.do_renew(name, years_to_renew, bonus_name)?;
-            self.handle_payment(price)?;
-            Ok(price)
-        }
+            years_to_register: u8,
+            voucher: Voucher,
+            signature: [u8; 65],
+        ) -> Result<()> {
+            if self.get_commitment_required() {
+                return Err(Error::CommitmentRequired);
+            }
+            self.end_scheduled_whitelist_phase();
+            if self.is_whitelist_phase() {
+                return Err(Error::RestrictedDuringWhitelistPhase);
+            }
+            self.ensure_allowed_in_phase(EntryPoint::RegisterWithVoucher)?;
+            self.ensure_valid_voucher(&name, &voucher, &signature)?;
+            self.used_voucher_nonces.insert(voucher.nonce, &());
+
+            self.register_discounted(
+                name.clone(),
+                voucher.recipient,
+                years_to_register,
+                voucher.discount,
+            )?;
+
+            Self::emit_event(VoucherRedeemed {
+                name,
+                recipient: voucher.recipient,
+                nonce: voucher.nonce,
+            });
+            Ok(())
+        }
+
+        /// Register multiple (name, recipient, years, referrer) entries with a single payment.
+        /// In the all-or-nothing mode the call fails if any of the entries fails, otherwise
+        /// the failed entries are skipped & only the registered names are charged.
+        /// @returns the registration result of each entry
+        #[ink(message, payable)]
+        pub fn batch_register(
+            &mut self,
+            entries: Vec<(String, AccountId, u8, Option<String>)>,
+            all_or_nothing: bool,
+        ) -> Result<Vec<Result<()>>> {
+            if self.get_commitment_required() {
+                return Err(Error::CommitmentRequired);
+            }
+            self.end_scheduled_whitelist_phase();
+            if self.is_whitelist_phase() {
+                return Err(Error::RestrictedDuringWhitelistPhase);
+            }
+            self.ensure_allowed_in_phase(EntryPoint::BatchRegister)?;
+            self.ensure_not_paused(PauseGroup::Registration)?;
+
+            /* Validate all the entries upfront */
+            let prices = self.batch_get_name_price(entries.clone());
+            let mut validated = Vec::with_capacity(entries.len());
+            for (entry, price) in entries.into_iter().zip(prices) {
+                let price = self.ensure_registrable(&entry.0).and(price);
+                if all_or_nothing {
+                    if let Err(e) = price {
+                        return Err(e);
+                    }
+                }
+                validated.push((entry, price));
+            }
+
+            let caller = self.env().caller();
+            let (mut cost, mut referral_fees) = (0, 0);
+            let mut results = Vec::with_capacity(validated.len());
+
+            for ((name, recipient, years, referrer), price) in validated {
+                let result = price.and_then(|(base_price, premium, discount, referrer_addr)| {
+                    let expiry_time = self.env().block_timestamp() + YEAR * years as u64;
+                    self.register_name(&name, &recipient, expiry_time)?;
+
+                    if let (Some(usr), Some(referrer_name)) = (referrer_addr, &referrer) {
+                        self.credit_referral_reward(referrer_name, usr, None, discount, discount);
+                    }
+
+                    // @dev The credit only covers the protocol's share, the referral reward is paid upfront
+                    let price = base_price + premium - discount;
+                    let price = price.min(discount)
+                        + self.redeem_registration_credit(price.saturating_sub(discount));
+                    self.record_payment(&name, price, 0);
+                    cost += price;
+                    referral_fees += discount;
+
+                    Self::emit_event(FeeReceived {
+                        name,
+                        from: caller,
+                        referrer,
+                        referrer_addr,
+                        received_fee: price - discount,
+                        forwarded_referrer_fee: discount,
+                    });
+                    Ok(())
+                });
+
+                if all_or_nothing {
+                    if let Err(e) = result {
+                        return Err(e);
+                    }
+                }
+                results.push(result);
+            }
+
+            /* Charge the registered names at once */
+            self.handle_payment(cost, cost.saturating_sub(referral_fees))?;
+
+            Ok(results)
+        }
+
+        #[ink(message, payable)]
+        pub fn renew(
+            &mut self,
+            name: String,
+            years_to_renew: u8,
+            bonus_name: Option<String>,
+        ) -> Result<Balance> {
+            let price = self.do_renew(name, years_to_renew, bonus_name, None)?;
+            self.handle_payment(price, price)?;
+            Ok(price)
+        }
 
         #[ink(message, payable)]
         pub fn batch_renew(&mut self, data: Vec<(String, u8, Option<String>)>) -> Result<Balance> {
             let mut cost = 0;
 
             for item in data {
-                cost += self.do_renew(item.0, item.1, item.2)?;
+                cost += self.do_renew(item.0, item.1, item.2, None)?;
+            }
+            self.handle_payment(cost, cost)?;
+
+            Ok(cost)
+        }
+
+        /// Same as `register_on_behalf_of()`, but the fee is paid in an accepted PSP22 token.
+        /// The caller must approve the token amount to this contract beforehand.
+        #[ink(message)]
+        pub fn register_with_token(
+            &mut self,
+            name: String,
+            recipient: AccountId,
+            years_to_register: u8,
+            referrer: Option<String>,
+            bonus_name: Option<String>,
+            token: AccountId,
+        ) -> Result<()> {
+            if self.get_commitment_required() {
+                return Err(Error::CommitmentRequired);
+            }
+            self.do_register(
+                name,
+                recipient,
+                years_to_register,
+                referrer,
+                bonus_name,
+                Some(token),
+            )
+        }
+
+        /// Same as `renew()`, but the fee is paid in an accepted PSP22 token
+        /// @returns the token amount paid
+        #[ink(message)]
+        pub fn renew_with_token(
+            &mut self,
+            name: String,
+            years_to_renew: u8,
+            bonus_name: Option<String>,
+            token: AccountId,
+        ) -> Result<Balance> {
+            self.batch_renew_with_token(vec![(name, years_to_renew, bonus_name)], token)
+        }
+
+        /// Same as `batch_renew()`, but the fee is paid in an accepted PSP22 token
+        /// @returns the token amount paid
+        #[ink(message)]
+        pub fn batch_renew_with_token(
+            &mut self,
+            data: Vec<(String, u8, Option<String>)>,
+            token: AccountId,
+        ) -> Result<Balance> {
+            let mut cost = 0;
+
+            for item in data {
+                cost += self.do_renew(item.0, item.1, item.2, Some(token))?;
             }
-            self.handle_payment(cost)?;
+            let cost = self.get_price_in(Some(token), cost)?;
+            self.handle_payment_in(Some(token), cost, cost)?;
 
             Ok(cost)
         }
 
+        /// Claim the referral rewards accrued in the given payment token (`None` for the native token)
+        #[ink(message)]
+        pub fn claim_referral_rewards(&mut self, token: Option<AccountId>) -> Result<Balance> {
+            let caller = self.env().caller();
+            let amount = self.referral_rewards.take((caller, token)).unwrap_or(0);
+            if amount == 0 {
+                return Err(Error::NothingToClaim);
+            }
+
+            match token {
+                Some(token) => {
+                    let escrowed = self.get_token_escrowed_balance(token);
+                    self.token_escrowed_balance
+                        .insert(token, &(escrowed - amount));
+                }
+                None => self
+                    .escrowed_balance
+                    .set(&(self.get_escrowed_balance() - amount)),
+            }
+            self.transfer_funds(token, caller, amount)?;
+
+            Self::emit_event(ReferralRewardsClaimed {
+                account: caller,
+                token,
+                amount,
+            });
+
+            Ok(amount)
+        }
+
+        /// Claim the caller's share of the protocol revenue
+        #[ink(message)]
+        pub fn claim(&mut self) -> Result<Balance> {
+            let caller = self.env().caller();
+            let amount = self.claimable_revenue.take(caller).unwrap_or(0);
+            if amount == 0 {
+                return Err(Error::NothingToClaim);
+            }
+
+            let total_claimable = self.total_claimable_revenue.get().unwrap_or(0);
+            self.total_claimable_revenue
+                .set(&(total_claimable - amount));
+            self.record_withdrawal(caller, amount);
+
+            if self.env().transfer(caller, amount).is_err() {
+                return Err(Error::WithdrawFailed);
+            }
+
+            Self::emit_event(RevenueClaimed {
+                beneficiary: caller,
+                amount,
+            });
+
+            Ok(amount)
+        }
+
         /// Allows users to claim their reserved name at zero cost
         #[ink(message)]
         pub fn claim_reserved_name(
@@ -455,6 +1438,7 @@ mod azns_registry {
             name: String,
             set_as_primary_name: bool,
         ) -> Result<()> {
+            self.ensure_allowed_in_phase(EntryPoint::ClaimReservedName)?;
             let caller = self.env().caller();
 
             let Some(user) = self.reserved_names.get(&name) else {
@@ -474,7 +1458,7 @@ mod azns_registry {
 
                     // Remove the name from the list once claimed
                     self.reserved_names.remove(&name);
-                    self.env().emit_event(Reserve {
+                    Self::emit_event(Reserve {
                         name,
                         account_id: Some(caller),
                         action: false,
@@ -490,15 +1474,127 @@ mod azns_registry {
             self.ensure_owner(&caller, &name)?;
             self.ensure_unlocked(&name)
                 .map_err(|_| Error::NotAuthorised)?;
+            self.ensure_not_leased(&name)?;
+
+            let refund = self.get_release_refund(name.clone());
+            self.remove_name(&name);
+
+            if refund > 0 {
+                let as_credit = self.get_refund_policy().is_some_and(|p| p.as_credit);
+                if as_credit {
+                    let credit = self.get_registration_credit(caller);
+                    self.registration_credits.insert(caller, &(credit + refund));
+                } else {
+                    // @dev Refunds are paid by the treasury, not by the escrowed or claimable funds
+                    let balance = self
+                        .env()
+                        .balance()
+                        .saturating_sub(self.get_escrowed_balance())
+                        .saturating_sub(self.total_claimable_revenue.get().unwrap_or(0));
+                    if refund > balance {
+                        return Err(Error::InsufficientBalance);
+                    }
+                    if self.env().transfer(caller, refund).is_err() {
+                        return Err(Error::WithdrawFailed);
+                    }
+                }
+
+                Self::emit_event(ReleaseRefunded {
+                    name: name.clone(),
+                    account: caller,
+                    amount: refund,
+                    as_credit,
+                });
+            }
+
+            Self::emit_event(Release { name, from: caller });
+
+            Ok(())
+        }
+
+        /// Issue a subname (e.g. `pay` under `alice` => `pay.alice`) to the given recipient.
+        /// Only the owner or controller of the parent name can issue subnames, unless it is leased.
+        // @dev expiry defaults to the parent's expiry and can never exceed it
+        #[ink(message)]
+        pub fn register_subname(
+            &mut self,
+            parent: String,
+            label: String,
+            recipient: AccountId,
+            expiry: Option<u64>,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_subname_manager(&caller, &parent)?;
+
+            if !self.is_name_allowed(&label) {
+                return Err(Error::NameNotAllowed);
+            }
+
+            let (_, parent_expiry) = self.get_registration_period_ref(&parent)?;
+            let expiry = expiry.unwrap_or(parent_expiry);
+            if expiry > parent_expiry {
+                return Err(Error::ExpiryExceedsParent);
+            }
+
+            let name = label + "." + &parent;
+            self.register_name(&name, &recipient, expiry)?;
+            self.add_subname_to_parent(&parent, &name);
+
+            Ok(())
+        }
+
+        /// Revoke a subname. Can be called by the owner or controller of the parent name.
+        #[ink(message)]
+        pub fn revoke_subname(&mut self, name: String) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_parent_controller_owner(&caller, &name)?;
+            self.ensure_unlocked(&name)
+                .map_err(|_| Error::NotAuthorised)?;
+            self.ensure_not_leased(&name)?;
 
+            if self.has_name_expired(&name) != Ok(false) {
+                return Err(Error::NameDoesntExist);
+            }
             self.remove_name(&name);
 
-            Self::env().emit_event(Release { name, from: caller });
+            Self::emit_event(Release { name, from: caller });
+
+            Ok(())
+        }
+
+        /// Update the expiry of a subname. Can be called by the owner or controller of the parent name.
+        #[ink(message)]
+        pub fn set_subname_expiry(&mut self, name: String, new_expiry: u64) -> Result<()> {
+            self.ensure_not_paused(PauseGroup::Renewal)?;
+
+            let caller = self.env().caller();
+            self.ensure_parent_controller_owner(&caller, &name)?;
+
+            if self.has_name_expired(&name) != Ok(false) {
+                return Err(Error::NameDoesntExist);
+            }
+
+            let parent = Self::get_parent_name(&name).ok_or(Error::NotASubname)?;
+            let (_, parent_expiry) = self.get_registration_period_ref(parent)?;
+            if new_expiry > parent_expiry {
+                return Err(Error::ExpiryExceedsParent);
+            }
+
+            let (registration, old_expiry) = self.get_registration_period_ref(&name)?;
+            self.name_to_period
+                .insert(&name, &(registration, new_expiry));
+
+            Self::emit_event(Renew {
+                name,
+                old_expiry,
+                new_expiry,
+            });
 
             Ok(())
         }
 
         /// Transfer owner to another address.
+        /// @note the owner or controller of the parent name can transfer its subnames as well
         #[ink(message)]
         pub fn transfer(
             &mut self,
@@ -519,2944 +1615,7755 @@ mod azns_registry {
             )
         }
 
-        /// Removes the associated state of expired-names from storage
+        /// List a name for sale at a fixed price on the native marketplace
+        /// @note re-listing a name overrides its previous listing
         #[ink(message)]
-        pub fn clear_expired_names(&mut self, names: Vec<String>) -> Result<u128> {
-            let mut count = 0;
-            names.into_iter().for_each(|name| {
-                // Verify the name has expired
-                if self.has_name_expired(&name) == Ok(true) {
-                    self.remove_name(&name);
-                    count += 1;
-                }
+        pub fn list_for_sale(&mut self, name: String, price: Balance, expiry: u64) -> Result<()> {
+            self.ensure_not_paused(PauseGroup::Marketplace)?;
+
+            let caller = self.env().caller();
+            self.ensure_owner(&caller, &name)?;
+            self.ensure_unlocked(&name)
+                .map_err(|_| Error::NotAuthorised)?;
+
+            if expiry <= self.env().block_timestamp() {
+                return Err(Error::InvalidListingExpiry);
+            }
+
+            self.listings
+                .insert(&name, &Listing::new(caller, price, expiry));
+
+            Self::emit_event(Listed {
+                name,
+                seller: caller,
+                price,
+                expiry,
             });
-            Ok(count)
+
+            Ok(())
         }
 
-        /// Set primary name of an address (reverse record)
-        /// @note if name is set to None then the primary-name for the caller will be removed (if exists)
+        /// Cancel the listing of a name
         #[ink(message)]
-        pub fn set_primary_name(&mut self, primary_name: Option<String>) -> Result<()> {
-            let address = self.env().caller();
+        pub fn cancel_listing(&mut self, name: String) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_owner(&caller, &name)?;
 
-            match &primary_name {
-                Some(name) => {
-                    let resolved = self.get_address_dict_ref(&name)?.resolved;
+            if self.listings.take(&name).is_none() {
+                return Err(Error::NotListed);
+            }
 
-                    /* Ensure the target name resolves to the address */
-                    if resolved != address {
-                        return Err(Error::NoResolvedAddress);
-                    }
-                    self.address_to_primary_name.insert(address, name);
-                }
-                None => self.address_to_primary_name.remove(address),
-            };
+            Self::emit_event(ListingCancelled { name });
 
-            self.env().emit_event(SetPrimaryName {
-                account: address,
-                primary_name,
-            });
             Ok(())
         }
 
-        /// Set resolved address for specific name.
-        #[ink(message)]
-        pub fn set_address(&mut self, name: String, new_address: AccountId) -> Result<()> {
-            /* Ensure the caller is the controller */
-            let caller = Self::env().caller();
-            self.ensure_controller_owner(&caller, &name)?;
+        /// Buy a listed name. Excess payment is refunded to the caller.
+        /// @note the name is transferred with its records, controller & resolved address reset
+        #[ink(message, payable)]
+        pub fn buy(&mut self, name: String) -> Result<()> {
+            self.ensure_not_paused(PauseGroup::Marketplace)?;
 
-            let mut address_dict = self.get_address_dict_ref(&name)?;
-            let old_address = address_dict.resolved;
-            address_dict.set_resolved(new_address);
-            self.name_to_address_dict.insert(&name, &address_dict);
+            let Some(Listing { seller, price, .. }) = self.get_listing(name.clone()) else {
+                return Err(Error::NotListed);
+            };
 
-            /* Remove the name from the old resolved address */
-            self.remove_name_from_resolving(&old_address, &name);
+            let protocol_fee = price * self.get_marketplace_fee() as Balance / MAX_BPS as Balance;
+            self.handle_payment(price, protocol_fee)?;
 
-            /* Add the name to the new resolved address */
-            self.add_name_to_resolving(&new_address, &name);
+            let buyer = self.env().caller();
+            self.do_transfer_name(buyer, &name, false, false, false, &Vec::new())
+                .map_err(Error::TransferFailed)?;
+
+            /* Pay the seller, the protocol fee stays in the contract */
+            if self.env().transfer(seller, price - protocol_fee).is_err() {
+                return Err(Error::WithdrawFailed);
+            }
 
-            Self::env().emit_event(SetAddress {
+            Self::emit_event(Sold {
                 name,
-                from: caller,
-                old_address: Some(old_address),
-                new_address,
+                seller,
+                buyer,
+                price,
+                protocol_fee,
             });
+
             Ok(())
         }
 
-        #[ink(message)]
-        pub fn set_controller(&mut self, name: String, new_controller: AccountId) -> Result<()> {
-            /* Ensure caller is either controller or owner */
-            let caller = Self::env().caller();
-            self.ensure_controller_owner(&caller, &name)?;
+        /// Make an escrowed offer on a registered name. The transferred value is held until
+        /// the offer is accepted by the owner or withdrawn by the bidder.
+        /// @note making another offer on the same name tops up the existing one, unless it is void
+        /// since the name changed hands or was registered anew (it has to be withdrawn first)
+        #[ink(message, payable)]
+        pub fn make_offer(&mut self, name: String, expiry: u64) -> Result<()> {
+            self.ensure_not_paused(PauseGroup::Marketplace)?;
 
-            let mut address_dict = self.get_address_dict_ref(&name)?;
-            let old_controller = address_dict.controller;
-            address_dict.set_controller(new_controller);
-            self.name_to_address_dict.insert(&name, &address_dict);
+            let bidder = self.env().caller();
+            let value = self.env().transferred_value();
+            let AddressDict { owner, .. } = self.get_address_dict_ref(&name)?;
+            let (registered_at, _) = self.get_registration_period_ref(&name)?;
 
-            /* Remove the name from the old controller */
-            self.remove_name_from_controller(&old_controller, &name);
+            if value == 0 {
+                return Err(Error::FeeNotPaid);
+            }
+            if expiry <= self.env().block_timestamp() {
+                return Err(Error::InvalidOfferExpiry);
+            }
 
-            /* Add the name to the new controller */
-            self.add_name_to_controller(&new_controller, &name);
+            let amount = match self.offers.get((&name, &bidder)) {
+                Some(offer) if !offer.targets(&owner, registered_at) => {
+                    return Err(Error::OfferExpired)
+                }
+                Some(offer) => offer.amount + value,
+                None => {
+                    self.add_offer_to_indexes(&name, &bidder);
+                    value
+                }
+            };
+
+            self.offers.insert(
+                (&name, &bidder),
+                &Offer::new(owner, registered_at, amount, expiry),
+            );
+            self.escrowed_balance
+                .set(&(self.get_escrowed_balance() + value));
 
-            self.env().emit_event(SetController {
+            Self::emit_event(OfferMade {
                 name,
-                from: caller,
-                old_controller: Some(old_controller),
-                new_controller,
+                bidder,
+                amount,
+                expiry,
             });
 
             Ok(())
         }
 
+        /// Withdraw an offer and get the escrowed amount refunded
         #[ink(message)]
-        pub fn reset_resolved_address(&mut self, names: Vec<String>) -> Result<()> {
-            let caller = self.env().caller();
+        pub fn withdraw_offer(&mut self, name: String) -> Result<()> {
+            let bidder = self.env().caller();
+            let offer = self.take_offer(&name, &bidder)?;
 
-            for name in names.iter() {
-                let mut address_dict = self.get_address_dict_ref(&name)?;
-                let owner = address_dict.owner;
-                let resolved = address_dict.resolved;
+            if self.env().transfer(bidder, offer.amount).is_err() {
+                return Err(Error::WithdrawFailed);
+            }
 
-                if resolved != caller {
-                    return Err(Error::NotAuthorised);
-                }
-                if resolved != owner {
-                    address_dict.set_resolved(owner);
-                    self.name_to_address_dict.insert(&name, &address_dict);
+            Self::emit_event(OfferWithdrawn {
+                name,
+                bidder,
+                amount: offer.amount,
+            });
 
-                    /* Remove the name from the old resolved address */
-                    self.remove_name_from_resolving(&resolved, &name);
+            Ok(())
+        }
 
-                    /* Add the name to the new resolved address */
-                    self.add_name_to_resolving(&owner, &name);
+        /// Accept an offer on the name. The name is transferred to the bidder
+        /// and the escrowed amount (minus the protocol fee) is paid to the owner.
+        #[ink(message)]
+        pub fn accept_offer(&mut self, name: String, bidder: AccountId) -> Result<()> {
+            self.ensure_not_paused(PauseGroup::Marketplace)?;
 
-                    self.env().emit_event(SetAddress {
-                        name: name.to_string(),
-                        from: caller,
-                        old_address: Some(resolved),
-                        new_address: owner,
-                    });
-                }
+            let seller = self.env().caller();
+            self.ensure_owner(&seller, &name)?;
+
+            let Some(offer) = self.offers.get((&name, &bidder)) else {
+                return Err(Error::OfferNotFound);
+            };
+            let (registered_at, _) = self.get_registration_period_ref(&name)?;
+            if !offer.is_active(&seller, registered_at, self.env().block_timestamp()) {
+                return Err(Error::OfferExpired);
+            }
+            self.take_offer(&name, &bidder)?;
+
+            self.do_transfer_name(bidder, &name, false, false, false, &Vec::new())
+                .map_err(Error::TransferFailed)?;
+
+            /* Pay the seller, the protocol fee stays in the contract */
+            let protocol_fee =
+                offer.amount * self.get_marketplace_fee() as Balance / MAX_BPS as Balance;
+            self.accrue_revenue(protocol_fee);
+            if self
+                .env()
+                .transfer(seller, offer.amount - protocol_fee)
+                .is_err()
+            {
+                return Err(Error::WithdrawFailed);
             }
+
+            Self::emit_event(OfferAccepted {
+                name,
+                seller,
+                bidder,
+                amount: offer.amount,
+                protocol_fee,
+            });
+
             Ok(())
         }
 
-        #[ink(message)]
-        pub fn reset_controller(&mut self, names: Vec<String>) -> Result<()> {
-            let caller = self.env().caller();
-
-            for name in names.iter() {
-                let mut address_dict = self.get_address_dict_ref(&name)?;
-                let owner = address_dict.owner;
-                let controller = address_dict.controller;
+        /// Bid on an auctioned name. The transferred value is escrowed and the
+        /// previous highest bidder gets refunded.
+        #[ink(message, payable)]
+        pub fn bid(&mut self, name: String) -> Result<()> {
+            self.ensure_not_paused(PauseGroup::Marketplace)?;
 
-                if controller != caller {
-                    return Err(Error::NotAuthorised);
-                }
-                if controller != owner {
-                    address_dict.set_controller(owner);
-                    self.name_to_address_dict.insert(&name, &address_dict);
+            let bidder = self.env().caller();
+            let amount = self.env().transferred_value();
+            let current_time = self.env().block_timestamp();
 
-                    /* Remove the name from the old controller address */
-                    self.remove_name_from_controller(&controller, &name);
+            let Some(mut auction) = self.auctions.get(&name) else {
+                return Err(Error::AuctionNotFound);
+            };
+            if auction.has_ended(current_time) {
+                return Err(Error::AuctionEnded);
+            }
+            if amount < auction.min_bid() {
+                return Err(Error::BidTooLow);
+            }
+            self.ensure_auctionable(&name)?;
 
-                    /* Add the name to the new controller address */
-                    self.add_name_to_controller(&owner, &name);
+            /* Refund the outbid bidder */
+            if let Some((prev_bidder, prev_amount)) = auction.highest_bid {
+                self.refund_bid(prev_bidder, prev_amount)?;
+            }
+            self.escrowed_balance
+                .set(&(self.get_escrowed_balance() + amount));
 
-                    self.env().emit_event(SetController {
-                        name: name.to_string(),
-                        from: caller,
-                        old_controller: Some(controller),
-                        new_controller: owner,
-                    });
-                }
+            // @dev Anti-sniping: late bids extend the auction
+            if auction.end_time - current_time < auction.extension {
+                auction.end_time = current_time + auction.extension;
             }
+            auction.highest_bid = Some((bidder, amount));
+            self.auctions.insert(&name, &auction);
+
+            Self::emit_event(BidPlaced {
+                name,
+                bidder,
+                amount,
+                end_time: auction.end_time,
+            });
+
             Ok(())
         }
 
+        /// Settle an auction once it has ended. The name is registered to the highest bidder
+        /// and the winning bid is collected as registration fee.
+        /// @note auctions without bids are closed and the name is left as is
+        /// @note the winning bid is refunded if the name cannot be auctioned anymore
         #[ink(message)]
-        pub fn update_records(
-            &mut self,
-            name: String,
-            records: Vec<(String, Option<String>)>,
-            remove_rest: bool,
-        ) -> Result<()> {
-            let caller: AccountId = Self::env().caller();
-            self.ensure_controller_owner(&caller, &name)?;
+        pub fn settle_auction(&mut self, name: String) -> Result<()> {
+            self.ensure_not_paused(PauseGroup::Registration)?;
 
-            use ink::prelude::collections::BTreeMap;
+            let Some(auction) = self.auctions.get(&name) else {
+                return Err(Error::AuctionNotFound);
+            };
+            if !auction.has_ended(self.env().block_timestamp()) {
+                return Err(Error::AuctionNotEnded);
+            }
+            self.auctions.remove(&name);
 
-            let mut data = BTreeMap::new();
+            let Some((winner, amount)) = auction.highest_bid else {
+                Self::emit_event(AuctionSettled {
+                    name,
+                    winner: None,
+                    amount: 0,
+                });
+                return Ok(());
+            };
 
-            if !remove_rest {
-                self.get_records_ref(&name)
-                    .into_iter()
-                    .for_each(|(key, val)| {
-                        data.insert(key, val);
-                    });
+            let expiry_time =
+                self.env().block_timestamp() + YEAR * auction.years_to_register as u64;
+            let registered = self
+                .ensure_auctionable(&name)
+                .and_then(|_| self.register_name(&name, &winner, expiry_time));
+
+            // @dev The winning bid must not get stuck in the escrow
+            if registered.is_err() {
+                self.refund_bid(winner, amount)?;
+                Self::emit_event(AuctionSettled {
+                    name,
+                    winner: None,
+                    amount: 0,
+                });
+                return Ok(());
             }
 
-            records.into_iter().for_each(|(key, val)| {
-                match val {
-                    Some(v) => data.insert(key, v),
-                    None => data.remove(&key),
-                };
-            });
+            self.escrowed_balance
+                .set(&(self.get_escrowed_balance() - amount));
+            self.accrue_revenue(amount);
+            self.record_payment(&name, amount, 0);
 
-            let updated_records: Vec<(String, String)> = data.into_iter().collect();
-            self.records.insert(&name, &updated_records);
+            if self.reserved_names.contains(&name) {
+                self.reserved_names.remove(&name);
+                Self::emit_event(Reserve {
+                    name: name.clone(),
+                    account_id: None,
+                    action: false,
+                });
+            }
 
-            self.ensure_records_under_limit(&name)?;
+            Self::emit_event(FeeReceived {
+                name: name.clone(),
+                from: winner,
+                referrer: None,
+                referrer_addr: None,
+                received_fee: amount,
+                forwarded_referrer_fee: 0,
+            });
+
+            Self::emit_event(AuctionSettled {
+                name,
+                winner: Some(winner),
+                amount,
+            });
 
-            self.env().emit_event(RecordsUpdated { name, from: caller });
             Ok(())
         }
 
-        /// Returns the current status of the name
+        /// Offer to lease the control of a name to the tenant until the given timestamp.
+        /// The lease starts once the tenant accepts it by paying the price.
+        /// @note offering another lease overrides the pending one
         #[ink(message)]
-        pub fn get_name_status(&self, names: Vec<String>) -> Vec<NameStatus> {
-            let status = |name: String| {
-                if let Ok(user) = self.get_address_dict_ref(&name) {
-                    let lock_status = self.get_lock_info(name.clone());
-                    NameStatus::Registered(user, lock_status)
-                } else if let Some(user) = self.reserved_names.get(&name) {
-                    NameStatus::Reserved(user)
-                } else if self.is_name_allowed(&name) {
-                    NameStatus::Available
-                } else {
-                    NameStatus::Unavailable
-                }
-            };
-
-            names.into_iter().map(status).collect()
-        }
+        pub fn lease(
+            &mut self,
+            name: String,
+            tenant: AccountId,
+            until: u64,
+            price: Balance,
+        ) -> Result<()> {
+            self.ensure_not_paused(PauseGroup::Marketplace)?;
 
-        /// Get the addresses related to specific name
-        #[ink(message)]
-        pub fn get_address_dict(&self, name: String) -> Result<AddressDict> {
-            self.get_address_dict_ref(&name)
-        }
+            let caller = self.env().caller();
+            self.ensure_owner(&caller, &name)?;
+            self.ensure_unlocked(&name)
+                .map_err(|_| Error::NotAuthorised)?;
+            self.ensure_not_leased(&name)?;
+            self.end_expired_lease(&name);
 
-        /// Get owner of specific name.
-        #[ink(message)]
-        pub fn get_owner(&self, name: String) -> Result<AccountId> {
-            self.get_address_dict_ref(&name).map(|x| x.owner)
-        }
+            if tenant == [0u8; 32].into() {
+                return Err(Error::ZeroAddress);
+            }
+            let (_, expiry) = self.get_registration_period_ref(&name)?;
+            if until <= self.env().block_timestamp() || until > expiry {
+                return Err(Error::InvalidLeaseExpiry);
+            }
 
-        /// Get controller of specific name.
-        #[ink(message)]
-        pub fn get_controller(&self, name: String) -> Result<AccountId> {
-            self.get_address_dict_ref(&name).map(|x| x.controller)
-        }
+            self.leases.insert(&name, &Lease::new(tenant, until, price));
 
-        /// Get address for specific name.
-        #[ink(message, selector = 0xd259f7ba)]
-        pub fn get_address(&self, name: String) -> Result<AccountId> {
-            self.get_address_dict_ref(&name).map(|x| x.resolved)
-        }
+            Self::emit_event(LeaseOffered {
+                name,
+                tenant,
+                until,
+                price,
+            });
 
-        #[ink(message)]
-        pub fn get_registration_period(&self, name: String) -> Result<(u64, u64)> {
-            self.get_registration_period_ref(&name)
+            Ok(())
         }
 
-        /// Gets all records
-        #[ink(message)]
-        pub fn get_all_records(&self, name: String) -> Vec<(String, String)> {
-            self.get_records_ref(&name)
-        }
+        /// Accept a lease offered to the caller. The price is paid to the owner & the caller
+        /// becomes the controller and resolved address of the name until the lease ends.
+        #[ink(message, payable)]
+        pub fn accept_lease(&mut self, name: String) -> Result<()> {
+            self.ensure_not_paused(PauseGroup::Marketplace)?;
 
-        /// Gets an arbitrary record by key
-        #[ink(message)]
-        pub fn get_record(&self, name: String, key: String) -> Result<String> {
-            let info = self.get_records_ref(&name);
-            match info.iter().find(|tuple| tuple.0 == key) {
-                Some(val) => Ok(val.clone().1),
-                None => Err(Error::RecordNotFound),
+            let tenant = self.env().caller();
+            let Some(mut lease) = self.leases.get(&name) else {
+                return Err(Error::LeaseNotFound);
+            };
+            if lease.tenant != tenant || lease.started {
+                return Err(Error::LeaseNotFound);
+            }
+            if lease.until < self.env().block_timestamp() {
+                return Err(Error::InvalidLeaseExpiry);
             }
-        }
 
-        /// Returns all names the address owns
-        #[ink(message)]
-        pub fn get_owned_names_of_address(&self, owner: AccountId) -> Vec<String> {
-            let count = self.get_owner_to_name_count(owner);
+            let mut address_dict = self.get_address_dict_ref(&name)?;
+            self.handle_payment(lease.price, 0)?;
+            if self
+                .env()
+                .transfer(address_dict.owner, lease.price)
+                .is_err()
+            {
+                return Err(Error::WithdrawFailed);
+            }
 
-            (0..count)
-                .filter_map(|idx| {
-                    let name = self.owner_to_names.get((owner, idx)).expect("Infallible");
-                    match self.has_name_expired(&name) {
-                        Ok(false) => Some(name),
-                        _ => None,
-                    }
-                })
-                .collect()
-        }
+            /* The tenant takes over the control of the name */
+            let AddressDict {
+                controller,
+                resolved,
+                ..
+            } = address_dict;
+            lease.start(LeaseSnapshot {
+                controller,
+                resolved,
+                records: self.get_records_ref(&name),
+                chain_addresses: self.get_all_chain_addresses(name.clone()),
+            });
+            address_dict.set_controller(tenant);
+            address_dict.set_resolved(tenant);
+            self.name_to_address_dict.insert(&name, &address_dict);
 
-        #[ink(message)]
-        pub fn get_controlled_names_of_address(&self, controller: AccountId) -> Vec<String> {
-            let count = self.get_controller_to_name_count(controller);
+            self.remove_name_from_controller(&controller, &name);
+            self.add_name_to_controller(&tenant, &name);
+            self.remove_name_from_resolving(&resolved, &name);
+            self.add_name_to_resolving(&tenant, &name);
 
-            (0..count)
-                .filter_map(|idx| {
-                    let name = self
-                        .controller_to_names
-                        .get((controller, idx))
-                        .expect("Infallible");
-                    match self.has_name_expired(&name) {
-                        Ok(false) => Some(name),
-                        _ => None,
-                    }
-                })
-                .collect()
-        }
+            self.leases.insert(&name, &lease);
 
-        #[ink(message)]
-        pub fn get_resolving_names_of_address(&self, address: AccountId) -> Vec<String> {
-            let count = self.get_resolving_to_name_count(address);
+            Self::emit_event(LeaseStarted {
+                name,
+                tenant,
+                until: lease.until,
+                price: lease.price,
+            });
 
-            (0..count)
-                .filter_map(|idx| {
-                    let name = self
-                        .resolving_to_names
-                        .get((address, idx))
-                        .expect("Infallible");
-                    match self.has_name_expired(&name) {
-                        Ok(false) => Some(name),
-                        _ => None,
-                    }
-                })
-                .collect()
+            Ok(())
         }
 
+        /// End a lease once its period is over, reverting the control to the owner.
+        /// Pending leases can be withdrawn by the owner anytime.
         #[ink(message)]
-        pub fn get_primary_name(&self, address: AccountId) -> Result<String> {
-            /* Get the naive primary name of the address */
-            let Some(primary_name) = self.address_to_primary_name.get(address) else {
-                /* No primary name set */
-                return Err(Error::NoResolvedAddress);
+        pub fn end_lease(&mut self, name: String) -> Result<()> {
+            let Some(lease) = self.leases.get(&name) else {
+                return Err(Error::LeaseNotFound);
             };
+            if lease.is_active(self.env().block_timestamp()) {
+                return Err(Error::NameLeased);
+            }
 
-            /* Check that the primary name actually resolves to the claimed address */
-            let resolved_address = self.get_address(primary_name.clone());
-            if resolved_address != Ok(address) {
-                /* Resolved address is no longer valid */
-                return Err(Error::NoResolvedAddress);
+            if !lease.started {
+                let caller = self.env().caller();
+                self.ensure_owner(&caller, &name)?;
+
+                self.leases.remove(&name);
+                Self::emit_event(LeaseEnded {
+                    name,
+                    tenant: lease.tenant,
+                });
+                return Ok(());
             }
 
-            Ok(primary_name)
+            self.end_expired_lease(&name);
+            Ok(())
         }
 
+        /// Removes the associated state of expired-names from storage
         #[ink(message)]
-        pub fn get_primary_domain(&self, address: AccountId) -> Option<String> {
-            self.get_primary_name(address)
-                .map(|name| name + "." + &self.tld)
-                .ok()
+        pub fn clear_expired_names(&mut self, names: Vec<String>) -> Result<u128> {
+            let mut count = 0;
+            names.into_iter().for_each(|name| {
+                // Verify the name has expired (and is neither in redemption nor at a premium)
+                if self.has_name_expired(&name) == Ok(true)
+                    && self.get_redemption_info(&name).is_none()
+                    && self.get_release_premium_ref(&name) == 0
+                {
+                    self.remove_name(&name);
+                    count += 1;
+                }
+            });
+            Ok(count)
         }
 
+        /// Set primary name of an address (reverse record)
+        /// @note if name is set to None then the primary-name for the caller will be removed (if exists)
         #[ink(message)]
-        pub fn get_lock_info(&self, name: String) -> Option<AccountId> {
-            self.name_to_lock
-                .get(&name)
-                .filter(|_| self.has_name_expired(&name) == Ok(false))
+        pub fn set_primary_name(&mut self, primary_name: Option<String>) -> Result<()> {
+            self.do_set_primary_name(self.env().caller(), primary_name)
         }
 
-        #[ink(message)]
-        pub fn get_names_of_address(&self, address: AccountId) -> Vec<String> {
-            let resolved_names = self.get_resolving_names_of_address(address);
-            let controlled_names = self.get_controlled_names_of_address(address);
-            let owned_names = self.get_owned_names_of_address(address);
+        fn do_set_primary_name(
+            &mut self,
+            address: AccountId,
+            primary_name: Option<String>,
+        ) -> Result<()> {
+            match &primary_name {
+                Some(name) => {
+                    let resolved = self.get_address_dict_ref(name)?.resolved;
 
-            // Using BTreeSet to remove duplicates
-            let set: ink::prelude::collections::BTreeSet<String> =
-                [resolved_names, controlled_names, owned_names]
-                    .into_iter()
-                    .flatten()
-                    .collect();
+                    /* Ensure the target name resolves to the address */
+                    if resolved != address {
+                        return Err(Error::NoResolvedAddress);
+                    }
+                    self.address_to_primary_name.insert(address, name);
+                }
+                None => self.address_to_primary_name.remove(address),
+            };
 
-            set.into_iter().collect()
+            Self::emit_event(SetPrimaryName {
+                account: address,
+                primary_name,
+            });
+            Ok(())
         }
 
-        // @note count includes expired names as well
+        /// Set resolved address for specific name.
         #[ink(message)]
-        pub fn get_owner_to_name_count(&self, user: AccountId) -> u128 {
-            self.owner_to_name_count.get(user).unwrap_or(0)
+        pub fn set_address(&mut self, name: String, new_address: AccountId) -> Result<()> {
+            self.do_set_address(Self::env().caller(), name, new_address)
         }
 
-        // @note count includes expired names as well
-        #[ink(message)]
-        pub fn get_controller_to_name_count(&self, user: AccountId) -> u128 {
-            self.controller_to_name_count.get(user).unwrap_or(0)
-        }
+        fn do_set_address(
+            &mut self,
+            caller: AccountId,
+            name: String,
+            new_address: AccountId,
+        ) -> Result<()> {
+            self.ensure_not_paused(PauseGroup::Records)?;
 
-        // @note count includes expired names as well
-        #[ink(message)]
-        pub fn get_resolving_to_name_count(&self, user: AccountId) -> u128 {
-            self.resolving_to_name_count.get(user).unwrap_or(0)
-        }
+            /* Ensure the caller is the controller */
+            self.ensure_controller_owner(&caller, &name)?;
+            self.end_expired_lease(&name);
 
-        #[ink(message)]
-        pub fn get_records_size_limit(&self) -> Option<u32> {
-            self.records_size_limit
-        }
+            let mut address_dict = self.get_address_dict_ref(&name)?;
+            let old_address = address_dict.resolved;
+            address_dict.set_resolved(new_address);
+            self.name_to_address_dict.insert(&name, &address_dict);
 
-        #[ink(message)]
-        pub fn get_tld(&self) -> String {
-            self.tld.clone()
-        }
+            /* Remove the name from the old resolved address */
+            self.remove_name_from_resolving(&old_address, &name);
 
-        #[ink(message)]
-        pub fn get_base_uri(&self) -> String {
-            self.base_uri.clone()
+            /* Add the name to the new resolved address */
+            self.add_name_to_resolving(&new_address, &name);
+
+            Self::emit_event(SetAddress {
+                name,
+                from: caller,
+                old_address: Some(old_address),
+                new_address,
+            });
+            Ok(())
         }
 
-        /// (ADMIN-OPERATION)
-        /// Transfers `value` amount of tokens to the caller.
+        /// Set the address of a name on another chain (identified by its SLIP-0044 coin-type).
+        /// NOTE: The native address is always the one set via `set_address()`
         #[ink(message)]
-        pub fn withdraw(
+        pub fn set_chain_address(
             &mut self,
-            beneficiary: Option<AccountId>,
-            value: Option<Balance>,
+            name: String,
+            coin_type: CoinType,
+            address: Vec<u8>,
         ) -> Result<()> {
-            self.ensure_admin()?;
+            self.ensure_not_paused(PauseGroup::Records)?;
 
-            let beneficiary = beneficiary.unwrap_or(self.env().caller());
-            let value = value.unwrap_or(self.env().balance());
+            /* Ensure the caller is the controller */
+            let caller = Self::env().caller();
+            self.ensure_controller_owner(&caller, &name)?;
+            self.end_expired_lease(&name);
 
-            if beneficiary == [0u8; 32].into() {
-                return Err(Error::ZeroAddress);
+            let format =
+                AddressFormat::from_coin_type(coin_type).ok_or(Error::UnsupportedCoinType)?;
+            if !format.is_valid_address(&address) {
+                return Err(Error::InvalidChainAddress);
             }
 
-            if value > self.env().balance() {
-                return Err(Error::InsufficientBalance);
-            }
-            if self.env().transfer(beneficiary, value).is_err() {
-                return Err(Error::WithdrawFailed);
+            let mut coin_types = self.name_to_coin_types.get(&name).unwrap_or_default();
+            if !coin_types.contains(&coin_type) {
+                coin_types.push(coin_type);
+                self.name_to_coin_types.insert(&name, &coin_types);
             }
+            self.chain_addresses.insert((&name, &coin_type), &address);
 
+            Self::emit_event(SetChainAddress {
+                name,
+                from: caller,
+                coin_type,
+                new_address: Some(address),
+            });
             Ok(())
         }
 
-        /// (ADMIN-OPERATION)
-        /// Reserve name name for specific addresses
-        // @dev (name, None) denotes that the name is reserved but not tied to any address yet
+        /// Remove the address of a name on another chain
         #[ink(message)]
-        pub fn add_reserved_names(
-            &mut self,
-            set: Vec<(String, Option<AccountId>)>,
-            skip_name_checker: bool,
-        ) -> Result<()> {
-            self.ensure_admin()?;
-
-            for (name, addr) in set.iter() {
-                if name.is_empty() {
-                    return Err(Error::NameEmpty);
-                }
-                if self.has_name_expired(name) == Ok(false) {
-                    return Err(Error::NameAlreadyExists);
-                }
-
-                if !skip_name_checker && !self.is_name_allowed(name) {
-                    return Err(Error::NameNotAllowed);
-                }
+        pub fn clear_chain_address(&mut self, name: String, coin_type: CoinType) -> Result<()> {
+            self.ensure_not_paused(PauseGroup::Records)?;
 
-                self.reserved_names.insert(name, addr);
-                self.env().emit_event(Reserve {
-                    name: name.clone(),
-                    account_id: *addr,
-                    action: true,
-                });
-            }
-            Ok(())
-        }
+            /* Ensure the caller is the controller */
+            let caller = Self::env().caller();
+            self.ensure_controller_owner(&caller, &name)?;
+            self.end_expired_lease(&name);
 
-        /// (ADMIN-OPERATION)
-        /// Remove given names from the list of reserved names
-        #[ink(message)]
-        pub fn remove_reserved_name(&mut self, set: Vec<String>) -> Result<()> {
-            self.ensure_admin()?;
+            let mut coin_types = self.name_to_coin_types.get(&name).unwrap_or_default();
+            coin_types.retain(|&ele| ele != coin_type);
+            self.name_to_coin_types.insert(&name, &coin_types);
+            self.chain_addresses.remove((&name, &coin_type));
 
-            set.iter().for_each(|name| {
-                if self.reserved_names.contains(name) {
-                    self.reserved_names.remove(name);
-                    self.env().emit_event(Reserve {
-                        name: name.clone(),
-                        account_id: None,
-                        action: false,
-                    });
-                }
+            Self::emit_event(SetChainAddress {
+                name,
+                from: caller,
+                coin_type,
+                new_address: None,
             });
             Ok(())
         }
 
-        /// (ADMIN-OPERATION)
-        /// Update the limit of records allowed to store per name
         #[ink(message)]
-        pub fn set_records_size_limit(&mut self, limit: Option<u32>) -> Result<()> {
-            self.ensure_admin()?;
-            self.records_size_limit = limit;
-            Ok(())
+        pub fn set_controller(&mut self, name: String, new_controller: AccountId) -> Result<()> {
+            self.do_set_controller(Self::env().caller(), name, new_controller)
         }
 
-        fn ensure_owner(&self, address: &AccountId, name: &str) -> Result<()> {
-            let AddressDict { owner, .. } = self.get_address_dict_ref(&name)?;
-            if address != &owner {
-                Err(Error::CallerIsNotOwner)
-            } else {
-                Ok(())
-            }
-        }
+        fn do_set_controller(
+            &mut self,
+            caller: AccountId,
+            name: String,
+            new_controller: AccountId,
+        ) -> Result<()> {
+            self.ensure_not_paused(PauseGroup::Records)?;
 
-        fn ensure_controller_owner(&self, address: &AccountId, name: &str) -> Result<()> {
-            /* Ensure that the address has the right to control the target name */
-            let AddressDict {
-                owner, controller, ..
-            } = self.get_address_dict_ref(&name)?;
+            /* Ensure caller is either controller or owner */
+            self.ensure_controller_owner(&caller, &name)?;
+            self.end_expired_lease(&name);
 
-            if address != &controller && address != &owner {
-                Err(Error::CallerIsNotController)
-            } else {
-                Ok(())
-            }
-        }
+            let mut address_dict = self.get_address_dict_ref(&name)?;
+            let old_controller = address_dict.controller;
+            address_dict.set_controller(new_controller);
+            self.name_to_address_dict.insert(&name, &address_dict);
 
-        fn ensure_unlocked(&self, name: &str) -> core::result::Result<(), PSP34Error> {
-            match self.name_to_lock.contains(name) {
-                true => Err(PSP34Error::Custom("Name is locked".to_string())),
-                false => Ok(()),
-            }
-        }
+            /* Remove the name from the old controller */
+            self.remove_name_from_controller(&old_controller, &name);
 
-        fn ensure_records_under_limit(&self, name: &str) -> Result<()> {
-            let size = self.records.size(name).unwrap_or(0);
-            let limit = self.records_size_limit.unwrap_or(u32::MAX);
+            /* Add the name to the new controller */
+            self.add_name_to_controller(&new_controller, &name);
 
-            match size <= limit {
-                true => Ok(()),
-                false => Err(Error::RecordsOverflow),
-            }
+            Self::emit_event(SetController {
+                name,
+                from: caller,
+                old_controller: Some(old_controller),
+                new_controller,
+            });
+
+            Ok(())
         }
 
-        fn register_name(&mut self, name: &str, recipient: &AccountId, expiry: u64) -> Result<()> {
-            match self.has_name_expired(&name) {
-                Ok(false) => return Err(Error::NameAlreadyExists), // Name is already registered
-                Ok(true) => self.remove_name(&name), // Clean the expired name state first
-                _ => (),                             // Name is available
-            }
+        #[ink(message)]
+        pub fn reset_resolved_address(&mut self, names: Vec<String>) -> Result<()> {
+            let caller = self.env().caller();
 
-            if recipient == &[0u8; 32].into() {
-                return Err(Error::ZeroAddress);
-            }
+            for name in names.iter() {
+                self.end_expired_lease(name);
+                let mut address_dict = self.get_address_dict_ref(name)?;
+                let owner = address_dict.owner;
+                let resolved = address_dict.resolved;
 
-            let registration = self.env().block_timestamp();
+                if resolved != caller {
+                    return Err(Error::NotAuthorised);
+                }
+                if resolved != owner {
+                    address_dict.set_resolved(owner);
+                    self.name_to_address_dict.insert(name, &address_dict);
 
-            let address_dict = AddressDict::new(recipient.clone());
-            self.name_to_address_dict.insert(name, &address_dict);
-            self.name_to_period.insert(name, &(registration, expiry));
+                    /* Remove the name from the old resolved address */
+                    self.remove_name_from_resolving(&resolved, name);
 
-            /* Update convenience mapping for owned names */
-            self.add_name_to_owner(recipient, name);
+                    /* Add the name to the new resolved address */
+                    self.add_name_to_resolving(&owner, name);
 
-            /* Update convenience mapping for controlled names */
-            self.add_name_to_controller(recipient, name);
+                    Self::emit_event(SetAddress {
+                        name: name.to_string(),
+                        from: caller,
+                        old_address: Some(resolved),
+                        new_address: owner,
+                    });
+                }
+            }
+            Ok(())
+        }
 
-            /* Update convenience mapping for resolved names */
-            self.add_name_to_resolving(recipient, name);
+        #[ink(message)]
+        pub fn reset_controller(&mut self, names: Vec<String>) -> Result<()> {
+            let caller = self.env().caller();
 
-            self.total_supply += 1;
+            for name in names.iter() {
+                self.end_expired_lease(name);
+                let mut address_dict = self.get_address_dict_ref(name)?;
+                let owner = address_dict.owner;
+                let controller = address_dict.controller;
 
-            /* Emit register event */
-            Self::env().emit_event(Register {
-                name: name.to_string(),
-                from: *recipient,
-                registration_timestamp: registration,
-                expiration_timestamp: expiry,
-            });
+                if controller != caller {
+                    return Err(Error::NotAuthorised);
+                }
+                if controller != owner {
+                    address_dict.set_controller(owner);
+                    self.name_to_address_dict.insert(name, &address_dict);
 
-            self.env().emit_event(Transfer {
-                from: None,
-                to: Some(*recipient),
-                id: name.to_string().into(),
-            });
+                    /* Remove the name from the old controller address */
+                    self.remove_name_from_controller(&controller, name);
 
+                    /* Add the name to the new controller address */
+                    self.add_name_to_controller(&owner, name);
+
+                    Self::emit_event(SetController {
+                        name: name.to_string(),
+                        from: caller,
+                        old_controller: Some(controller),
+                        new_controller: owner,
+                    });
+                }
+            }
             Ok(())
         }
 
-        fn remove_name(&mut self, name: &str) {
-            let Ok(address_dict) = self.get_address_dict_ref(&name) else {
-                return;
-            };
-
-            self.name_to_address_dict.remove(name);
-            self.name_to_lock.remove(name);
-            self.name_to_period.remove(name);
-            self.records.remove(name);
+        #[ink(message)]
+        pub fn update_records(
+            &mut self,
+            name: String,
+            records: Vec<(String, Option<String>)>,
+            remove_rest: bool,
+        ) -> Result<()> {
+            self.do_update_records(Self::env().caller(), name, records, remove_rest)
+        }
 
-            self.remove_name_from_owner(&address_dict.owner, &name);
-            self.remove_name_from_controller(&address_dict.controller, &name);
-            self.remove_name_from_resolving(&address_dict.resolved, &name);
+        fn do_update_records(
+            &mut self,
+            caller: AccountId,
+            name: String,
+            records: Vec<(String, Option<String>)>,
+            remove_rest: bool,
+        ) -> Result<()> {
+            self.ensure_not_paused(PauseGroup::Records)?;
 
-            self.total_supply -= 1;
+            self.ensure_controller_owner(&caller, &name)?;
+            self.end_expired_lease(&name);
 
-            self.env().emit_event(Transfer {
-                from: Some(address_dict.owner),
-                to: None,
-                id: name.to_string().into(),
-            });
-        }
+            use ink::prelude::collections::BTreeMap;
 
-        fn transfer_name(
-            &mut self,
-            to: AccountId,
-            name: &str,
-            keep_records: bool,
-            keep_controller: bool,
-            keep_resolving: bool,
-            data: &Vec<u8>,
-        ) -> core::result::Result<(), PSP34Error> {
-            self.ensure_unlocked(name)?;
+            let mut data = BTreeMap::new();
 
-            if to == [0u8; 32].into() {
-                return Err(PSP34Error::Custom("Zero address".to_string()));
+            if !remove_rest {
+                self.get_records_ref(&name)
+                    .into_iter()
+                    .for_each(|(key, val)| {
+                        data.insert(key, val);
+                    });
             }
 
-            let id: Id = name.to_string().into();
-            let mut address_dict = self
-                .get_address_dict_ref(&name)
-                .map_err(|_| PSP34Error::TokenNotExists)?;
+            records.into_iter().for_each(|(key, val)| {
+                match val {
+                    Some(v) => data.insert(key, v),
+                    None => data.remove(&key),
+                };
+            });
 
-            let AddressDict {
-                owner,
-                controller,
-                resolved,
-            } = address_dict;
+            let updated_records: Vec<(String, String)> = data.into_iter().collect();
+            self.records.insert(&name, &updated_records);
 
-            // Ensure the caller is authorised to transfer the name
-            let caller = self.env().caller();
-            if caller != owner && !self.allowance(owner, caller, Some(id.clone())) {
-                return Err(PSP34Error::NotApproved);
-            }
+            self.ensure_records_under_limit(&name)?;
 
-            address_dict.owner = to;
-            self.remove_name_from_owner(&owner, &name);
-            self.add_name_to_owner(&to, &name);
+            Self::emit_event(RecordsUpdated { name, from: caller });
+            Ok(())
+        }
 
-            if !keep_controller {
-                address_dict.controller = to;
-                self.remove_name_from_controller(&controller, &name);
-                self.add_name_to_controller(&to, &name);
+        /// Approve the operator on behalf of the owner, who signed (ECDSA) `get_permit_hash()`
+        /// with their next permit nonce. Same as the owner calling `approve(operator, id, true)`.
+        ///
+        /// NOTE: The owner must be an ECDSA account. sr25519 owners have to call `approve()`.
+        #[ink(message)]
+        pub fn permit(
+            &mut self,
+            owner: AccountId,
+            operator: AccountId,
+            id: Option<Id>,
+            deadline: u64,
+            signature: [u8; 65],
+        ) -> core::result::Result<(), PSP34Error> {
+            if deadline < self.env().block_timestamp() {
+                return Err(PSP34Error::Custom("Permit expired".to_string()));
             }
 
-            if !keep_resolving {
-                address_dict.resolved = to;
-                self.remove_name_from_resolving(&resolved, &name);
-                self.add_name_to_resolving(&to, &name);
+            let nonce = self.get_permit_nonce(owner);
+            let hash = self.get_permit_hash(owner, operator, id.clone(), nonce, deadline);
+            if self.recover_signer(&signature, &hash) != Some(owner) {
+                return Err(PSP34Error::Custom("Invalid signature".to_string()));
             }
 
-            if !keep_records {
-                self.records.remove(name);
+            if operator == [0u8; 32].into() {
+                return Err(PSP34Error::Custom("Zero address".to_string()));
+            }
+            if owner == operator {
+                return Err(PSP34Error::SelfApprove);
+            }
+            if let Some(id) = &id {
+                if self.owner_of(id.clone()) != Some(owner) {
+                    return Err(PSP34Error::NotApproved);
+                }
             }
 
-            self.name_to_address_dict.insert(name, &address_dict);
+            self.permit_nonces.insert(owner, &(nonce + 1));
+            let timestamp = self.env().block_timestamp();
             self.operator_approvals
-                .remove((&owner, &caller, &Some(id.clone())));
+                .insert((&owner, &operator, &id), &timestamp);
 
-            self.safe_transfer_check(&caller, &owner, &to, &id, &data)?;
-
-            Self::env().emit_event(Transfer {
-                from: Some(owner),
-                to: Some(to),
+            Self::emit_event(Approval {
+                owner,
+                operator,
                 id,
+                approved: true,
             });
-
             Ok(())
         }
 
-        #[cfg_attr(test, allow(unused))]
-        fn safe_transfer_check(
+        /// Execute an action on behalf of the (ECDSA) signer of `get_meta_tx_hash()`,
+        /// as if the signer were the caller. The nonce must be the signer's next nonce.
+        ///
+        /// NOTE: Only ECDSA accounts can sign meta-transactions. Names held by sr25519 accounts
+        /// (the default of most Aleph Zero wallets) have to be managed by direct calls.
+        #[ink(message)]
+        pub fn execute_meta_tx(
             &mut self,
-            operator: &AccountId,
-            from: &AccountId,
-            to: &AccountId,
-            id: &Id,
-            data: &Vec<u8>,
-        ) -> core::result::Result<(), PSP34Error> {
-            // @dev This is disabled during tests due to the use of `invoke_contract()` not being
-            // supported (tests end up panicking).
-            #[cfg(not(test))]
-            {
-                use ink::env::call::{build_call, ExecutionInput, Selector};
+            action: MetaAction,
+            nonce: u128,
+            deadline: u64,
+            signature: [u8; 65],
+        ) -> Result<()> {
+            if deadline < self.env().block_timestamp() {
+                return Err(Error::DeadlineExpired);
+            }
 
-                const BEFORE_RECEIVED_SELECTOR: [u8; 4] = [0xBB, 0x7D, 0xF7, 0x80];
+            let hash = self.get_meta_tx_hash(action.clone(), nonce, deadline);
+            let signer = self
+                .recover_signer(&signature, &hash)
+                .ok_or(Error::InvalidSignature)?;
 
-                let result = build_call::<Environment>()
-                    .call(*to)
-                    .call_flags(ink::env::CallFlags::default().set_allow_reentry(true))
-                    .exec_input(
-                        ExecutionInput::new(Selector::new(BEFORE_RECEIVED_SELECTOR))
-                            .push_arg(operator)
-                            .push_arg(from)
-                            .push_arg(id)
-                            .push_arg::<Vec<u8>>(data.clone()),
-                    )
-                    .returns::<core::result::Result<(), u8>>()
-                    .params()
-                    .try_invoke();
+            if nonce != self.get_meta_tx_nonce(signer) {
+                return Err(Error::InvalidNonce);
+            }
+            self.meta_tx_nonces.insert(signer, &(nonce + 1));
 
-                match result {
-                    Ok(v) => {
-                        ink::env::debug_println!(
-                            "Received return value \"{:?}\" from contract {:?}",
-                            v.clone()
-                                .expect("Call should be valid, don't expect a `LangError`."),
-                            from
-                        );
-                        assert_eq!(
-                            v.clone()
-                                .expect("Call should be valid, don't expect a `LangError`."),
-                            Ok(()),
-                            "The recipient contract at {to:?} does not accept token transfers.\n
-                            Expected: Ok(()), Got {v:?}"
-                        )
-                    }
-                    Err(e) => {
-                        match e {
-                            ink::env::Error::CodeNotFound | ink::env::Error::NotCallable => {
-                                // Our recipient wasn't a smart contract, so there's nothing more for
-                                // us to do
-                                ink::env::debug_println!(
-                                    "Recipient at {:?} from is not a smart contract ({:?})",
-                                    from,
-                                    e
-                                );
-                            }
-                            _ => {
-                                // We got some sort of error from the call to our recipient smart
-                                // contract, and as such we must revert this call
-                                panic!("Got error \"{e:?}\" while trying to call {from:?}")
-                            }
-                        }
-                    }
+            match action {
+                MetaAction::SetAddress { name, address } => {
+                    self.do_set_address(signer, name, address)
                 }
-            }
+                MetaAction::SetController { name, controller } => {
+                    self.do_set_controller(signer, name, controller)
+                }
+                MetaAction::UpdateRecords {
+                    name,
+                    records,
+                    remove_rest,
+                } => self.do_update_records(signer, name, records, remove_rest),
+                MetaAction::SetPrimaryName { primary_name } => {
+                    self.do_set_primary_name(signer, primary_name)
+                }
+            }?;
 
+            Self::emit_event(MetaTxExecuted {
+                signer,
+                relayer: self.env().caller(),
+                nonce,
+            });
             Ok(())
         }
 
-        /// Adds a name to owners' collection
-        fn add_name_to_owner(&mut self, owner: &AccountId, name: &str) {
-            let name = name.to_string();
-            let count = self.get_owner_to_name_count(*owner);
+        /// Returns the current status of the name
+        #[ink(message)]
+        pub fn get_name_status(&self, names: Vec<String>) -> Vec<NameStatus> {
+            let status = |name: String| {
+                if let Ok(user) = self.get_address_dict_ref(&name) {
+                    match self.get_active_lease_ref(&name) {
+                        Some(lease) => NameStatus::Leased(user, lease),
+                        None => NameStatus::Registered(user, self.get_lock_info(name.clone())),
+                    }
+                } else if let Some((owner, end)) = self.get_redemption_info(&name) {
+                    NameStatus::Redemption(owner, end)
+                } else if let Some(user) = self.reserved_names.get(&name) {
+                    NameStatus::Reserved(user)
+                } else if self.is_name_allowed(&name) {
+                    match self.get_release_premium_ref(&name) {
+                        0 => NameStatus::Available,
+                        premium => NameStatus::Premium(premium),
+                    }
+                } else {
+                    NameStatus::Unavailable
+                }
+            };
 
-            self.owner_to_names.insert((owner, &count), &name);
-            self.name_to_owner_index.insert(&name, &count);
-            self.owner_to_name_count.insert(owner, &(count + 1));
+            names.into_iter().map(status).collect()
         }
 
-        /// Adds a name to controllers' collection
-        fn add_name_to_controller(&mut self, controller: &AccountId, name: &str) {
-            let name = name.to_string();
-            let count = self.get_controller_to_name_count(*controller);
-
-            self.controller_to_names.insert((controller, &count), &name);
-            self.name_to_controller_index.insert(&name, &count);
-            self.controller_to_name_count
-                .insert(controller, &(count + 1));
+        /// Get the addresses related to specific name
+        #[ink(message)]
+        pub fn get_address_dict(&self, name: String) -> Result<AddressDict> {
+            self.get_address_dict_ref(&name)
         }
 
-        /// Adds a name to resolvings' collection
-        fn add_name_to_resolving(&mut self, resolving: &AccountId, name: &str) {
-            let name = name.to_string();
-            let count = self.get_resolving_to_name_count(*resolving);
+        /// Get owner of specific name.
+        #[ink(message)]
+        pub fn get_owner(&self, name: String) -> Result<AccountId> {
+            self.get_address_dict_ref(&name).map(|x| x.owner)
+        }
 
-            self.resolving_to_names.insert((resolving, &count), &name);
-            self.name_to_resolving_index.insert(&name, &count);
-            self.resolving_to_name_count.insert(resolving, &(count + 1));
+        /// Get controller of specific name.
+        #[ink(message)]
+        pub fn get_controller(&self, name: String) -> Result<AccountId> {
+            self.get_address_dict_ref(&name).map(|x| x.controller)
         }
 
-        /// Deletes a name from owner
-        fn remove_name_from_owner(&mut self, owner: &AccountId, name: &str) {
-            let idx = self.name_to_owner_index.get(name).expect("Infallible");
-            let count = self.get_owner_to_name_count(*owner);
+        /// Get address for specific name.
+        #[ink(message, selector = 0xd259f7ba)]
+        pub fn get_address(&self, name: String) -> Result<AccountId> {
+            self.get_address_dict_ref(&name).map(|x| x.resolved)
+        }
 
-            // if name is not stored at the last index
-            if idx != count - 1 {
-                // swap last index item to pos:idx
-                let last_name = self
-                    .owner_to_names
-                    .get((owner, (count - 1)))
-                    .expect("Infallible");
-                self.owner_to_names.insert((owner, idx), &last_name);
-                self.name_to_owner_index.insert(&last_name, &idx);
-            }
+        /// Get the addresses of a name for the given coin-types (chains)
+        #[ink(message)]
+        pub fn get_chain_addresses(
+            &self,
+            name: String,
+            coin_types: Vec<CoinType>,
+        ) -> Vec<Option<Vec<u8>>> {
+            let all_addresses = self.get_all_chain_addresses(name);
 
-            // remove last index
-            self.owner_to_names.remove((owner, count - 1));
-            self.name_to_owner_index.remove(name);
-            self.owner_to_name_count.insert(owner, &(count - 1));
+            coin_types
+                .into_iter()
+                .map(|coin_type| {
+                    all_addresses
+                        .iter()
+                        .find(|(ele, _)| *ele == coin_type)
+                        .map(|(_, address)| address.clone())
+                })
+                .collect()
         }
 
-        /// Deletes a name from controllers' collection
-        fn remove_name_from_controller(&mut self, controller: &AccountId, name: &str) {
-            let idx = self.name_to_controller_index.get(name).expect("Infallible");
-            let count = self.get_controller_to_name_count(*controller);
-
-            // if name is not stored at the last index
-            if idx != count - 1 {
-                // swap last index item to pos:idx
-                let last_name = self
-                    .controller_to_names
-                    .get((controller, (count - 1)))
-                    .expect("Infallible");
-                self.controller_to_names
-                    .insert((controller, idx), &last_name);
-                self.name_to_controller_index.insert(&last_name, &idx);
+        /// Get all (coin-type, address) pairs set for a name
+        #[ink(message)]
+        pub fn get_all_chain_addresses(&self, name: String) -> Vec<(CoinType, Vec<u8>)> {
+            if self.has_name_expired(&name) != Ok(false) {
+                return Vec::new();
+            }
+            // @dev The addresses set by the tenant are (lazily) reverted once the lease ends
+            if let Some(snapshot) = self.get_ended_lease_snapshot(&name) {
+                return snapshot.chain_addresses;
             }
 
-            // remove last index
-            self.controller_to_names.remove((controller, count - 1));
-            self.name_to_controller_index.remove(name);
-            self.controller_to_name_count
-                .insert(controller, &(count - 1));
+            self.name_to_coin_types
+                .get(&name)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|coin_type| {
+                    self.chain_addresses
+                        .get((&name, &coin_type))
+                        .map(|address| (coin_type, address))
+                })
+                .collect()
         }
 
-        /// Deletes a name from resolvings' collection
-        fn remove_name_from_resolving(&mut self, resolving: &AccountId, name: &str) {
-            let idx = self.name_to_resolving_index.get(name).expect("Infallible");
-            let count = self.get_resolving_to_name_count(*resolving);
+        #[ink(message)]
+        pub fn get_registration_period(&self, name: String) -> Result<(u64, u64)> {
+            self.get_registration_period_ref(&name)
+        }
 
-            // if name is not stored at the last index
-            if idx != count - 1 {
-                // swap last index item to pos:idx
-                let last_name = self
-                    .resolving_to_names
-                    .get((resolving, (count - 1)))
-                    .expect("Infallible");
-                self.resolving_to_names.insert((resolving, idx), &last_name);
-                self.name_to_resolving_index.insert(&last_name, &idx);
+        /// Gets all records
+        #[ink(message)]
+        pub fn get_all_records(&self, name: String) -> Vec<(String, String)> {
+            self.get_records_ref(&name)
+        }
+
+        /// Gets an arbitrary record by key
+        #[ink(message)]
+        pub fn get_record(&self, name: String, key: String) -> Result<String> {
+            let info = self.get_records_ref(&name);
+            match info.iter().find(|tuple| tuple.0 == key) {
+                Some(val) => Ok(val.clone().1),
+                None => Err(Error::RecordNotFound),
             }
+        }
 
-            // remove last index
-            self.resolving_to_names.remove((resolving, count - 1));
-            self.name_to_resolving_index.remove(name);
-            self.resolving_to_name_count.insert(resolving, &(count - 1));
+        /// Returns all names the address owns
+        #[ink(message)]
+        pub fn get_owned_names_of_address(&self, owner: AccountId) -> Vec<String> {
+            self.owned_names(owner).collect()
+        }
 
-            /* Check if the resolved address had this name set as the primary name */
-            /* If yes -> clear it */
-            if self.address_to_primary_name.get(resolving) == Some(name.to_string()) {
-                self.address_to_primary_name.remove(resolving);
+        #[ink(message)]
+        pub fn get_controlled_names_of_address(&self, controller: AccountId) -> Vec<String> {
+            let slots = self.get_controller_to_name_slots(controller);
 
-                self.env().emit_event(SetPrimaryName {
-                    account: *resolving,
-                    primary_name: None,
-                });
-            }
+            (0..slots)
+                .filter_map(|idx| {
+                    let name = self.controller_to_names.get((controller, idx))?;
+                    match self.has_name_expired(&name) {
+                        Ok(false) => Some(name),
+                        _ => None,
+                    }
+                })
+                .collect()
         }
 
-        fn is_name_allowed(&self, name: &str) -> bool {
-            /* Name cannot be empty */
-            if name.is_empty() {
-                return false;
-            }
+        #[ink(message)]
+        pub fn get_resolving_names_of_address(&self, address: AccountId) -> Vec<String> {
+            let slots = self.get_resolving_to_name_slots(address);
 
-            /* Name must be legal */
-            if let Some(name_checker) = &self.name_checker {
-                if name_checker.is_name_allowed(name.to_string()) != Ok(()) {
-                    return false;
-                }
-            }
-            true
+            (0..slots)
+                .filter_map(|idx| {
+                    let name = self.resolving_to_names.get((address, idx))?;
+                    match self.has_name_expired(&name) {
+                        Ok(false) => Some(name),
+                        _ => None,
+                    }
+                })
+                .collect()
         }
 
+        /// Returns all (active) subnames issued under the given parent name
         #[ink(message)]
-        pub fn get_name_price(
-            &self,
-            name: String,
-            recipient: AccountId,
-            years_to_register: u8,
-            referrer: Option<String>,
-        ) -> Result<(Balance, Balance, Balance, Option<AccountId>)> {
-            let (base_price, premium) = match &self.fee_calculator {
-                None => (1000, 0), // For unit testing only
-                Some(model) => model
-                    .get_name_price(name.clone(), years_to_register)
-                    .map_err(|e| Error::FeeError(e))?,
-            };
-            let price = base_price + premium;
-            let mut discount = 0;
-            let mut referrer_addr = None;
+        pub fn get_subnames(&self, parent: String) -> Vec<String> {
+            let count = self.get_parent_to_subname_count(parent.clone());
 
-            if let Some(referrer_name) = referrer {
-                if self.validate_referrer(recipient, referrer_name.clone()) {
-                    referrer_addr = Some(self.get_address(referrer_name).unwrap());
+            (0..count)
+                .filter_map(|idx| {
+                    let name = self
+                        .parent_to_subnames
+                        .get((&parent, idx))
+                        .expect("Infallible");
+                    match self.has_name_expired(&name) {
+                        Ok(false) => Some(name),
+                        _ => None,
+                    }
+                })
+                .collect()
+        }
 
-                    // discount = 5 * price / 100; // 5% discount
-                    // overflow-check bug patch
-                    let tmp = 5 * price;
-                    discount = (tmp >> 7) + (tmp >> 9) + (tmp >> 12); // 5.005% discount
-                }
+        #[ink(message)]
+        pub fn get_primary_name(&self, address: AccountId) -> Result<String> {
+            /* Get the naive primary name of the address */
+            let Some(primary_name) = self.address_to_primary_name.get(address) else {
+                /* No primary name set */
+                return Err(Error::NoResolvedAddress);
+            };
+
+            /* Check that the primary name actually resolves to the claimed address */
+            let resolved_address = self.get_address(primary_name.clone());
+            if resolved_address != Ok(address) {
+                /* Resolved address is no longer valid */
+                return Err(Error::NoResolvedAddress);
             }
 
-            Ok((base_price, premium, discount, referrer_addr))
+            Ok(primary_name)
         }
 
         #[ink(message)]
-        pub fn batch_get_name_price(
-            &self,
-            input: Vec<(String, AccountId, u8, Option<String>)>,
-        ) -> Vec<Result<(Balance, Balance, Balance, Option<AccountId>)>> {
-            input
-                .into_iter()
-                .map(|item| self.get_name_price(item.0, item.1, item.2, item.3))
-                .collect()
+        pub fn get_primary_domain(&self, address: AccountId) -> Option<String> {
+            self.get_primary_name(address)
+                .map(|name| name + "." + &self.tld)
+                .ok()
         }
 
         #[ink(message)]
-        pub fn validate_referrer(&self, recipient: AccountId, referrer_name: String) -> bool {
-            self.get_address_dict_ref(&referrer_name)
-                .map_or(false, |x| {
-                    recipient != x.owner && recipient != x.controller && recipient != x.resolved
-                })
+        pub fn get_lock_info(&self, name: String) -> Option<AccountId> {
+            self.name_to_lock
+                .get(&name)
+                .filter(|_| self.has_name_expired(&name) == Ok(false))
         }
 
-        fn get_address_dict_ref(&self, name: &str) -> Result<AddressDict> {
-            self.name_to_address_dict
-                .get(name)
-                .filter(|_| self.has_name_expired(name) == Ok(false))
-                .ok_or(Error::NameDoesntExist)
+        #[ink(message)]
+        pub fn get_names_of_address(&self, address: AccountId) -> Vec<String> {
+            let resolved_names = self.get_resolving_names_of_address(address);
+            let controlled_names = self.get_controlled_names_of_address(address);
+            let owned_names = self.get_owned_names_of_address(address);
+
+            // Using BTreeSet to remove duplicates
+            let set: ink::prelude::collections::BTreeSet<String> =
+                [resolved_names, controlled_names, owned_names]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+
+            set.into_iter().collect()
         }
 
-        fn get_records_ref(&self, name: &str) -> Vec<(String, String)> {
-            self.records
-                .get(name)
-                .filter(|_| self.has_name_expired(name) == Ok(false))
-                .unwrap_or_default()
+        /// Returns all the registered names (paginated)
+        /// @returns (names, cursor) where cursor is the offset of the next page (if any)
+        /// @note names follow the order of `token_by_index()`, which changes upon removals
+        #[ink(message)]
+        pub fn get_all_names(&self, offset: u128, limit: u128) -> (Vec<String>, Option<u128>) {
+            self.paginate_names(
+                self.get_all_names_count(),
+                offset,
+                limit,
+                |idx| self.all_names.get(idx),
+                Some,
+            )
         }
 
-        fn get_registration_period_ref(&self, name: &str) -> Result<(u64, u64)> {
-            self.name_to_period
-                .get(name)
-                .map(|(start, expiry)| {
-                    let expiry = match expiry {
-                        0 => 0,
-                        _ if expiry <= GRACE_TIMESTAMP => GRACE_TIMESTAMP,
-                        _ => expiry,
-                    };
-                    (start, expiry)
-                })
-                .ok_or(Error::NameDoesntExist)
+        /// Returns the number of names in the global index
+        #[ink(message)]
+        pub fn get_all_names_count(&self) -> u128 {
+            self.all_names_count.get_or_default()
         }
 
-        fn has_name_expired(&self, name: &str) -> Result<bool> {
-            match self.get_registration_period_ref(name) {
-                Ok((_, expiry)) => Ok(expiry <= self.env().block_timestamp()),
-                _ => Err(Error::NameDoesntExist),
-            }
+        /// Paginated version of `get_owned_names_of_address()`
+        /// @returns (names, cursor) where cursor is the slot of the next page (if any)
+        /// @note slots are stable, i.e. removing a name doesn't shift the remaining ones
+        #[ink(message)]
+        pub fn get_owned_names_of_address_paginated(
+            &self,
+            owner: AccountId,
+            offset: u128,
+            limit: u128,
+        ) -> (Vec<String>, Option<u128>) {
+            let (names, cursor) =
+                self.paginate_collection(owner, (NameCollection::Owned, offset), limit, Some);
+            (names, cursor.map(|(_, slot)| slot))
         }
 
-        fn get_static_attribute_ref(&self, name: &str, key: &str) -> Option<String> {
-            match key {
-                "TLD" => Some(self.tld.clone()),
-                "Length" => Some(name.chars().count().to_string()),
-                "Registration" => Some(match self.get_registration_period_ref(&name) {
-                    Ok(period) => period.0.to_string(),
-                    _ => String::new(),
-                }),
-                "Expiration" => Some(match self.get_registration_period_ref(&name) {
-                    Ok(period) => period.1.to_string(),
-                    _ => String::new(),
-                }),
-                _ => None,
-            }
+        /// Paginated version of `get_controlled_names_of_address()`
+        /// @returns (names, cursor) where cursor is the slot of the next page (if any)
+        #[ink(message)]
+        pub fn get_controlled_names_of_address_paginated(
+            &self,
+            controller: AccountId,
+            offset: u128,
+            limit: u128,
+        ) -> (Vec<String>, Option<u128>) {
+            let (names, cursor) = self.paginate_collection(
+                controller,
+                (NameCollection::Controlled, offset),
+                limit,
+                Some,
+            );
+            (names, cursor.map(|(_, slot)| slot))
         }
 
-        fn is_lock_requested(&self, data: &Vec<u8>) -> bool {
-            // Generic permissionless system
-            let msg = "azero.id-lock".as_bytes().to_vec();
-            data == &msg
+        /// Paginated version of `get_resolving_names_of_address()`
+        /// @returns (names, cursor) where cursor is the slot of the next page (if any)
+        #[ink(message)]
+        pub fn get_resolving_names_of_address_paginated(
+            &self,
+            address: AccountId,
+            offset: u128,
+            limit: u128,
+        ) -> (Vec<String>, Option<u128>) {
+            let (names, cursor) =
+                self.paginate_collection(address, (NameCollection::Resolving, offset), limit, Some);
+            (names, cursor.map(|(_, slot)| slot))
         }
 
-        fn lock_name(&mut self, to: AccountId, name: &str) -> core::result::Result<(), PSP34Error> {
-            self.ensure_unlocked(name)?; // Should carry-forward be allowed?
+        /// Paginated version of `get_names_of_address()`
+        /// Walks the owned, controlled & then resolving names, each name being returned once
+        /// @returns (names, cursor) where cursor is the position of the next page (if any)
+        #[ink(message)]
+        pub fn get_names_of_address_paginated(
+            &self,
+            address: AccountId,
+            cursor: NamesCursor,
+            limit: u128,
+        ) -> (Vec<String>, Option<NamesCursor>) {
+            let mut names = Vec::new();
+            let mut cursor = Some(cursor);
+
+            while let Some(position) = cursor {
+                let remaining = limit - names.len() as u128;
+                if remaining == 0 {
+                    break;
+                }
 
-            let caller = self.env().caller();
-            let id = Some(name.to_string().into());
-            let owner = self
-                .get_owner(name.to_string())
-                .map_err(|_| PSP34Error::TokenNotExists)?;
+                let (page, next) = self.paginate_collection(address, position, remaining, |name| {
+                    let AddressDict {
+                        owner, controller, ..
+                    } = self.get_address_dict_ref(&name).ok()?;
 
-            if !self.allowance(owner, caller, id.clone()) {
-                return Err(PSP34Error::NotApproved);
+                    // Skip the names already listed under a previous collection
+                    let is_listed = match position.0 {
+                        NameCollection::Owned => false,
+                        NameCollection::Controlled => owner == address,
+                        NameCollection::Resolving => owner == address || controller == address,
+                    };
+                    (!is_listed).then_some(name)
+                });
+                names.extend(page);
+
+                cursor = next.or(match position.0 {
+                    NameCollection::Owned => Some((NameCollection::Controlled, 0)),
+                    NameCollection::Controlled => Some((NameCollection::Resolving, 0)),
+                    NameCollection::Resolving => None,
+                });
             }
 
-            self.operator_approvals.remove(&(owner, caller, id));
-            self.name_to_lock.insert(name, &to);
+            (names, cursor)
+        }
 
-            self.env().emit_event(Lock {
-                name: name.to_string(),
-                caller,
-                unlocker: to,
+        /// Returns the owned names along with their registration period, sorted by expiry
+        /// @returns (names, cursor) where cursor is the slot of the next page (if any)
+        /// @note names are sorted within the page, a `limit` of at least `get_owner_to_name_count()`
+        /// returns all of them in a single page
+        #[ink(message)]
+        pub fn get_owned_names_with_period(
+            &self,
+            owner: AccountId,
+            offset: u128,
+            limit: u128,
+        ) -> (Vec<NameWithPeriod>, Option<u128>) {
+            let (mut names, cursor) =
+                self.paginate_collection(owner, (NameCollection::Owned, offset), limit, |name| {
+                    let period = self.get_registration_period_ref(&name).ok()?;
+                    Some((name, period))
+                });
+
+            // Sort by expiry, then by name for determinism
+            names.sort_by(|(name_a, (_, expiry_a)), (name_b, (_, expiry_b))| {
+                expiry_a.cmp(expiry_b).then_with(|| name_a.cmp(name_b))
             });
 
-            Ok(())
+            (names, cursor.map(|(_, slot)| slot))
         }
 
-        fn do_renew(
-            &mut self,
-            name: String,
-            years_to_renew: u8,
-            bonus_name: Option<String>,
-        ) -> Result<Balance> {
-            if self.has_name_expired(&name) != Ok(false) {
-                return Err(Error::NameDoesntExist);
-            }
+        /// Number of slots taken by the owned names (incl. the gaps left by removed names)
+        #[ink(message)]
+        pub fn get_owner_to_name_slots(&self, user: AccountId) -> u128 {
+            // @dev Collections written prior to the introduction of gaps are densely packed
+            self.owner_to_name_slots
+                .get(user)
+                .unwrap_or_else(|| self.get_owner_to_name_count(user))
+        }
 
-            let (registration, old_expiry) = self.get_registration_period_ref(&name)?;
+        /// Number of slots taken by the controlled names (incl. the gaps left by removed names)
+        #[ink(message)]
+        pub fn get_controller_to_name_slots(&self, user: AccountId) -> u128 {
+            self.controller_to_name_slots
+                .get(user)
+                .unwrap_or_else(|| self.get_controller_to_name_count(user))
+        }
 
-            let (base_price, premium) = match &self.fee_calculator {
-                None => (1000, 0), // For unit testing only
-                Some(model) => model
-                    .get_name_price(name.clone(), years_to_renew)
-                    .map_err(Error::FeeError)?,
-            };
-            let price = base_price + premium;
+        /// Number of slots taken by the resolving names (incl. the gaps left by removed names)
+        #[ink(message)]
+        pub fn get_resolving_to_name_slots(&self, user: AccountId) -> u128 {
+            self.resolving_to_name_slots
+                .get(user)
+                .unwrap_or_else(|| self.get_resolving_to_name_count(user))
+        }
 
-            let new_expiry = old_expiry + YEAR * years_to_renew as u64;
-            self.name_to_period
-                .insert(&name, &(registration, new_expiry));
+        // @note count includes expired names as well
+        #[ink(message)]
+        pub fn get_owner_to_name_count(&self, user: AccountId) -> u128 {
+            self.owner_to_name_count.get(user).unwrap_or(0)
+        }
 
-            // Emit event
-            self.env().emit_event(Renew {
-                name: name.clone(),
-                old_expiry,
-                new_expiry,
-            });
+        // @note count includes expired names as well
+        #[ink(message)]
+        pub fn get_controller_to_name_count(&self, user: AccountId) -> u128 {
+            self.controller_to_name_count.get(user).unwrap_or(0)
+        }
 
-            let owner = self.get_owner(name)?;
-            self.redeem_bonus_name(bonus_name, years_to_renew, owner)?;
+        // @note count includes expired names as well
+        #[ink(message)]
+        pub fn get_resolving_to_name_count(&self, user: AccountId) -> u128 {
+            self.resolving_to_name_count.get(user).unwrap_or(0)
+        }
 
-            Ok(price)
+        // @note count includes expired subnames as well
+        #[ink(message)]
+        pub fn get_parent_to_subname_count(&self, parent: String) -> u128 {
+            self.parent_to_subname_count.get(parent).unwrap_or(0)
         }
 
-        fn redeem_bonus_name(
-            &mut self,
-            name: Option<String>,
-            period_extended: u8,
+        /// Returns the commitment hash to be passed to `commit()`
+        #[ink(message)]
+        pub fn make_commitment(
+            &self,
+            name: String,
             owner: AccountId,
-        ) -> Result<()> {
-            if period_extended < 10 || name.is_none() {
-                return Ok(());
-            }
-
-            let name = name.unwrap();
-            if !self.is_name_allowed(&name) || name.len() < 5 {
-                return Err(Error::NameNotAllowed);
-            }
-            // The name must not be a reserved name
-            if self.reserved_names.contains(&name) {
-                return Err(Error::CannotBuyReservedName);
-            }
+            secret: [u8; 32],
+        ) -> [u8; 32] {
+            self.env()
+                .hash_encoded::<Keccak256, _>(&(name, owner, secret))
+        }
 
-            let expiry_time = self.env().block_timestamp() + YEAR;
-            self.register_name(&name, &owner, expiry_time)
+        #[ink(message)]
+        pub fn get_meta_tx_hash(&self, action: MetaAction, nonce: u128, deadline: u64) -> [u8; 32] {
+            self.env().hash_encoded::<Keccak256, _>(&(
+                self.env().account_id(),
+                action,
+                nonce,
+                deadline,
+            ))
         }
 
-        fn handle_payment(&mut self, price: Balance) -> Result<()> {
-            let transferred = self.env().transferred_value();
-            if transferred < price {
-                return Err(Error::FeeNotPaid);
-            } else if transferred > price {
-                let caller = self.env().caller();
-                let change = transferred - price;
+        #[ink(message)]
+        pub fn get_meta_tx_nonce(&self, account: AccountId) -> u128 {
+            self.meta_tx_nonces.get(account).unwrap_or_default()
+        }
 
-                if self.env().transfer(caller, change).is_err() {
-                    return Err(Error::WithdrawFailed);
-                }
-            }
-            Ok(())
+        #[ink(message)]
+        pub fn get_permit_hash(
+            &self,
+            owner: AccountId,
+            operator: AccountId,
+            id: Option<Id>,
+            nonce: u128,
+            deadline: u64,
+        ) -> [u8; 32] {
+            self.env().hash_encoded::<Keccak256, _>(&(
+                self.env().account_id(),
+                "permit",
+                owner,
+                operator,
+                id,
+                nonce,
+                deadline,
+            ))
         }
-    }
 
-    impl PSP34 for Registry {
-        // TLD is our collection id
         #[ink(message)]
-        fn collection_id(&self) -> Id {
-            let id = ".".to_string() + &self.tld.to_ascii_uppercase() + " Domains";
-            id.into()
+        pub fn get_permit_nonce(&self, owner: AccountId) -> u128 {
+            self.permit_nonces.get(owner).unwrap_or_default()
         }
 
         #[ink(message)]
-        fn balance_of(&self, owner: AccountId) -> u32 {
-            self.get_owned_names_of_address(owner).len() as u32
+        pub fn get_launch_schedule(&self) -> Vec<PhaseConfig> {
+            self.launch_schedule.get_or_default()
         }
 
+        /// Returns the current and the next phase of the launch schedule
         #[ink(message)]
-        fn owner_of(&self, id: Id) -> Option<AccountId> {
-            id.try_into().map_or(None, |name| self.get_owner(name).ok())
+        pub fn get_launch_phase(&self) -> (Option<PhaseConfig>, Option<PhaseConfig>) {
+            let now = self.env().block_timestamp();
+            let schedule = self.get_launch_schedule();
+            let next = schedule.iter().position(|p| p.start > now);
+
+            let current = match next {
+                Some(0) => None,
+                Some(i) => schedule.get(i - 1).cloned(),
+                None => schedule.last().cloned(),
+            };
+            (current, next.map(|i| schedule[i].clone()))
         }
 
+        /// Whether only whitelisted accounts can register names, i.e. during a whitelist-phase
+        /// or, if a launch schedule is set, during its `Allowlist` phase
         #[ink(message)]
-        fn allowance(&self, owner: AccountId, operator: AccountId, id: Option<Id>) -> bool {
-            if self.operator_approvals.contains(&(owner, operator, None)) {
-                return true;
+        pub fn is_whitelist_phase(&self) -> bool {
+            // @dev The schedule (if any) drives the switch, regardless of the verifier
+            if self.launch_schedule.get_or_default().is_empty() {
+                return self.whitelisted_address_verifier.get().flatten().is_some();
             }
+            self.get_current_phase_ref()
+                .is_some_and(|p| p.phase == LaunchPhase::Allowlist)
+        }
 
-            if let Some(id_val) = id.clone() {
-                let Ok(name) = TryInto::<String>::try_into(id_val) else {
-                    return false;
-                };
-
-                let Ok((registration_time, expiration_time)) =
-                    self.get_registration_period_ref(&name)
-                else {
-                    return false;
-                };
-
-                let Some(approval_time) = self.operator_approvals.get(&(owner, operator, id))
-                else {
-                    return false;
-                };
+        /// Returns the number of names registered by the account during the current whitelist-phase
+        #[ink(message)]
+        pub fn get_whitelist_usage(&self, account: AccountId) -> u32 {
+            let round = self.whitelist_round.get_or_default();
+            self.whitelist_usage
+                .get((round, account))
+                .unwrap_or_default()
+        }
 
-                // @dev This check ensures that approval do not carry forward post domain-expiration
-                let current_time = self.env().block_timestamp();
-                return registration_time <= approval_time && current_time <= expiration_time;
+        /// Verifies the account's quota is part of the whitelist
+        #[ink(message)]
+        pub fn verify_whitelist_proof(
+            &self,
+            account: AccountId,
+            quota: WhitelistQuota,
+            merkle_proof: Vec<[u8; 32]>,
+        ) -> bool {
+            match self.whitelisted_address_verifier.get().flatten() {
+                Some(verifier) => verifier.verify_proof(quota.leaf(account), merkle_proof),
+                None => false,
             }
+        }
 
-            false
+        #[ink(message)]
+        pub fn get_voucher_signer(&self) -> Option<AccountId> {
+            self.voucher_signer.get().flatten()
         }
 
         #[ink(message)]
-        fn approve(
-            &mut self,
-            operator: AccountId,
-            id: Option<Id>,
-            approved: bool,
-        ) -> core::result::Result<(), PSP34Error> {
-            let mut caller = self.env().caller();
+        pub fn is_voucher_used(&self, nonce: u128) -> bool {
+            self.used_voucher_nonces.contains(nonce)
+        }
 
-            if operator == [0u8; 32].into() {
-                return Err(PSP34Error::Custom("Zero address".to_string()));
-            }
+        /// Returns the hash the voucher signer must sign
+        #[ink(message)]
+        pub fn get_voucher_hash(&self, voucher: Voucher) -> [u8; 32] {
+            self.env()
+                .hash_encoded::<Keccak256, _>(&(self.env().account_id(), voucher))
+        }
 
-            if let Some(id) = &id {
-                let owner = self
-                    .owner_of(id.clone())
-                    .ok_or(PSP34Error::TokenNotExists)?;
+        /// Returns the time at which the commitment was made (if any)
+        #[ink(message)]
+        pub fn get_commitment(&self, commitment: [u8; 32]) -> Option<u64> {
+            self.commitments.get(commitment)
+        }
 
-                if approved && owner == operator {
-                    return Err(PSP34Error::SelfApprove);
-                }
+        #[ink(message)]
+        pub fn get_commitment_age(&self) -> (u64, u64) {
+            self.commitment_age.get().unwrap_or(DEFAULT_COMMITMENT_AGE)
+        }
 
-                if owner != caller && !self.allowance(owner, caller, None) {
-                    return Err(PSP34Error::NotApproved);
-                };
-                caller = owner;
-            }
+        /// Returns whether registrations must go through the commit-reveal flow (false by default)
+        #[ink(message)]
+        pub fn get_commitment_required(&self) -> bool {
+            self.commitment_required.get().unwrap_or(false)
+        }
 
-            match approved {
-                true => {
-                    let timestamp = self.env().block_timestamp();
-                    self.operator_approvals
-                        .insert((&caller, &operator, &id), &timestamp);
-                }
-                false => self.operator_approvals.remove((&caller, &operator, &id)),
+        #[ink(message)]
+        pub fn get_redemption_period(&self) -> u64 {
+            self.redemption_period.get().unwrap_or(0)
+        }
+
+        /// Returns the active listing of a name (if any)
+        #[ink(message)]
+        pub fn get_listing(&self, name: String) -> Option<Listing> {
+            let listing = self.listings.get(&name)?;
+            let owner = self.get_address_dict_ref(&name).ok()?.owner;
+
+            // @dev Listings don't outlive their expiry, nor a change of ownership
+            match listing.seller == owner && listing.is_active(self.env().block_timestamp()) {
+                true => Some(listing),
+                false => None,
             }
+        }
 
-            // Emit event
-            self.env().emit_event(Approval {
-                owner: caller,
-                operator,
-                id,
-                approved,
-            });
+        #[ink(message)]
+        pub fn get_marketplace_fee(&self) -> u16 {
+            self.marketplace_fee.get().unwrap_or(0)
+        }
 
-            Ok(())
+        #[ink(message)]
+        pub fn get_offer(&self, name: String, bidder: AccountId) -> Option<Offer> {
+            self.offers.get((name, bidder))
         }
 
+        /// Returns the active offers on a name
         #[ink(message)]
-        fn transfer(
-            &mut self,
-            to: AccountId,
-            id: Id,
-            data: Vec<u8>,
-        ) -> core::result::Result<(), PSP34Error> {
-            let caller = self.env().caller();
-            let name: String = id.clone().try_into()?;
-            let owner = self
-                .get_owner(name.clone())
-                .map_err(|_| PSP34Error::TokenNotExists)?;
+        pub fn get_offers(&self, name: String) -> Vec<(AccountId, Offer)> {
+            let Ok(AddressDict { owner, .. }) = self.get_address_dict_ref(&name) else {
+                return Vec::new();
+            };
+            let Ok((registered_at, _)) = self.get_registration_period_ref(&name) else {
+                return Vec::new();
+            };
+            let current_time = self.env().block_timestamp();
 
-            if self.is_lock_requested(&data) {
-                return self.lock_name(to, &name);
-            } else if self.name_to_lock.get(&name) == Some(caller) {
-                self.name_to_lock.remove(&name);
+            (0..self.get_name_to_bidder_count(name.clone()))
+                .map(|idx| {
+                    let bidder = self.name_to_bidders.get((&name, idx)).expect("Infallible");
+                    let offer = self.offers.get((&name, bidder)).expect("Infallible");
+                    (bidder, offer)
+                })
+                .filter(|(_, offer)| offer.is_active(&owner, registered_at, current_time))
+                .collect()
+        }
 
-                self.env().emit_event(Unlock { name: name.clone() });
+        /// Returns all the offers made by an account
+        /// @note includes the expired ones as well, which can be withdrawn
+        #[ink(message)]
+        pub fn get_offers_of_address(&self, bidder: AccountId) -> Vec<(String, Offer)> {
+            (0..self.get_bidder_to_name_count(bidder))
+                .map(|idx| {
+                    let name = self.bidder_to_names.get((bidder, idx)).expect("Infallible");
+                    let offer = self.offers.get((&name, bidder)).expect("Infallible");
+                    (name, offer)
+                })
+                .collect()
+        }
 
-                if to == owner {
-                    return Ok(());
-                }
+        // @note count includes inactive offers as well
+        #[ink(message)]
+        pub fn get_name_to_bidder_count(&self, name: String) -> u128 {
+            self.name_to_bidder_count.get(name).unwrap_or(0)
+        }
 
-                let time = self.env().block_timestamp();
-                self.operator_approvals
-                    .insert(&(owner, caller, Some(id)), &time);
-            }
-            self.transfer_name(to, &name, false, false, false, &data)
+        #[ink(message)]
+        pub fn get_bidder_to_name_count(&self, bidder: AccountId) -> u128 {
+            self.bidder_to_name_count.get(bidder).unwrap_or(0)
         }
 
+        /// Returns the ongoing auction of a name (if any)
         #[ink(message)]
-        fn total_supply(&self) -> Balance {
-            self.total_supply
+        pub fn get_auction(&self, name: String) -> Option<Auction> {
+            self.auctions.get(name)
         }
-    }
 
-    impl PSP34Enumerable for Registry {
+        /// Returns the pending or ongoing lease of a name (if any)
         #[ink(message)]
-        fn owners_token_by_index(
-            &self,
-            owner: AccountId,
-            index: u128,
-        ) -> core::result::Result<Id, PSP34Error> {
-            let tokens = self.get_owned_names_of_address(owner);
+        pub fn get_lease(&self, name: String) -> Option<Lease> {
+            self.leases
+                .get(name)
+                .filter(|lease| !lease.has_ended(self.env().block_timestamp()))
+        }
 
-            match tokens.get(index as usize) {
-                Some(name) => Ok(name.clone().into()),
-                None => Err(PSP34Error::TokenNotExists),
-            }
+        /// Returns the (numerator, denominator) rate of an accepted PSP22 token
+        #[ink(message)]
+        pub fn get_token_rate(&self, token: AccountId) -> Option<(u128, u128)> {
+            self.token_rates.get(token)
         }
 
+        /// Converts a price in the native token to the given accepted PSP22 token
         #[ink(message)]
-        fn token_by_index(&self, _index: u128) -> core::result::Result<Id, PSP34Error> {
-            Err(PSP34Error::Custom("Not Supported".to_string()))
+        pub fn get_price_in_token(&self, token: AccountId, price: Balance) -> Result<Balance> {
+            self.get_price_in(Some(token), price)
         }
-    }
 
-    impl PSP34Metadata for Registry {
+        /// Returns the referral rate (in basis points) applicable to the given referrer name
+        /// @note `None` denotes the legacy 5.005% rate
         #[ink(message)]
-        fn get_attribute(&self, id: Id, key: Vec<u8>) -> Option<Vec<u8>> {
-            match TryInto::<String>::try_into(id) {
-                Ok(name) => {
-                    let Ok(key) = String::from_utf8(key) else {
-                        return None;
-                    };
+        pub fn get_referral_rate(&self, referrer_name: String) -> Option<u16> {
+            let (referrals, _) = self.get_referrer_stats(referrer_name);
 
-                    self.get_static_attribute_ref(&name, &key)
-                        .map(|s| s.into_bytes())
-                }
-                Err(_) => None,
-            }
+            self.get_referral_tiers()
+                .into_iter()
+                .rev()
+                .find(|(min_referrals, _)| referrals >= *min_referrals)
+                .map(|(_, rate)| rate)
+                .or(self.referral_rate.get().flatten())
         }
-    }
 
-    impl Psp34Traits for Registry {
         #[ink(message)]
-        fn get_owner(&self) -> AccountId {
-            self.admin
+        pub fn get_referral_tiers(&self) -> Vec<ReferralTier> {
+            self.referral_tiers.get().unwrap_or_default()
         }
 
+        /// Returns the (total referrals, total earnings in the native token) of a referrer name
         #[ink(message)]
-        fn token_uri(&self, token_id: Id) -> String {
-            let name: core::result::Result<String, _> = token_id.try_into();
-
-            match name {
-                Ok(name) => self.base_uri.clone() + &name + "." + &self.tld + ".json",
-                _ => String::new(),
-            }
+        pub fn get_referrer_stats(&self, referrer_name: String) -> (u128, Balance) {
+            self.referrer_stats.get(referrer_name).unwrap_or((0, 0))
         }
 
+        /// Returns the claimable referral rewards of an account in the given payment token
         #[ink(message)]
-        fn set_base_uri(&mut self, uri: String) -> core::result::Result<(), ArtZeroError> {
-            self.ensure_admin()
-                .map_err(|_| ArtZeroError::Custom("Not Authorised".to_string()))?;
-
-            if uri.len() == 0 {
-                return Err(ArtZeroError::Custom("Zero length string".to_string()));
-            }
-            self.base_uri = uri;
-            Ok(())
+        pub fn get_referral_rewards(
+            &self,
+            account: AccountId,
+            token: Option<AccountId>,
+        ) -> Balance {
+            self.referral_rewards.get((account, token)).unwrap_or(0)
         }
 
+        /// Returns the PSP22 tokens held on behalf of users
         #[ink(message)]
-        fn get_attribute_count(&self) -> u32 {
-            4
+        pub fn get_token_escrowed_balance(&self, token: AccountId) -> Balance {
+            self.token_escrowed_balance.get(token).unwrap_or(0)
         }
 
+        /// Returns the funds held in escrow on behalf of users
         #[ink(message)]
-        fn get_attribute_name(&self, index: u32) -> String {
-            let attr = match index {
-                0 => "TLD",
-                1 => "Length",
-                2 => "Registration",
-                3 => "Expiration",
-                _ => "",
-            };
-            attr.into()
+        pub fn get_escrowed_balance(&self) -> Balance {
+            self.escrowed_balance.get().unwrap_or(0)
         }
 
         #[ink(message)]
-        fn get_attributes(&self, token_id: Id, attributes: Vec<String>) -> Vec<String> {
-            let name: String = match token_id
-                .try_into()
-                .map_err(|_| ArtZeroError::Custom("TokenNotFound".to_string()))
-            {
-                Ok(name) => name,
-                _ => return Default::default(),
+        pub fn get_revenue_shares(&self) -> Vec<RevenueShare> {
+            self.revenue_shares.get().unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_claimable_revenue(&self, beneficiary: AccountId) -> Balance {
+            self.claimable_revenue.get(beneficiary).unwrap_or(0)
+        }
+
+        /// Returns the funds withdrawn by the beneficiary so far
+        #[ink(message)]
+        pub fn get_withdrawn_revenue(&self, beneficiary: AccountId) -> Balance {
+            self.withdrawn_revenue.get(beneficiary).unwrap_or(0)
+        }
+
+        /// Returns the protocol revenue (net of referral payouts) received since deployment
+        #[ink(message)]
+        pub fn get_lifetime_revenue(&self) -> Balance {
+            self.lifetime_revenue.get().unwrap_or(0)
+        }
+
+        /// Returns the referral rewards (in the native token) credited since deployment
+        #[ink(message)]
+        pub fn get_referral_payouts(&self) -> Balance {
+            self.referral_payouts.get().unwrap_or(0)
+        }
+
+        /// Returns the funds withdrawn by all the beneficiaries so far
+        #[ink(message)]
+        pub fn get_total_withdrawn_revenue(&self) -> Balance {
+            self.total_withdrawn_revenue.get().unwrap_or(0)
+        }
+
+        #[ink(message)]
+        pub fn get_refund_policy(&self) -> Option<RefundPolicy> {
+            self.refund_policy.get().flatten()
+        }
+
+        /// Returns the refund the owner would get upon releasing the name now
+        // @dev Only the paid registration time counts, i.e. the grace period & subnames are not refunded.
+        // The refund is prorated over the fees actually paid for the name (net of any discount).
+        #[ink(message)]
+        pub fn get_release_refund(&self, name: String) -> Balance {
+            let Some(policy) = self.get_refund_policy() else {
+                return 0;
+            };
+            if Self::get_parent_name(&name).is_some() {
+                return 0;
+            }
+            let Some((registration, expiry)) = self.name_to_period.get(&name) else {
+                return 0;
             };
 
-            attributes
-                .into_iter()
-                .map(|key| {
-                    self.get_static_attribute_ref(&name, &key)
-                        .unwrap_or_default()
-                })
-                .collect()
+            let now = self.env().block_timestamp();
+            if !policy.is_eligible(registration, now) {
+                return 0;
+            }
+            let Some((paid, paid_time)) = self.name_to_payment.get(&name) else {
+                return 0;
+            };
+            if paid_time == 0 {
+                return 0;
+            }
+
+            let unused_time = expiry.saturating_sub(now).min(paid_time) as Balance;
+            paid * unused_time * policy.share as Balance
+                / (paid_time as Balance * MAX_BPS as Balance)
+        }
+
+        #[ink(message)]
+        pub fn get_registration_credit(&self, account: AccountId) -> Balance {
+            self.registration_credits.get(account).unwrap_or(0)
+        }
+
+        #[ink(message)]
+        pub fn get_records_size_limit(&self) -> Option<u32> {
+            self.records_size_limit
+        }
+
+        #[ink(message)]
+        pub fn get_tld(&self) -> String {
+            self.tld.clone()
+        }
+
+        #[ink(message)]
+        pub fn get_base_uri(&self) -> String {
+            self.base_uri.clone()
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Transfers `value` amount of tokens to the caller.
+        #[ink(message)]
+        pub fn withdraw(
+            &mut self,
+            beneficiary: Option<AccountId>,
+            value: Option<Balance>,
+        ) -> Result<()> {
+            self.ensure_role(Role::Treasurer)?;
+
+            // @dev Escrowed funds belong to the users & claimable revenue to the beneficiaries
+            let balance = self
+                .env()
+                .balance()
+                .saturating_sub(self.get_escrowed_balance())
+                .saturating_sub(self.total_claimable_revenue.get().unwrap_or(0));
+            let beneficiary = beneficiary.unwrap_or(self.env().caller());
+            let value = value.unwrap_or(balance);
+
+            if beneficiary == [0u8; 32].into() {
+                return Err(Error::ZeroAddress);
+            }
+
+            if value > balance {
+                return Err(Error::InsufficientBalance);
+            }
+            if self.env().transfer(beneficiary, value).is_err() {
+                return Err(Error::WithdrawFailed);
+            }
+            self.record_withdrawal(beneficiary, value);
+
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Transfers `value` amount of the given PSP22 token to the beneficiary (defaults to the caller).
+        #[ink(message)]
+        pub fn withdraw_token(
+            &mut self,
+            token: AccountId,
+            beneficiary: Option<AccountId>,
+            value: Option<Balance>,
+        ) -> Result<()> {
+            self.ensure_role(Role::Treasurer)?;
+
+            // @dev Unclaimed referral rewards belong to the users
+            let balance = self
+                .psp22_balance_of(token, self.env().account_id())
+                .saturating_sub(self.get_token_escrowed_balance(token));
+            let beneficiary = beneficiary.unwrap_or(self.env().caller());
+            let value = value.unwrap_or(balance);
+
+            if beneficiary == [0u8; 32].into() {
+                return Err(Error::ZeroAddress);
+            }
+
+            if value > balance {
+                return Err(Error::InsufficientBalance);
+            }
+            self.psp22_transfer(token, beneficiary, value)
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Update the referral rate (in basis points) & the optional (min_referrals, rate) tiers.
+        /// Referrers get the rate of the highest tier they qualify for. A `None` rate restores the legacy 5.005%.
+        #[ink(message)]
+        pub fn set_referral_rate(
+            &mut self,
+            rate: Option<u16>,
+            tiers: Vec<ReferralTier>,
+        ) -> Result<()> {
+            self.ensure_role(Role::PricingManager)?;
+
+            let is_sorted = tiers.windows(2).all(|pair| pair[0].0 < pair[1].0);
+            let is_capped = rate
+                .iter()
+                .chain(tiers.iter().map(|(_, rate)| rate))
+                .all(|rate| *rate <= MAX_BPS);
+            if !is_sorted || !is_capped {
+                return Err(Error::InvalidReferralConfig);
+            }
+
+            self.referral_rate.set(&rate);
+            self.referral_tiers.set(&tiers);
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Accept a PSP22 token for payments at the given (numerator, denominator) rate,
+        /// i.e. `token_amount = native_amount * numerator / denominator`. Pass `None` to stop accepting it.
+        #[ink(message)]
+        pub fn set_token_rate(
+            &mut self,
+            token: AccountId,
+            rate: Option<(u128, u128)>,
+        ) -> Result<()> {
+            self.ensure_role(Role::PricingManager)?;
+
+            match rate {
+                Some((0, _)) | Some((_, 0)) => return Err(Error::InvalidTokenRate),
+                Some(rate) => {
+                    self.token_rates.insert(token, &rate);
+                }
+                None => self.token_rates.remove(token),
+            }
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Update the beneficiaries of the protocol revenue & their shares (in basis points).
+        /// The unallocated share stays with the treasury. Already accrued revenue is unaffected.
+        #[ink(message)]
+        pub fn set_revenue_shares(&mut self, shares: Vec<RevenueShare>) -> Result<()> {
+            self.ensure_role(Role::Treasurer)?;
+
+            let total: u32 = shares.iter().map(|(_, share)| *share as u32).sum();
+            if total > MAX_BPS as u32 {
+                return Err(Error::InvalidRevenueShares);
+            }
+            for (idx, (beneficiary, _)) in shares.iter().enumerate() {
+                if beneficiary == &[0u8; 32].into()
+                    || shares[..idx].iter().any(|(other, _)| other == beneficiary)
+                {
+                    return Err(Error::InvalidRevenueShares);
+                }
+            }
+
+            self.revenue_shares.set(&shares);
+            Ok(())
         }
 
-        #[ink(message)]
-        fn set_multiple_attributes(
-            &mut self,
-            _token_id: Id,
-            _metadata: Vec<(String, String)>,
-        ) -> core::result::Result<(), ArtZeroError> {
-            Err(ArtZeroError::Custom("Not Supported".to_string()))
-        }
-    }
-}
+        /// (ADMIN-OPERATION)
+        /// Set the launch schedule, i.e. the phases (sorted by start) with their pricing & allowed entry points.
+        /// Pass an empty schedule to lift all the restrictions.
+        #[ink(message)]
+        pub fn set_launch_schedule(&mut self, schedule: Vec<PhaseConfig>) -> Result<()> {
+            self.ensure_role(Role::ConfigManager)?;
+            if schedule.windows(2).any(|w| w[0].start >= w[1].start) {
+                return Err(Error::InvalidLaunchSchedule);
+            }
+
+            self.launch_schedule.set(&schedule);
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Start a whitelist-phase where only the accounts (& quotas) of the verifier's merkle tree can register names
+        #[ink(message)]
+        pub fn start_whitelist_phase(&mut self, verifier_addr: AccountId) -> Result<()> {
+            self.ensure_role(Role::ReservationManager)?;
+            if self.whitelisted_address_verifier.get().flatten().is_some() {
+                return Err(Error::RestrictedDuringWhitelistPhase);
+            }
+
+            let verifier = MerkleVerifierRef::from_account_id(verifier_addr);
+            self.whitelisted_address_verifier.set(&Some(verifier));
+            let round = self.whitelist_round.get_or_default();
+            self.whitelist_round.set(&(round + 1));
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Switch from whitelist-phase to public-phase
+        #[ink(message)]
+        pub fn switch_to_public_phase(&mut self) -> Result<()> {
+            self.ensure_role(Role::ReservationManager)?;
+            if self.whitelisted_address_verifier.get().flatten().is_none() {
+                return Err(Error::OnlyDuringWhitelistPhase);
+            }
+
+            self.whitelisted_address_verifier.set(&None);
+            Self::emit_event(PublicPhaseActivated {});
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Update the account (blake2 hash of the compressed ECDSA public key) signing the discount vouchers.
+        /// Pass `None` to disable vouchers.
+        /// @note must be an ECDSA account, an sr25519 account cannot sign vouchers
+        #[ink(message)]
+        pub fn set_voucher_signer(&mut self, signer: Option<AccountId>) -> Result<()> {
+            self.ensure_role(Role::PricingManager)?;
+            self.voucher_signer.set(&signer);
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Update the refund policy for released names. Pass `None` to stop refunding releases.
+        #[ink(message)]
+        pub fn set_refund_policy(&mut self, policy: Option<RefundPolicy>) -> Result<()> {
+            self.ensure_role(Role::PricingManager)?;
+
+            if policy.as_ref().is_some_and(|p| p.share > MAX_BPS) {
+                return Err(Error::InvalidRefundPolicy);
+            }
+            self.refund_policy.set(&policy);
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Adds the given names, registered prior to the global index, to it.
+        /// To be called in batches until `get_all_names_count()` matches `total_supply()`.
+        #[ink(message)]
+        pub fn backfill_global_index(&mut self, names: Vec<String>) -> Result<u128> {
+            self.ensure_admin()?;
+
+            let mut count = 0;
+            names.into_iter().for_each(|name| {
+                if self.name_to_address_dict.contains(&name)
+                    && !self.name_to_global_index.contains(&name)
+                {
+                    self.add_name_to_global_index(&name);
+                    count += 1;
+                }
+            });
+            Ok(count)
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Reserve name name for specific addresses
+        // @dev (name, None) denotes that the name is reserved but not tied to any address yet
+        #[ink(message)]
+        pub fn add_reserved_names(
+            &mut self,
+            set: Vec<(String, Option<AccountId>)>,
+            skip_name_checker: bool,
+        ) -> Result<()> {
+            self.ensure_role(Role::ReservationManager)?;
+
+            for (name, addr) in set.iter() {
+                if name.is_empty() {
+                    return Err(Error::NameEmpty);
+                }
+                if self.has_name_expired(name) == Ok(false) {
+                    return Err(Error::NameAlreadyExists);
+                }
+
+                if !skip_name_checker && !self.is_name_allowed(name) {
+                    return Err(Error::NameNotAllowed);
+                }
+
+                self.reserved_names.insert(name, addr);
+                Self::emit_event(Reserve {
+                    name: name.clone(),
+                    account_id: *addr,
+                    action: true,
+                });
+            }
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Remove given names from the list of reserved names
+        #[ink(message)]
+        pub fn remove_reserved_name(&mut self, set: Vec<String>) -> Result<()> {
+            self.ensure_role(Role::ReservationManager)?;
+
+            set.iter().for_each(|name| {
+                if self.reserved_names.contains(name) {
+                    self.reserved_names.remove(name);
+                    Self::emit_event(Reserve {
+                        name: name.clone(),
+                        account_id: None,
+                        action: false,
+                    });
+                }
+            });
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Start an English auction for a name reserved without an account, or an expired name at a premium
+        /// @note `extension` is the anti-sniping window, bids placed within it extend the auction
+        #[ink(message)]
+        pub fn start_auction(
+            &mut self,
+            name: String,
+            start_price: Balance,
+            min_increment: Balance,
+            duration: u64,
+            extension: u64,
+            years_to_register: u8,
+        ) -> Result<()> {
+            self.ensure_role(Role::ReservationManager)?;
+
+            if self.auctions.contains(&name) {
+                return Err(Error::AuctionAlreadyExists);
+            }
+            if duration == 0 || min_increment == 0 || years_to_register == 0 {
+                return Err(Error::InvalidAuctionConfig);
+            }
+
+            self.ensure_auctionable(&name)?;
+            let is_reserved = self.reserved_names.get(&name) == Some(None);
+            if !is_reserved && self.get_release_premium_ref(&name) == 0 {
+                return Err(Error::NotAuctionable);
+            }
+
+            let end_time = self.env().block_timestamp() + duration;
+            let auction = Auction {
+                start_price,
+                min_increment,
+                end_time,
+                extension,
+                years_to_register,
+                highest_bid: None,
+            };
+            self.auctions.insert(&name, &auction);
+
+            Self::emit_event(AuctionStarted {
+                name,
+                start_price,
+                min_increment,
+                end_time,
+            });
+
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Cancel an auction, refunding its highest bid (if any)
+        #[ink(message)]
+        pub fn cancel_auction(&mut self, name: String) -> Result<()> {
+            self.ensure_role(Role::ReservationManager)?;
+
+            let Some(auction) = self.auctions.get(&name) else {
+                return Err(Error::AuctionNotFound);
+            };
+            self.auctions.remove(&name);
+
+            if let Some((bidder, amount)) = auction.highest_bid {
+                self.refund_bid(bidder, amount)?;
+            }
+
+            Self::emit_event(AuctionSettled {
+                name,
+                winner: None,
+                amount: 0,
+            });
+
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Update the limit of records allowed to store per name
+        #[ink(message)]
+        pub fn set_records_size_limit(&mut self, limit: Option<u32>) -> Result<()> {
+            self.ensure_role(Role::ConfigManager)?;
+            self.records_size_limit = limit;
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Update the (min, max) age of a commitment before it can be revealed
+        #[ink(message)]
+        pub fn set_commitment_age(&mut self, min_age: u64, max_age: u64) -> Result<()> {
+            self.ensure_role(Role::ConfigManager)?;
+
+            if min_age > max_age {
+                return Err(Error::InvalidCommitmentAge);
+            }
+            self.commitment_age.set(&(min_age, max_age));
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Enforce (or lift) the commit-reveal flow for all registrations
+        /// @note not enforced by default, deployments wanting front-running protection must opt in
+        #[ink(message)]
+        pub fn set_commitment_required(&mut self, required: bool) -> Result<()> {
+            self.ensure_role(Role::ConfigManager)?;
+            self.commitment_required.set(&required);
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Update the period after expiry during which only the previous owner can renew a name
+        #[ink(message)]
+        pub fn set_redemption_period(&mut self, period: u64) -> Result<()> {
+            self.ensure_role(Role::ConfigManager)?;
+            self.redemption_period.set(&period);
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Update the protocol fee (in basis points) charged on marketplace sales
+        #[ink(message)]
+        pub fn set_marketplace_fee(&mut self, fee: u16) -> Result<()> {
+            self.ensure_role(Role::PricingManager)?;
+            if fee > MAX_BPS {
+                return Err(Error::InvalidMarketplaceFee);
+            }
+            self.marketplace_fee.set(&fee);
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Grants a role to the given account
+        #[ink(message)]
+        pub fn grant_role(&mut self, role: Role, account: AccountId) -> Result<()> {
+            self.ensure_admin()?;
+
+            if self.roles.grant(role, account) {
+                Self::emit_event(RoleGranted {
+                    role,
+                    account,
+                    sender: self.env().caller(),
+                });
+            }
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Revokes a role from the given account
+        #[ink(message)]
+        pub fn revoke_role(&mut self, role: Role, account: AccountId) -> Result<()> {
+            self.ensure_admin()?;
+            self.remove_role(role, account);
+            Ok(())
+        }
+
+        /// Gives up a role held by the caller
+        #[ink(message)]
+        pub fn renounce_role(&mut self, role: Role) -> Result<()> {
+            self.remove_role(role, self.env().caller());
+            Ok(())
+        }
+
+        /// @note the admin implicitly holds every role
+        #[ink(message)]
+        pub fn has_role(&self, role: Role, account: AccountId) -> bool {
+            self.roles.holds(self.admin, role, account)
+        }
+
+        /// Pauses the given group of operations
+        #[ink(message)]
+        pub fn pause(&mut self, group: PauseGroup) -> Result<()> {
+            self.ensure_role(Role::Pauser)?;
+
+            if !self.paused_groups.contains(group) {
+                self.paused_groups.insert(group, &());
+                Self::emit_event(Paused {
+                    group,
+                    sender: self.env().caller(),
+                });
+            }
+            Ok(())
+        }
+
+        /// Unpauses the given group of operations
+        #[ink(message)]
+        pub fn unpause(&mut self, group: PauseGroup) -> Result<()> {
+            self.ensure_role(Role::Pauser)?;
+
+            if self.paused_groups.contains(group) {
+                self.paused_groups.remove(group);
+                Self::emit_event(Unpaused {
+                    group,
+                    sender: self.env().caller(),
+                });
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_paused(&self, group: PauseGroup) -> bool {
+            self.paused_groups.contains(group)
+        }
+
+        /// Upgrades the contract code
+        #[ink(message)]
+        pub fn upgrade_contract(&mut self, code_hash: [u8; 32]) -> Result<()> {
+            self.ensure_role(Role::Upgrader)?;
+            access_control::upgrade_contract(code_hash);
+            Ok(())
+        }
+
+        fn ensure_role(&self, role: Role) -> Result<()> {
+            match self.has_role(role, self.env().caller()) {
+                true => Ok(()),
+                false => Err(Error::MissingRole(role)),
+            }
+        }
+
+        fn ensure_not_paused(&self, group: PauseGroup) -> Result<()> {
+            match self.paused_groups.contains(group) {
+                true => Err(Error::GroupPaused(group)),
+                false => Ok(()),
+            }
+        }
+
+        fn remove_role(&mut self, role: Role, account: AccountId) {
+            if self.roles.revoke(role, account) {
+                Self::emit_event(RoleRevoked {
+                    role,
+                    account,
+                    sender: self.env().caller(),
+                });
+            }
+        }
+
+        fn ensure_owner(&self, address: &AccountId, name: &str) -> Result<()> {
+            let AddressDict { owner, .. } = self.get_address_dict_ref(name)?;
+            if address != &owner {
+                Err(Error::CallerIsNotOwner)
+            } else {
+                Ok(())
+            }
+        }
+
+        fn ensure_controller_owner(&self, address: &AccountId, name: &str) -> Result<()> {
+            /* Ensure that the address has the right to control the target name */
+            let AddressDict {
+                owner, controller, ..
+            } = self.get_address_dict_ref(name)?;
+
+            // @dev The owner cannot take back the control during an active lease
+            let is_leased = self.get_active_lease_ref(name).is_some();
+            if address != &controller && (address != &owner || is_leased) {
+                Err(Error::CallerIsNotController)
+            } else {
+                Ok(())
+            }
+        }
+
+        fn ensure_subname_manager(&self, address: &AccountId, parent: &str) -> Result<()> {
+            /* A tenant controls a leased name, but must not manage subnames outliving the lease */
+            self.ensure_controller_owner(address, parent)?;
+            self.ensure_not_leased(parent)
+        }
+
+        fn ensure_parent_controller_owner(&self, address: &AccountId, name: &str) -> Result<()> {
+            let parent = Self::get_parent_name(name).ok_or(Error::NotASubname)?;
+            self.ensure_subname_manager(address, parent)
+        }
+
+        /// Ensures the name is either reserved without an account, or neither registered nor in redemption
+        fn ensure_auctionable(&self, name: &str) -> Result<()> {
+            let is_auctionable = match self.reserved_names.get(name) {
+                Some(account) => account.is_none(),
+                None => {
+                    self.has_name_expired(name) != Ok(false)
+                        && self.get_redemption_info(name).is_none()
+                }
+            };
+
+            match is_auctionable {
+                true => Ok(()),
+                false => Err(Error::NotAuctionable),
+            }
+        }
+
+        fn ensure_not_leased(&self, name: &str) -> Result<()> {
+            match self.get_active_lease_ref(name) {
+                Some(_) => Err(Error::NameLeased),
+                None => Ok(()),
+            }
+        }
+
+        fn ensure_unlocked(&self, name: &str) -> core::result::Result<(), PSP34Error> {
+            match self.name_to_lock.contains(name) {
+                true => Err(PSP34Error::Custom("Name is locked".to_string())),
+                false => Ok(()),
+            }
+        }
+
+        fn ensure_records_under_limit(&self, name: &str) -> Result<()> {
+            let size = self.records.size(name).unwrap_or(0);
+            let limit = self.records_size_limit.unwrap_or(u32::MAX);
+
+            match size <= limit {
+                true => Ok(()),
+                false => Err(Error::RecordsOverflow),
+            }
+        }
+
+        fn get_current_phase_ref(&self) -> Option<PhaseConfig> {
+            self.get_launch_phase().0
+        }
+
+        /// Ends the whitelist-phase once the launch schedule has reached the public sale
+        fn end_scheduled_whitelist_phase(&mut self) {
+            if self.whitelisted_address_verifier.get().flatten().is_none() {
+                return;
+            }
+            if self
+                .get_current_phase_ref()
+                .is_some_and(|p| p.phase == LaunchPhase::PublicSale)
+            {
+                self.whitelisted_address_verifier.set(&None);
+                Self::emit_event(PublicPhaseActivated {});
+            }
+        }
+
+        /// Ensures the entry point is open, i.e. no schedule is set or the current phase allows it
+        fn ensure_allowed_in_phase(&self, entry: EntryPoint) -> Result<()> {
+            if self.launch_schedule.get_or_default().is_empty() {
+                return Ok(());
+            }
+            match self.get_current_phase_ref() {
+                Some(phase) if phase.allows(entry) => Ok(()),
+                _ => Err(Error::NotAllowedInCurrentPhase),
+            }
+        }
+
+        /// Returns the account of the ECDSA key that signed the message hash
+        // @dev The account is derived as the blake2 hash of the compressed public key, as for
+        // Substrate ECDSA accounts. sr25519 signatures cannot be verified with ink! 4.
+        fn recover_signer(&self, signature: &[u8; 65], hash: &[u8; 32]) -> Option<AccountId> {
+            let pubkey = self.env().ecdsa_recover(signature, hash).ok()?;
+            let mut account = [0u8; 32];
+            ink::env::hash_bytes::<Blake2x256>(&pubkey, &mut account);
+            Some(AccountId::from(account))
+        }
+
+        fn ensure_valid_voucher(
+            &self,
+            name: &str,
+            voucher: &Voucher,
+            signature: &[u8; 65],
+        ) -> Result<()> {
+            let Some(signer) = self.get_voucher_signer() else {
+                return Err(Error::InvalidVoucher);
+            };
+
+            let hash = self.get_voucher_hash(voucher.clone());
+            if self.recover_signer(signature, &hash) != Some(signer) {
+                return Err(Error::InvalidVoucher);
+            }
+
+            // @dev Only the recipient can redeem the voucher, so that it cannot be front-run
+            if voucher.recipient != self.env().caller() {
+                return Err(Error::InvalidVoucher);
+            }
+            if voucher.discount > MAX_BPS || !voucher.applies_to(name) {
+                return Err(Error::InvalidVoucher);
+            }
+            if voucher.expiry < self.env().block_timestamp() {
+                return Err(Error::VoucherExpired);
+            }
+            if self.is_voucher_used(voucher.nonce) {
+                return Err(Error::VoucherAlreadyUsed);
+            }
+            Ok(())
+        }
+
+        /// Ensures the name can be bought, i.e. it is allowed & not reserved
+        fn ensure_registrable(&self, name: &str) -> Result<()> {
+            if !self.is_name_allowed(name) {
+                return Err(Error::NameNotAllowed);
+            }
+
+            // The name must not be a reserved name
+            if self.reserved_names.contains(name) {
+                return Err(Error::CannotBuyReservedName);
+            }
+            Ok(())
+        }
+
+        /// Emits an event of this contract
+        // @dev Events of contracts we depend upon are in scope as well, which makes
+        // `Self::emit_event()` ambiguous. Hence the contract is named explicitly.
+        fn emit_event<E>(event: E)
+        where
+            E: Into<<Registry as ink::reflect::ContractEventBase>::Type>,
+        {
+            ink::codegen::EmitEvent::<Registry>::emit_event(Self::env(), event);
+        }
+
+        fn register_name(&mut self, name: &str, recipient: &AccountId, expiry: u64) -> Result<()> {
+            self.ensure_not_paused(PauseGroup::Registration)?;
+
+            if self.auctions.contains(name) {
+                return Err(Error::NameUnderAuction);
+            }
+
+            match self.has_name_expired(name) {
+                Ok(false) => return Err(Error::NameAlreadyExists), // Name is already registered
+                Ok(true) if self.get_redemption_info(name).is_some() => {
+                    return Err(Error::NameInRedemptionPeriod); // Only the previous owner can renew
+                }
+                Ok(true) => self.remove_name(name), // Clean the expired name state first
+                _ => (),                            // Name is available
+            }
+
+            if recipient == &[0u8; 32].into() {
+                return Err(Error::ZeroAddress);
+            }
+
+            let registration = self.env().block_timestamp();
+
+            let address_dict = AddressDict::new(*recipient);
+            self.name_to_address_dict.insert(name, &address_dict);
+            self.name_to_period.insert(name, &(registration, expiry));
+            self.name_to_payment
+                .insert(name, &(0, expiry.saturating_sub(registration)));
+
+            /* Update convenience mapping for owned names */
+            self.add_name_to_owner(recipient, name);
+
+            /* Update convenience mapping for controlled names */
+            self.add_name_to_controller(recipient, name);
+
+            /* Update convenience mapping for resolved names */
+            self.add_name_to_resolving(recipient, name);
+
+            /* Update the global index of names */
+            self.add_name_to_global_index(name);
+
+            self.total_supply += 1;
+
+            /* Emit register event */
+            Self::emit_event(Register {
+                name: name.to_string(),
+                from: *recipient,
+                registration_timestamp: registration,
+                expiration_timestamp: expiry,
+            });
+
+            Self::emit_event(Transfer {
+                from: None,
+                to: Some(*recipient),
+                id: name.to_string().into(),
+            });
+
+            Ok(())
+        }
+
+        fn remove_name(&mut self, name: &str) {
+            // @dev Expired names need to be cleaned up as well
+            let Some(address_dict) = self.name_to_address_dict.get(name) else {
+                return;
+            };
+
+            self.name_to_address_dict.remove(name);
+            self.name_to_lock.remove(name);
+            self.name_to_period.remove(name);
+            self.name_to_payment.remove(name);
+            self.records.remove(name);
+            self.listings.remove(name);
+            self.leases.remove(name);
+            self.clear_chain_addresses(name);
+            /* Offers stay refundable, but are void for any later registration of the name */
+
+            self.remove_name_from_owner(&address_dict.owner, name);
+            self.remove_name_from_controller(&address_dict.controller, name);
+            self.remove_name_from_resolving(&address_dict.resolved, name);
+
+            /* Subnames cannot outlive their parent */
+            if let Some(parent) = Self::get_parent_name(name) {
+                self.remove_subname_from_parent(parent, name);
+            }
+            let subnames: Vec<String> = (0..self.get_parent_to_subname_count(name.to_string()))
+                .map(|idx| {
+                    self.parent_to_subnames
+                        .get((name, idx))
+                        .expect("Infallible")
+                })
+                .collect();
+            subnames
+                .iter()
+                .for_each(|subname| self.remove_name(subname));
+
+            self.remove_name_from_global_index(name);
+
+            self.total_supply -= 1;
+
+            Self::emit_event(Transfer {
+                from: Some(address_dict.owner),
+                to: None,
+                id: name.to_string().into(),
+            });
+        }
+
+        fn transfer_name(
+            &mut self,
+            to: AccountId,
+            name: &str,
+            keep_records: bool,
+            keep_controller: bool,
+            keep_resolving: bool,
+            data: &[u8],
+        ) -> core::result::Result<(), PSP34Error> {
+            let id: Id = name.to_string().into();
+            let AddressDict { owner, .. } = self
+                .get_address_dict_ref(name)
+                .map_err(|_| PSP34Error::TokenNotExists)?;
+
+            // Ensure the caller is authorised to transfer the name
+            let caller = self.env().caller();
+            if caller != owner
+                && !self.allowance(owner, caller, Some(id))
+                && self.ensure_parent_controller_owner(&caller, name).is_err()
+            {
+                return Err(PSP34Error::NotApproved);
+            }
+
+            self.do_transfer_name(
+                to,
+                name,
+                keep_records,
+                keep_controller,
+                keep_resolving,
+                data,
+            )
+        }
+
+        /// Transfers the name without checking the caller's authorisation
+        fn do_transfer_name(
+            &mut self,
+            to: AccountId,
+            name: &str,
+            keep_records: bool,
+            keep_controller: bool,
+            keep_resolving: bool,
+            data: &[u8],
+        ) -> core::result::Result<(), PSP34Error> {
+            self.ensure_not_paused(PauseGroup::Transfers)
+                .map_err(|_| PSP34Error::Custom("Transfers are paused".to_string()))?;
+            self.ensure_unlocked(name)?;
+            if self.get_active_lease_ref(name).is_some() {
+                return Err(PSP34Error::Custom("Name is leased".to_string()));
+            }
+            self.end_expired_lease(name);
+
+            if to == [0u8; 32].into() {
+                return Err(PSP34Error::Custom("Zero address".to_string()));
+            }
+
+            let id: Id = name.to_string().into();
+            let mut address_dict = self
+                .get_address_dict_ref(name)
+                .map_err(|_| PSP34Error::TokenNotExists)?;
+
+            let AddressDict {
+                owner,
+                controller,
+                resolved,
+            } = address_dict;
+            let caller = self.env().caller();
+
+            address_dict.owner = to;
+            self.remove_name_from_owner(&owner, name);
+            self.add_name_to_owner(&to, name);
+
+            if !keep_controller {
+                address_dict.controller = to;
+                self.remove_name_from_controller(&controller, name);
+                self.add_name_to_controller(&to, name);
+            }
+
+            if !keep_resolving {
+                address_dict.resolved = to;
+                self.remove_name_from_resolving(&resolved, name);
+                self.add_name_to_resolving(&to, name);
+                self.clear_chain_addresses(name);
+            }
+
+            if !keep_records {
+                self.records.remove(name);
+            }
+
+            self.listings.remove(name);
+            self.leases.remove(name);
+            self.name_to_address_dict.insert(name, &address_dict);
+            self.operator_approvals
+                .remove((&owner, &caller, &Some(id.clone())));
+
+            self.safe_transfer_check(&caller, &owner, &to, &id, data)?;
+
+            Self::emit_event(Transfer {
+                from: Some(owner),
+                to: Some(to),
+                id,
+            });
+
+            Ok(())
+        }
+
+        #[cfg_attr(test, allow(unused))]
+        fn safe_transfer_check(
+            &mut self,
+            operator: &AccountId,
+            from: &AccountId,
+            to: &AccountId,
+            id: &Id,
+            data: &[u8],
+        ) -> core::result::Result<(), PSP34Error> {
+            // @dev This is disabled during tests due to the use of `invoke_contract()` not being
+            // supported (tests end up panicking).
+            #[cfg(not(test))]
+            {
+                use ink::env::call::{build_call, ExecutionInput, Selector};
+
+                const BEFORE_RECEIVED_SELECTOR: [u8; 4] = [0xBB, 0x7D, 0xF7, 0x80];
+
+                let result = build_call::<Environment>()
+                    .call(*to)
+                    .call_flags(ink::env::CallFlags::default().set_allow_reentry(true))
+                    .exec_input(
+                        ExecutionInput::new(Selector::new(BEFORE_RECEIVED_SELECTOR))
+                            .push_arg(operator)
+                            .push_arg(from)
+                            .push_arg(id)
+                            .push_arg::<Vec<u8>>(data.to_vec()),
+                    )
+                    .returns::<core::result::Result<(), u8>>()
+                    .params()
+                    .try_invoke();
+
+                match result {
+                    Ok(v) => {
+                        ink::env::debug_println!(
+                            "Received return value \"{:?}\" from contract {:?}",
+                            v.expect("Call should be valid, don't expect a `LangError`."),
+                            from
+                        );
+                        assert_eq!(
+                            v.expect("Call should be valid, don't expect a `LangError`."),
+                            Ok(()),
+                            "The recipient contract at {to:?} does not accept token transfers.\n
+                            Expected: Ok(()), Got {v:?}"
+                        )
+                    }
+                    Err(e) => {
+                        match e {
+                            ink::env::Error::CodeNotFound | ink::env::Error::NotCallable => {
+                                // Our recipient wasn't a smart contract, so there's nothing more for
+                                // us to do
+                                ink::env::debug_println!(
+                                    "Recipient at {:?} from is not a smart contract ({:?})",
+                                    from,
+                                    e
+                                );
+                            }
+                            _ => {
+                                // We got some sort of error from the call to our recipient smart
+                                // contract, and as such we must revert this call
+                                panic!("Got error \"{e:?}\" while trying to call {from:?}")
+                            }
+                        }
+                    }
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Adds a name to the global collection
+        fn add_name_to_global_index(&mut self, name: &str) {
+            let name = name.to_string();
+            let count = self.get_all_names_count();
+
+            self.all_names.insert(count, &name);
+            self.name_to_global_index.insert(&name, &count);
+            self.all_names_count.set(&(count + 1));
+        }
+
+        /// Adds a name to owners' collection
+        fn add_name_to_owner(&mut self, owner: &AccountId, name: &str) {
+            let name = name.to_string();
+            let count = self.get_owner_to_name_count(*owner);
+            let slots = self.get_owner_to_name_slots(*owner);
+
+            // reuse the last freed slot (if any), otherwise append
+            let slot = match slots - count {
+                0 => {
+                    self.owner_to_name_slots.insert(owner, &(slots + 1));
+                    slots
+                }
+                gaps => self
+                    .owner_to_free_slots
+                    .take((owner, gaps - 1))
+                    .expect("Infallible"),
+            };
+
+            self.owner_to_names.insert((owner, &slot), &name);
+            self.name_to_owner_index.insert(&name, &slot);
+            self.owner_to_name_count.insert(owner, &(count + 1));
+        }
+
+        /// Adds a name to controllers' collection
+        fn add_name_to_controller(&mut self, controller: &AccountId, name: &str) {
+            let name = name.to_string();
+            let count = self.get_controller_to_name_count(*controller);
+            let slots = self.get_controller_to_name_slots(*controller);
+
+            // reuse the last freed slot (if any), otherwise append
+            let slot = match slots - count {
+                0 => {
+                    self.controller_to_name_slots
+                        .insert(controller, &(slots + 1));
+                    slots
+                }
+                gaps => self
+                    .controller_to_free_slots
+                    .take((controller, gaps - 1))
+                    .expect("Infallible"),
+            };
+
+            self.controller_to_names.insert((controller, &slot), &name);
+            self.name_to_controller_index.insert(&name, &slot);
+            self.controller_to_name_count
+                .insert(controller, &(count + 1));
+        }
+
+        /// Adds a name to resolvings' collection
+        fn add_name_to_resolving(&mut self, resolving: &AccountId, name: &str) {
+            let name = name.to_string();
+            let count = self.get_resolving_to_name_count(*resolving);
+            let slots = self.get_resolving_to_name_slots(*resolving);
+
+            // reuse the last freed slot (if any), otherwise append
+            let slot = match slots - count {
+                0 => {
+                    self.resolving_to_name_slots.insert(resolving, &(slots + 1));
+                    slots
+                }
+                gaps => self
+                    .resolving_to_free_slots
+                    .take((resolving, gaps - 1))
+                    .expect("Infallible"),
+            };
+
+            self.resolving_to_names.insert((resolving, &slot), &name);
+            self.name_to_resolving_index.insert(&name, &slot);
+            self.resolving_to_name_count.insert(resolving, &(count + 1));
+        }
+
+        /// Adds a subname to parents' collection
+        fn add_subname_to_parent(&mut self, parent: &str, name: &str) {
+            if self.subname_to_parent_index.contains(name) {
+                return;
+            }
+
+            let parent = parent.to_string();
+            let name = name.to_string();
+            let count = self.get_parent_to_subname_count(parent.clone());
+
+            self.parent_to_subnames.insert((&parent, &count), &name);
+            self.subname_to_parent_index.insert(&name, &count);
+            self.parent_to_subname_count.insert(&parent, &(count + 1));
+        }
+
+        /// Deletes a name from the global collection
+        fn remove_name_from_global_index(&mut self, name: &str) {
+            // @dev Names registered prior to the index are missing until backfilled
+            let Some(idx) = self.name_to_global_index.get(name) else {
+                return;
+            };
+            let count = self.get_all_names_count();
+
+            // if name is not stored at the last index
+            if idx != count - 1 {
+                // swap last index item to pos:idx
+                let last_name = self.all_names.get(count - 1).expect("Infallible");
+                self.all_names.insert(idx, &last_name);
+                self.name_to_global_index.insert(&last_name, &idx);
+            }
+
+            // remove last index
+            self.all_names.remove(count - 1);
+            self.name_to_global_index.remove(name);
+            self.all_names_count.set(&(count - 1));
+        }
+
+        /// Deletes a name from owner
+        fn remove_name_from_owner(&mut self, owner: &AccountId, name: &str) {
+            let idx = self.name_to_owner_index.get(name).expect("Infallible");
+            let count = self.get_owner_to_name_count(*owner);
+            let slots = self.get_owner_to_name_slots(*owner);
+
+            // leave a gap, the slots of the other names remain unchanged
+            self.owner_to_names.remove((owner, idx));
+            self.name_to_owner_index.remove(name);
+            self.owner_to_name_count.insert(owner, &(count - 1));
+
+            // the last slot is dropped, any other is freed to be reused
+            match idx + 1 == slots {
+                true => self.owner_to_name_slots.insert(owner, &(slots - 1)),
+                false => {
+                    self.owner_to_name_slots.insert(owner, &slots);
+                    self.owner_to_free_slots
+                        .insert((owner, slots - count), &idx)
+                }
+            };
+        }
+
+        /// Deletes a name from controllers' collection
+        fn remove_name_from_controller(&mut self, controller: &AccountId, name: &str) {
+            let idx = self.name_to_controller_index.get(name).expect("Infallible");
+            let count = self.get_controller_to_name_count(*controller);
+            let slots = self.get_controller_to_name_slots(*controller);
+
+            // leave a gap, the slots of the other names remain unchanged
+            self.controller_to_names.remove((controller, idx));
+            self.name_to_controller_index.remove(name);
+            self.controller_to_name_count
+                .insert(controller, &(count - 1));
+
+            // the last slot is dropped, any other is freed to be reused
+            match idx + 1 == slots {
+                true => self
+                    .controller_to_name_slots
+                    .insert(controller, &(slots - 1)),
+                false => {
+                    self.controller_to_name_slots.insert(controller, &slots);
+                    self.controller_to_free_slots
+                        .insert((controller, slots - count), &idx)
+                }
+            };
+        }
+
+        /// Deletes a name from resolvings' collection
+        fn remove_name_from_resolving(&mut self, resolving: &AccountId, name: &str) {
+            let idx = self.name_to_resolving_index.get(name).expect("Infallible");
+            let count = self.get_resolving_to_name_count(*resolving);
+            let slots = self.get_resolving_to_name_slots(*resolving);
+
+            // leave a gap, the slots of the other names remain unchanged
+            self.resolving_to_names.remove((resolving, idx));
+            self.name_to_resolving_index.remove(name);
+            self.resolving_to_name_count.insert(resolving, &(count - 1));
+
+            // the last slot is dropped, any other is freed to be reused
+            match idx + 1 == slots {
+                true => self.resolving_to_name_slots.insert(resolving, &(slots - 1)),
+                false => {
+                    self.resolving_to_name_slots.insert(resolving, &slots);
+                    self.resolving_to_free_slots
+                        .insert((resolving, slots - count), &idx)
+                }
+            };
+
+            /* Check if the resolved address had this name set as the primary name */
+            /* If yes -> clear it */
+            if self.address_to_primary_name.get(resolving) == Some(name.to_string()) {
+                self.address_to_primary_name.remove(resolving);
+
+                Self::emit_event(SetPrimaryName {
+                    account: *resolving,
+                    primary_name: None,
+                });
+            }
+        }
+
+        /// Deletes a subname from parents' collection
+        fn remove_subname_from_parent(&mut self, parent: &str, name: &str) {
+            let Some(idx) = self.subname_to_parent_index.get(name) else {
+                return;
+            };
+            let parent = parent.to_string();
+            let count = self.get_parent_to_subname_count(parent.clone());
+
+            // if name is not stored at the last index
+            if idx != count - 1 {
+                // swap last index item to pos:idx
+                let last_name = self
+                    .parent_to_subnames
+                    .get((&parent, (count - 1)))
+                    .expect("Infallible");
+                self.parent_to_subnames.insert((&parent, idx), &last_name);
+                self.subname_to_parent_index.insert(&last_name, &idx);
+            }
+
+            // remove last index
+            self.parent_to_subnames.remove((&parent, count - 1));
+            self.subname_to_parent_index.remove(name);
+            self.parent_to_subname_count.insert(&parent, &(count - 1));
+        }
+
+        /// Removes the offer from storage & releases its escrow
+        fn take_offer(&mut self, name: &str, bidder: &AccountId) -> Result<Offer> {
+            let Some(offer) = self.offers.take((name, bidder)) else {
+                return Err(Error::OfferNotFound);
+            };
+
+            self.remove_offer_from_indexes(name, bidder);
+            self.escrowed_balance
+                .set(&(self.get_escrowed_balance() - offer.amount));
+
+            Ok(offer)
+        }
+
+        /// Adds an offer to the names' & bidders' collections
+        fn add_offer_to_indexes(&mut self, name: &str, bidder: &AccountId) {
+            let name = name.to_string();
+
+            let count = self.get_name_to_bidder_count(name.clone());
+            self.name_to_bidders.insert((&name, &count), bidder);
+            self.offer_to_name_index.insert((&name, bidder), &count);
+            self.name_to_bidder_count.insert(&name, &(count + 1));
+
+            let count = self.get_bidder_to_name_count(*bidder);
+            self.bidder_to_names.insert((bidder, &count), &name);
+            self.offer_to_bidder_index.insert((&name, bidder), &count);
+            self.bidder_to_name_count.insert(bidder, &(count + 1));
+        }
+
+        /// Deletes an offer from the names' & bidders' collections
+        fn remove_offer_from_indexes(&mut self, name: &str, bidder: &AccountId) {
+            let name = name.to_string();
+
+            let idx = self
+                .offer_to_name_index
+                .get((&name, bidder))
+                .expect("Infallible");
+            let count = self.get_name_to_bidder_count(name.clone());
+
+            // if bidder is not stored at the last index
+            if idx != count - 1 {
+                // swap last index item to pos:idx
+                let last_bidder = self
+                    .name_to_bidders
+                    .get((&name, (count - 1)))
+                    .expect("Infallible");
+                self.name_to_bidders.insert((&name, idx), &last_bidder);
+                self.offer_to_name_index.insert((&name, &last_bidder), &idx);
+            }
+
+            // remove last index
+            self.name_to_bidders.remove((&name, count - 1));
+            self.offer_to_name_index.remove((&name, bidder));
+            self.name_to_bidder_count.insert(&name, &(count - 1));
+
+            let idx = self
+                .offer_to_bidder_index
+                .get((&name, bidder))
+                .expect("Infallible");
+            let count = self.get_bidder_to_name_count(*bidder);
+
+            // if name is not stored at the last index
+            if idx != count - 1 {
+                // swap last index item to pos:idx
+                let last_name = self
+                    .bidder_to_names
+                    .get((bidder, (count - 1)))
+                    .expect("Infallible");
+                self.bidder_to_names.insert((bidder, idx), &last_name);
+                self.offer_to_bidder_index
+                    .insert((&last_name, bidder), &idx);
+            }
+
+            // remove last index
+            self.bidder_to_names.remove((bidder, count - 1));
+            self.offer_to_bidder_index.remove((&name, bidder));
+            self.bidder_to_name_count.insert(bidder, &(count - 1));
+        }
+
+        /// Deletes all multi-chain addresses of a name
+        fn clear_chain_addresses(&mut self, name: &str) {
+            let coin_types = self.name_to_coin_types.take(name).unwrap_or_default();
+            coin_types.iter().for_each(|coin_type| {
+                self.chain_addresses.remove((name, coin_type));
+            });
+        }
+
+        /// Iterates over the active names the address owns, without loading them all at once
+        fn owned_names(&self, owner: AccountId) -> impl Iterator<Item = String> + '_ {
+            (0..self.get_owner_to_name_slots(owner))
+                .filter_map(move |idx| self.owner_to_names.get((owner, idx)))
+                .filter(|name| self.has_name_expired(name) == Ok(false))
+        }
+
+        /// Collects up to `limit` active names, starting at slot `offset` (of `slots`)
+        /// @dev `get_item` maps the name of a slot to the item (if any) to be collected
+        fn paginate_names<T>(
+            &self,
+            slots: u128,
+            offset: u128,
+            limit: u128,
+            get_name: impl Fn(u128) -> Option<String>,
+            get_item: impl Fn(String) -> Option<T>,
+        ) -> (Vec<T>, Option<u128>) {
+            let mut items = Vec::new();
+            let mut idx = offset;
+
+            while idx < slots && (items.len() as u128) < limit {
+                if let Some(name) = get_name(idx) {
+                    if self.has_name_expired(&name) == Ok(false) {
+                        items.extend(get_item(name));
+                    }
+                }
+                idx += 1;
+            }
+
+            let cursor = (idx < slots).then_some(idx);
+            (items, cursor)
+        }
+
+        /// Paginates over the given collection of names of an address
+        fn paginate_collection<T>(
+            &self,
+            address: AccountId,
+            (collection, offset): NamesCursor,
+            limit: u128,
+            get_item: impl Fn(String) -> Option<T>,
+        ) -> (Vec<T>, Option<NamesCursor>) {
+            let (items, cursor) = match collection {
+                NameCollection::Owned => self.paginate_names(
+                    self.get_owner_to_name_slots(address),
+                    offset,
+                    limit,
+                    |idx| self.owner_to_names.get((address, idx)),
+                    get_item,
+                ),
+                NameCollection::Controlled => self.paginate_names(
+                    self.get_controller_to_name_slots(address),
+                    offset,
+                    limit,
+                    |idx| self.controller_to_names.get((address, idx)),
+                    get_item,
+                ),
+                NameCollection::Resolving => self.paginate_names(
+                    self.get_resolving_to_name_slots(address),
+                    offset,
+                    limit,
+                    |idx| self.resolving_to_names.get((address, idx)),
+                    get_item,
+                ),
+            };
+            (items, cursor.map(|slot| (collection, slot)))
+        }
+
+        /// Returns the parent of a subname, i.e. `alice` for `pay.alice`
+        fn get_parent_name(name: &str) -> Option<&str> {
+            name.split_once('.').map(|(_, parent)| parent)
+        }
+
+        fn is_name_allowed(&self, name: &str) -> bool {
+            /* Name cannot be empty */
+            if name.is_empty() {
+                return false;
+            }
+
+            /* Name cannot contain the subname separator */
+            if name.contains('.') {
+                return false;
+            }
+
+            /* Name must be legal */
+            if let Some(name_checker) = &self.name_checker {
+                if name_checker.is_name_allowed(name.to_string()) != Ok(()) {
+                    return false;
+                }
+            }
+            true
+        }
+
+        #[ink(message)]
+        pub fn get_name_price(
+            &self,
+            name: String,
+            recipient: AccountId,
+            years_to_register: u8,
+            referrer: Option<String>,
+        ) -> Result<(Balance, Balance, Balance, Option<AccountId>)> {
+            let (base_price, premium) = self.get_base_price_ref(&name, years_to_register)?;
+
+            // The launch phase may mark up (or down) the base price, premiums are left as is
+            let multiplier = self
+                .get_current_phase_ref()
+                .map_or(MAX_BPS as u32, |p| p.price_multiplier);
+            let base_price = base_price * multiplier as Balance / MAX_BPS as Balance;
+
+            let price = base_price + premium;
+            let mut discount = 0;
+            let mut referrer_addr = None;
+
+            if let Some(referrer_name) = referrer {
+                if self.validate_referrer(recipient, referrer_name.clone()) {
+                    referrer_addr = Some(self.get_address(referrer_name.clone()).unwrap());
+
+                    discount = match self.get_referral_rate(referrer_name) {
+                        Some(rate) => price * rate as Balance / MAX_BPS as Balance,
+                        None => {
+                            // discount = 5 * price / 100; // 5% discount
+                            // overflow-check bug patch
+                            let tmp = 5 * price;
+                            (tmp >> 7) + (tmp >> 9) + (tmp >> 12) // 5.005% discount
+                        }
+                    };
+                }
+            }
+
+            Ok((base_price, premium, discount, referrer_addr))
+        }
+
+        #[ink(message)]
+        pub fn batch_get_name_price(
+            &self,
+            input: Vec<(String, AccountId, u8, Option<String>)>,
+        ) -> Vec<Result<(Balance, Balance, Balance, Option<AccountId>)>> {
+            input
+                .into_iter()
+                .map(|item| self.get_name_price(item.0, item.1, item.2, item.3))
+                .collect()
+        }
+
+        #[ink(message)]
+        pub fn validate_referrer(&self, recipient: AccountId, referrer_name: String) -> bool {
+            self.get_address_dict_ref(&referrer_name).is_ok_and(|x| {
+                recipient != x.owner && recipient != x.controller && recipient != x.resolved
+            })
+        }
+
+        fn get_address_dict_ref(&self, name: &str) -> Result<AddressDict> {
+            let mut address_dict = self
+                .name_to_address_dict
+                .get(name)
+                .filter(|_| self.has_name_expired(name) == Ok(false))
+                .ok_or(Error::NameDoesntExist)?;
+
+            // @dev The control reverts (lazily) once the lease ends
+            if let Some(snapshot) = self.get_ended_lease_snapshot(name) {
+                address_dict.set_controller(snapshot.controller);
+                address_dict.set_resolved(snapshot.resolved);
+            }
+            Ok(address_dict)
+        }
+
+        /// Returns the state to be restored by the name's lease, if it has ended
+        fn get_ended_lease_snapshot(&self, name: &str) -> Option<LeaseSnapshot> {
+            self.leases
+                .get(name)
+                .filter(|lease| lease.has_ended(self.env().block_timestamp()))
+                .and_then(|lease| lease.snapshot)
+        }
+
+        fn get_active_lease_ref(&self, name: &str) -> Option<Lease> {
+            self.leases
+                .get(name)
+                .filter(|lease| lease.is_active(self.env().block_timestamp()))
+        }
+
+        /// Restores the control & records of the name as of the start of its lease, if it has ended
+        fn end_expired_lease(&mut self, name: &str) {
+            let Some(lease) = self.leases.get(name) else {
+                return;
+            };
+            if !lease.has_ended(self.env().block_timestamp()) {
+                return;
+            }
+            self.leases.remove(name);
+
+            // @dev The stored state is used as the indexes are in sync with it
+            if let (Some(mut address_dict), Some(snapshot)) =
+                (self.name_to_address_dict.get(name), lease.snapshot)
+            {
+                let AddressDict {
+                    controller,
+                    resolved,
+                    ..
+                } = address_dict;
+
+                if controller != snapshot.controller {
+                    address_dict.set_controller(snapshot.controller);
+                    self.remove_name_from_controller(&controller, name);
+                    self.add_name_to_controller(&snapshot.controller, name);
+                }
+                if resolved != snapshot.resolved {
+                    address_dict.set_resolved(snapshot.resolved);
+                    self.remove_name_from_resolving(&resolved, name);
+                    self.add_name_to_resolving(&snapshot.resolved, name);
+                }
+                self.name_to_address_dict.insert(name, &address_dict);
+
+                /* The records set by the tenant are dropped */
+                self.records.insert(name, &snapshot.records);
+                self.clear_chain_addresses(name);
+                let coin_types: Vec<CoinType> = snapshot
+                    .chain_addresses
+                    .iter()
+                    .map(|(coin_type, address)| {
+                        self.chain_addresses.insert((name, coin_type), address);
+                        *coin_type
+                    })
+                    .collect();
+                self.name_to_coin_types.insert(name, &coin_types);
+            }
+
+            Self::emit_event(LeaseEnded {
+                name: name.to_string(),
+                tenant: lease.tenant,
+            });
+        }
+
+        fn get_records_ref(&self, name: &str) -> Vec<(String, String)> {
+            if self.has_name_expired(name) != Ok(false) {
+                return Vec::new();
+            }
+            match self.get_ended_lease_snapshot(name) {
+                Some(snapshot) => snapshot.records,
+                None => self.records.get(name).unwrap_or_default(),
+            }
+        }
+
+        /// Returns the (base price, premium) charged by the fee calculator
+        fn get_base_price_ref(&self, name: &str, years: u8) -> Result<(Balance, Balance)> {
+            match &self.fee_calculator {
+                None => Ok((1000, 0)), // For unit testing only
+                Some(model) => model
+                    .get_name_price(name.to_string(), years, self.get_released_at(name))
+                    .map_err(Error::FeeError),
+            }
+        }
+
+        fn get_registration_period_ref(&self, name: &str) -> Result<(u64, u64)> {
+            self.name_to_period
+                .get(name)
+                .map(|(start, expiry)| {
+                    let expiry = match expiry {
+                        0 => 0,
+                        _ if expiry <= GRACE_TIMESTAMP => GRACE_TIMESTAMP,
+                        _ => expiry,
+                    };
+                    (start, expiry)
+                })
+                .ok_or(Error::NameDoesntExist)
+        }
+
+        /// Returns the previous owner & the end of the redemption period, if the name is in it
+        fn get_redemption_info(&self, name: &str) -> Option<(AccountId, u64)> {
+            if Self::get_parent_name(name).is_some() {
+                return None;
+            }
+
+            let (_, expiry) = self.get_registration_period_ref(name).ok()?;
+            let end = expiry + self.get_redemption_period();
+            let now = self.env().block_timestamp();
+            if now < expiry || end <= now {
+                return None;
+            }
+
+            self.name_to_address_dict
+                .get(name)
+                .map(|address_dict| (address_dict.owner, end))
+        }
+
+        /// Returns the time a name was released at, if its redemption period is over
+        fn get_released_at(&self, name: &str) -> Option<u64> {
+            let (_, expiry) = self.get_registration_period_ref(name).ok()?;
+            let released_at = expiry + self.get_redemption_period();
+            (released_at <= self.env().block_timestamp()).then_some(released_at)
+        }
+
+        /// Returns the premium of a name whose redemption period is over
+        fn get_release_premium_ref(&self, name: &str) -> Balance {
+            match (self.get_released_at(name), &self.fee_calculator) {
+                (Some(released_at), Some(model)) => model.get_release_premium(released_at),
+                _ => 0,
+            }
+        }
+
+        fn has_commitment_expired(&self, timestamp: u64) -> bool {
+            let (_, max_age) = self.get_commitment_age();
+            timestamp + max_age < self.env().block_timestamp()
+        }
+
+        fn has_name_expired(&self, name: &str) -> Result<bool> {
+            match self.get_registration_period_ref(name) {
+                Ok((_, expiry)) => Ok(expiry <= self.env().block_timestamp()),
+                _ => Err(Error::NameDoesntExist),
+            }
+        }
+
+        fn get_static_attribute_ref(&self, name: &str, key: &str) -> Option<String> {
+            match key {
+                "TLD" => Some(self.tld.clone()),
+                "Length" => Some(name.chars().count().to_string()),
+                "Registration" => Some(match self.get_registration_period_ref(name) {
+                    Ok(period) => period.0.to_string(),
+                    _ => String::new(),
+                }),
+                "Expiration" => Some(match self.get_registration_period_ref(name) {
+                    Ok(period) => period.1.to_string(),
+                    _ => String::new(),
+                }),
+                _ => None,
+            }
+        }
+
+        fn is_lock_requested(&self, data: &[u8]) -> bool {
+            // Generic permissionless system
+            data == "azero.id-lock".as_bytes()
+        }
+
+        fn lock_name(&mut self, to: AccountId, name: &str) -> core::result::Result<(), PSP34Error> {
+            self.ensure_unlocked(name)?; // Should carry-forward be allowed?
+
+            let caller = self.env().caller();
+            let id = Some(name.to_string().into());
+            let owner = self
+                .get_owner(name.to_string())
+                .map_err(|_| PSP34Error::TokenNotExists)?;
+
+            if !self.allowance(owner, caller, id.clone()) {
+                return Err(PSP34Error::NotApproved);
+            }
+
+            self.operator_approvals.remove(&(owner, caller, id));
+            self.name_to_lock.insert(name, &to);
+
+            Self::emit_event(Lock {
+                name: name.to_string(),
+                caller,
+                unlocker: to,
+            });
+
+            Ok(())
+        }
+
+        /// Renews the name & returns its (native) price, net of the redeemed credit if paid natively
+        fn do_renew(
+            &mut self,
+            name: String,
+            years_to_renew: u8,
+            bonus_name: Option<String>,
+            token: Option<AccountId>,
+        ) -> Result<Balance> {
+            self.ensure_not_paused(PauseGroup::Renewal)?;
+
+            match self.has_name_expired(&name) {
+                Ok(false) => (),
+                // Only the previous owner can renew during the redemption period
+                Ok(true) => match self.get_redemption_info(&name) {
+                    Some((owner, _)) if owner == self.env().caller() => (),
+                    Some(_) => return Err(Error::CallerIsNotOwner),
+                    None => return Err(Error::NameDoesntExist),
+                },
+                _ => return Err(Error::NameDoesntExist),
+            }
+
+            // Subnames are bound to their parent's expiry
+            if Self::get_parent_name(&name).is_some() {
+                return Err(Error::NotSupportedForSubnames);
+            }
+
+            let (registration, old_expiry) = self.get_registration_period_ref(&name)?;
+
+            let (base_price, premium) = match &self.fee_calculator {
+                None => (1000, 0), // For unit testing only
+                Some(model) => model
+                    .get_name_price(name.clone(), years_to_renew, None)
+                    .map_err(Error::FeeError)?,
+            };
+            let (price, native_paid) = match token {
+                None => {
+                    let price = self.redeem_registration_credit(base_price + premium);
+                    (price, price)
+                }
+                Some(_) => (base_price + premium, 0),
+            };
+
+            let new_expiry = old_expiry + YEAR * years_to_renew as u64;
+            self.name_to_period
+                .insert(&name, &(registration, new_expiry));
+            self.record_payment(&name, native_paid, YEAR * years_to_renew as u64);
+
+            // Emit event
+            Self::emit_event(Renew {
+                name: name.clone(),
+                old_expiry,
+                new_expiry,
+            });
+
+            let owner = self.get_owner(name)?;
+            self.redeem_bonus_name(bonus_name, years_to_renew, owner)?;
+
+            Ok(price)
+        }
+
+        fn redeem_bonus_name(
+            &mut self,
+            name: Option<String>,
+            period_extended: u8,
+            owner: AccountId,
+        ) -> Result<()> {
+            if period_extended < 10 || name.is_none() {
+                return Ok(());
+            }
+
+            let name = name.unwrap();
+            if !self.is_name_allowed(&name) || name.len() < 5 {
+                return Err(Error::NameNotAllowed);
+            }
+            // The name must not be a reserved name
+            if self.reserved_names.contains(&name) {
+                return Err(Error::CannotBuyReservedName);
+            }
+
+            let expiry_time = self.env().block_timestamp() + YEAR;
+            self.register_name(&name, &owner, expiry_time)
+        }
+
+        /// Collects the price from the caller & accrues the `revenue` part of it to the beneficiaries
+        fn handle_payment(&mut self, price: Balance, revenue: Balance) -> Result<()> {
+            let transferred = self.env().transferred_value();
+            if transferred < price {
+                return Err(Error::FeeNotPaid);
+            } else if transferred > price {
+                let caller = self.env().caller();
+                let change = transferred - price;
+
+                if self.env().transfer(caller, change).is_err() {
+                    return Err(Error::WithdrawFailed);
+                }
+            }
+            self.accrue_revenue(revenue);
+            Ok(())
+        }
+
+        /// Releases an escrowed bid back to the bidder
+        fn refund_bid(&mut self, bidder: AccountId, amount: Balance) -> Result<()> {
+            self.escrowed_balance
+                .set(&(self.get_escrowed_balance() - amount));
+
+            match self.env().transfer(bidder, amount) {
+                Ok(_) => Ok(()),
+                Err(_) => Err(Error::WithdrawFailed),
+            }
+        }
+
+        /// Splits the revenue among the beneficiaries as per their shares.
+        // @dev The unallocated part (incl. rounding dust) is left to the treasury
+        fn accrue_revenue(&mut self, revenue: Balance) {
+            self.lifetime_revenue
+                .set(&(self.get_lifetime_revenue() + revenue));
+
+            let mut allocated = 0;
+            for (beneficiary, share) in self.get_revenue_shares() {
+                let amount = revenue * share as Balance / MAX_BPS as Balance;
+                let claimable = self.get_claimable_revenue(beneficiary);
+                self.claimable_revenue
+                    .insert(beneficiary, &(claimable + amount));
+                allocated += amount;
+            }
+
+            let total_claimable = self.total_claimable_revenue.get().unwrap_or(0);
+            self.total_claimable_revenue
+                .set(&(total_claimable + allocated));
+        }
+
+        /// Deducts the caller's registration credit from the (native) price & returns the rest
+        fn redeem_registration_credit(&mut self, price: Balance) -> Balance {
+            let caller = self.env().caller();
+            let credit = self.get_registration_credit(caller);
+            let redeemed = credit.min(price);
+
+            if redeemed > 0 {
+                self.registration_credits
+                    .insert(caller, &(credit - redeemed));
+            }
+            price - redeemed
+        }
+
+        /// Records the fee paid natively for the name & the registration time it extends
+        // @dev Fees paid in tokens or by the registration credit extend the time only, so that
+        // the release refund never pays out native funds that were never paid in
+        fn record_payment(&mut self, name: &str, amount: Balance, extension: u64) {
+            let (paid, paid_time) = self.name_to_payment.get(name).unwrap_or_default();
+            self.name_to_payment
+                .insert(name, &(paid + amount, paid_time + extension));
+        }
+
+        fn record_withdrawal(&mut self, beneficiary: AccountId, amount: Balance) {
+            let withdrawn = self.get_withdrawn_revenue(beneficiary);
+            self.withdrawn_revenue
+                .insert(beneficiary, &(withdrawn + amount));
+            self.total_withdrawn_revenue
+                .set(&(self.get_total_withdrawn_revenue() + amount));
+        }
+
+        /// Credits the referral reward to the referrer's claimable balance & updates its stats
+        fn credit_referral_reward(
+            &mut self,
+            referrer_name: &str,
+            referrer_addr: AccountId,
+            token: Option<AccountId>,
+            reward: Balance,
+            native_reward: Balance,
+        ) {
+            let rewards = self.get_referral_rewards(referrer_addr, token);
+            self.referral_rewards
+                .insert((referrer_addr, token), &(rewards + reward));
+
+            match token {
+                Some(token) => {
+                    let escrowed = self.get_token_escrowed_balance(token);
+                    self.token_escrowed_balance
+                        .insert(token, &(escrowed + reward));
+                }
+                None => {
+                    self.escrowed_balance
+                        .set(&(self.get_escrowed_balance() + reward));
+                    self.referral_payouts
+                        .set(&(self.get_referral_payouts() + reward));
+                }
+            }
+
+            let (referrals, earnings) = self.get_referrer_stats(referrer_name.to_string());
+            self.referrer_stats
+                .insert(referrer_name, &(referrals + 1, earnings + native_reward));
+        }
+
+        /// Converts a price in the native token to the payment token (if any)
+        fn get_price_in(&self, token: Option<AccountId>, price: Balance) -> Result<Balance> {
+            let Some(token) = token else {
+                return Ok(price);
+            };
+            let Some((numerator, denominator)) = self.token_rates.get(token) else {
+                return Err(Error::TokenNotAccepted);
+            };
+
+            price
+                .checked_mul(numerator)
+                .map(|amount| amount / denominator)
+                .ok_or(Error::InvalidTokenRate)
+        }
+
+        /// Collects the price in the payment token (if any) from the caller
+        // @dev Only the native revenue is split among the beneficiaries, tokens are withdrawn by the treasury
+        fn handle_payment_in(
+            &mut self,
+            token: Option<AccountId>,
+            price: Balance,
+            revenue: Balance,
+        ) -> Result<()> {
+            let Some(token) = token else {
+                return self.handle_payment(price, revenue);
+            };
+            let caller = self.env().caller();
+            let contract = self.env().account_id();
+
+            if self.psp22_allowance(token, caller, contract) < price {
+                return Err(Error::InsufficientAllowance);
+            }
+            self.psp22_transfer_from(token, caller, contract, price)
+        }
+
+        /// Transfers funds in the payment token (if any) from the contract
+        fn transfer_funds(
+            &mut self,
+            token: Option<AccountId>,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<()> {
+            match token {
+                Some(token) => self.psp22_transfer(token, to, amount),
+                None => self
+                    .env()
+                    .transfer(to, amount)
+                    .map_err(|_| Error::WithdrawFailed),
+            }
+        }
+
+        // @dev PSP22 cross-contract calls are replaced by `mock_psp22` during tests due to
+        // the use of `invoke_contract()` not being supported (tests end up panicking).
+        #[cfg(not(test))]
+        fn psp22_allowance(
+            &self,
+            token: AccountId,
+            owner: AccountId,
+            spender: AccountId,
+        ) -> Balance {
+            use ink::env::call::{build_call, ExecutionInput, Selector};
+
+            const ALLOWANCE_SELECTOR: [u8; 4] = [0x4D, 0x47, 0xD9, 0x21];
+            build_call::<Environment>()
+                .call(token)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ALLOWANCE_SELECTOR))
+                        .push_arg(owner)
+                        .push_arg(spender),
+                )
+                .returns::<Balance>()
+                .invoke()
+        }
+
+        #[cfg(not(test))]
+        fn psp22_balance_of(&self, token: AccountId, owner: AccountId) -> Balance {
+            use ink::env::call::{build_call, ExecutionInput, Selector};
+
+            const BALANCE_OF_SELECTOR: [u8; 4] = [0x65, 0x68, 0x38, 0x2F];
+            build_call::<Environment>()
+                .call(token)
+                .exec_input(ExecutionInput::new(Selector::new(BALANCE_OF_SELECTOR)).push_arg(owner))
+                .returns::<Balance>()
+                .invoke()
+        }
+
+        #[cfg(not(test))]
+        fn psp22_transfer(
+            &mut self,
+            token: AccountId,
+            to: AccountId,
+            value: Balance,
+        ) -> Result<()> {
+            use ink::env::call::{build_call, ExecutionInput, Selector};
+
+            const TRANSFER_SELECTOR: [u8; 4] = [0xDB, 0x20, 0xF9, 0xF5];
+            build_call::<Environment>()
+                .call(token)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(TRANSFER_SELECTOR))
+                        .push_arg(to)
+                        .push_arg(value)
+                        .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<core::result::Result<(), PSP22Error>>()
+                .invoke()
+                .map_err(Error::TokenTransferFailed)
+        }
+
+        #[cfg(not(test))]
+        fn psp22_transfer_from(
+            &mut self,
+            token: AccountId,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+        ) -> Result<()> {
+            use ink::env::call::{build_call, ExecutionInput, Selector};
+
+            const TRANSFER_FROM_SELECTOR: [u8; 4] = [0x54, 0xB3, 0xC7, 0x6E];
+            build_call::<Environment>()
+                .call(token)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(TRANSFER_FROM_SELECTOR))
+                        .push_arg(from)
+                        .push_arg(to)
+                        .push_arg(value)
+                        .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<core::result::Result<(), PSP22Error>>()
+                .invoke()
+                .map_err(Error::TokenTransferFailed)
+        }
+
+        #[cfg(test)]
+        fn psp22_allowance(
+            &self,
+            token: AccountId,
+            owner: AccountId,
+            spender: AccountId,
+        ) -> Balance {
+            crate::mock_psp22::allowance(token, owner, spender)
+        }
+
+        #[cfg(test)]
+        fn psp22_balance_of(&self, token: AccountId, owner: AccountId) -> Balance {
+            crate::mock_psp22::balance_of(token, owner)
+        }
+
+        #[cfg(test)]
+        fn psp22_transfer(
+            &mut self,
+            token: AccountId,
+            to: AccountId,
+            value: Balance,
+        ) -> Result<()> {
+            crate::mock_psp22::transfer(token, self.env().account_id(), to, value)
+                .map_err(Error::TokenTransferFailed)
+        }
+
+        #[cfg(test)]
+        fn psp22_transfer_from(
+            &mut self,
+            token: AccountId,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+        ) -> Result<()> {
+            let spender = self.env().account_id();
+            crate::mock_psp22::transfer_from(token, spender, from, to, value)
+                .map_err(Error::TokenTransferFailed)
+        }
+    }
+
+    impl PSP34 for Registry {
+        // TLD is our collection id
+        #[ink(message)]
+        fn collection_id(&self) -> Id {
+            let id = ".".to_string() + &self.tld.to_ascii_uppercase() + " Domains";
+            id.into()
+        }
+
+        #[ink(message)]
+        fn balance_of(&self, owner: AccountId) -> u32 {
+            self.owned_names(owner).count() as u32
+        }
+
+        #[ink(message)]
+        fn owner_of(&self, id: Id) -> Option<AccountId> {
+            id.try_into().map_or(None, |name| self.get_owner(name).ok())
+        }
+
+        #[ink(message)]
+        fn allowance(&self, owner: AccountId, operator: AccountId, id: Option<Id>) -> bool {
+            if self.operator_approvals.contains(&(owner, operator, None)) {
+                return true;
+            }
+
+            if let Some(id_val) = id.clone() {
+                let Ok(name) = TryInto::<String>::try_into(id_val) else {
+                    return false;
+                };
+
+                let Ok((registration_time, expiration_time)) =
+                    self.get_registration_period_ref(&name)
+                else {
+                    return false;
+                };
+
+                let Some(approval_time) = self.operator_approvals.get(&(owner, operator, id))
+                else {
+                    return false;
+                };
+
+                // @dev This check ensures that approval do not carry forward post domain-expiration
+                let current_time = self.env().block_timestamp();
+                return registration_time <= approval_time && current_time <= expiration_time;
+            }
+
+            false
+        }
+
+        #[ink(message)]
+        fn approve(
+            &mut self,
+            operator: AccountId,
+            id: Option<Id>,
+            approved: bool,
+        ) -> core::result::Result<(), PSP34Error> {
+            let mut caller = self.env().caller();
+
+            if operator == [0u8; 32].into() {
+                return Err(PSP34Error::Custom("Zero address".to_string()));
+            }
+
+            if let Some(id) = &id {
+                let owner = self
+                    .owner_of(id.clone())
+                    .ok_or(PSP34Error::TokenNotExists)?;
+
+                if approved && owner == operator {
+                    return Err(PSP34Error::SelfApprove);
+                }
+
+                if owner != caller && !self.allowance(owner, caller, None) {
+                    return Err(PSP34Error::NotApproved);
+                };
+                caller = owner;
+            }
+
+            match approved {
+                true => {
+                    let timestamp = self.env().block_timestamp();
+                    self.operator_approvals
+                        .insert((&caller, &operator, &id), &timestamp);
+                }
+                false => self.operator_approvals.remove((&caller, &operator, &id)),
+            }
+
+            // Emit event
+            Self::emit_event(Approval {
+                owner: caller,
+                operator,
+                id,
+                approved,
+            });
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn transfer(
+            &mut self,
+            to: AccountId,
+            id: Id,
+            data: Vec<u8>,
+        ) -> core::result::Result<(), PSP34Error> {
+            let caller = self.env().caller();
+            let name: String = id.clone().try_into()?;
+            let owner = self
+                .get_owner(name.clone())
+                .map_err(|_| PSP34Error::TokenNotExists)?;
+
+            if self.is_lock_requested(&data) {
+                return self.lock_name(to, &name);
+            } else if self.name_to_lock.get(&name) == Some(caller) {
+                self.name_to_lock.remove(&name);
+
+                Self::emit_event(Unlock { name: name.clone() });
+
+                if to == owner {
+                    return Ok(());
+                }
+
+                let time = self.env().block_timestamp();
+                self.operator_approvals
+                    .insert(&(owner, caller, Some(id)), &time);
+            }
+            self.transfer_name(to, &name, false, false, false, &data)
+        }
+
+        #[ink(message)]
+        fn total_supply(&self) -> Balance {
+            self.total_supply
+        }
+    }
+
+    impl PSP34Enumerable for Registry {
+        #[ink(message)]
+        fn owners_token_by_index(
+            &self,
+            owner: AccountId,
+            index: u128,
+        ) -> core::result::Result<Id, PSP34Error> {
+            match self.owned_names(owner).nth(index as usize) {
+                Some(name) => Ok(name.into()),
+                None => Err(PSP34Error::TokenNotExists),
+            }
+        }
+
+        #[ink(message)]
+        fn token_by_index(&self, index: u128) -> core::result::Result<Id, PSP34Error> {
+            match self.all_names.get(index) {
+                Some(name) => Ok(name.into()),
+                None => Err(PSP34Error::TokenNotExists),
+            }
+        }
+    }
+
+    impl PSP34Metadata for Registry {
+        #[ink(message)]
+        fn get_attribute(&self, id: Id, key: Vec<u8>) -> Option<Vec<u8>> {
+            match TryInto::<String>::try_into(id) {
+                Ok(name) => {
+                    let Ok(key) = String::from_utf8(key) else {
+                        return None;
+                    };
+
+                    self.get_static_attribute_ref(&name, &key)
+                        .map(|s| s.into_bytes())
+                }
+                Err(_) => None,
+            }
+        }
+    }
+
+    impl Psp34Traits for Registry {
+        #[ink(message)]
+        fn get_owner(&self) -> AccountId {
+            self.admin
+        }
+
+        #[ink(message)]
+        fn token_uri(&self, token_id: Id) -> String {
+            let name: core::result::Result<String, _> = token_id.try_into();
+
+            match name {
+                Ok(name) => self.base_uri.clone() + &name + "." + &self.tld + ".json",
+                _ => String::new(),
+            }
+        }
+
+        #[ink(message)]
+        fn set_base_uri(&mut self, uri: String) -> core::result::Result<(), ArtZeroError> {
+            self.ensure_role(Role::ConfigManager)
+                .map_err(|_| ArtZeroError::Custom("Not Authorised".to_string()))?;
+
+            if uri.is_empty() {
+                return Err(ArtZeroError::Custom("Zero length string".to_string()));
+            }
+            self.base_uri = uri;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn get_attribute_count(&self) -> u32 {
+            4
+        }
+
+        #[ink(message)]
+        fn get_attribute_name(&self, index: u32) -> String {
+            let attr = match index {
+                0 => "TLD",
+                1 => "Length",
+                2 => "Registration",
+                3 => "Expiration",
+                _ => "",
+            };
+            attr.into()
+        }
+
+        #[ink(message)]
+        fn get_attributes(&self, token_id: Id, attributes: Vec<String>) -> Vec<String> {
+            let name: String = match token_id
+                .try_into()
+                .map_err(|_| ArtZeroError::Custom("TokenNotFound".to_string()))
+            {
+                Ok(name) => name,
+                _ => return Default::default(),
+            };
+
+            attributes
+                .into_iter()
+                .map(|key| {
+                    self.get_static_attribute_ref(&name, &key)
+                        .unwrap_or_default()
+                })
+                .collect()
+        }
+
+        #[ink(message)]
+        fn set_multiple_attributes(
+            &mut self,
+            _token_id: Id,
+            _metadata: Vec<(String, String)>,
+        ) -> core::result::Result<(), ArtZeroError> {
+            Err(ArtZeroError::Custom("Not Supported".to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::azns_registry::*;
+    use crate::address_dict::AddressDict;
+    use crate::auction::Auction;
+    use crate::launch_phase::{EntryPoint, LaunchPhase, PhaseConfig};
+    use crate::lease::Lease;
+    use crate::listing::Listing;
+    use crate::meta_tx::MetaAction;
+    use crate::mock_psp22;
+    use crate::offer::Offer;
+    use crate::refund_policy::RefundPolicy;
+    use crate::voucher::Voucher;
+    use crate::whitelist::WhitelistQuota;
+    use ink::codegen::Env;
+    use ink::env::test::*;
+    use ink::env::DefaultEnvironment;
+    use ink::prelude::string::{String, ToString};
+    use ink::prelude::vec::Vec;
+    use ink::primitives::AccountId;
+    use interfaces::access_control::Role;
+    use interfaces::psp22::PSP22Error;
+    use interfaces::psp34_standard::*;
+
+    type Balance = u128;
+
+    fn default_accounts() -> DefaultAccounts<DefaultEnvironment> {
+        ink::env::test::default_accounts::<DefaultEnvironment>()
+    }
+
+    fn set_next_caller(caller: AccountId) {
+        set_caller::<DefaultEnvironment>(caller);
+    }
+
+    fn get_test_name_service() -> Registry {
+        let contract_addr: AccountId = AccountId::from([0xFF_u8; 32]);
+        set_callee::<DefaultEnvironment>(contract_addr);
+        Registry::new(
+            default_accounts().alice,
+            None,
+            None,
+            "azero".to_string(),
+            "ipfs://05121999/".to_string(),
+        )
+    }
+
+    #[ink::test]
+    fn owner_to_names_works() {
+        let default_accounts = default_accounts();
+        let name = String::from("test");
+        let name2 = String::from("foo");
+        let name3 = String::from("bar");
+
+        set_next_caller(default_accounts.alice);
+        let mut contract = get_test_name_service();
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name.clone(), 1, None, None, false),
+            Ok(())
+        );
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name2.clone(), 1, None, None, false),
+            Ok(())
+        );
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name3.clone(), 1, None, None, false),
+            Ok(())
+        );
+
+        /* Now alice owns three names */
+        /* getting all owned names should return all three */
+        assert_eq!(
+            contract.get_owned_names_of_address(default_accounts.alice),
+            vec![name, name2, name3]
+        );
+    }
+
+    #[ink::test]
+    fn controller_to_names_works() {
+        let default_accounts = default_accounts();
+        let name = String::from("test");
+        let name2 = String::from("foo");
+        let name3 = String::from("bar");
+
+        set_next_caller(default_accounts.alice);
+        let mut contract = get_test_name_service();
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name.clone(), 1, None, None, false),
+            Ok(())
+        );
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name2.clone(), 1, None, None, false),
+            Ok(())
+        );
+
+        /* Register bar under bob, but set controller to alice */
+        set_next_caller(default_accounts.bob);
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name3.clone(), 1, None, None, false),
+            Ok(())
+        );
+        assert_eq!(
+            contract.set_controller(name3.clone(), default_accounts.alice),
+            Ok(())
+        );
+
+        /* Now alice owns three names */
+        /* getting all owned names should return all three */
+        assert_eq!(
+            contract.get_controlled_names_of_address(default_accounts.alice),
+            vec![name, name2, name3]
+        );
+    }
+
+    #[ink::test]
+    fn get_names_of_address_works() {
+        let default_accounts = default_accounts();
+        let name = String::from("test");
+        let name2 = String::from("foo");
+        let name3 = String::from("bar");
+
+        set_next_caller(default_accounts.alice);
+        let mut contract = get_test_name_service();
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name.clone(), 1, None, None, false),
+            Ok(())
+        );
+
+        set_next_caller(default_accounts.charlie);
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name2.clone(), 1, None, None, false),
+            Ok(())
+        );
+
+        /* getting all names should return first only */
+        assert_eq!(
+            contract.get_names_of_address(default_accounts.alice),
+            vec![name.clone()]
+        );
+
+        /* Register bar under bob, but set resolved address to alice */
+        set_next_caller(default_accounts.bob);
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name3.clone(), 1, None, None, false),
+            Ok(())
+        );
+        assert_eq!(
+            contract.set_address(name3.clone(), default_accounts.alice),
+            Ok(())
+        );
+
+        /* getting all names should return all three */
+        assert_eq!(
+            contract.get_names_of_address(default_accounts.alice),
+            vec![name3.clone(), name.clone()]
+        );
+
+        set_next_caller(default_accounts.charlie);
+        assert_eq!(
+            contract.set_controller(name2.clone(), default_accounts.alice),
+            Ok(())
+        );
+
+        /* getting all names should return all three */
+        assert_eq!(
+            contract.get_names_of_address(default_accounts.alice),
+            vec![name3, name2, name]
+        );
+    }
+
+    #[ink::test]
+    fn resolving_to_names_works() {
+        let default_accounts = default_accounts();
+        let name = String::from("test");
+        let name2 = String::from("foo");
+        let name3 = String::from("bar");
+
+        set_next_caller(default_accounts.alice);
+        let mut contract = get_test_name_service();
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name.clone(), 1, None, None, false),
+            Ok(())
+        );
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name2.clone(), 1, None, None, false),
+            Ok(())
+        );
+
+        /* getting all names should return first two */
+        assert_eq!(
+            contract.get_resolving_names_of_address(default_accounts.alice),
+            vec![name.clone(), name2.clone()]
+        );
+
+        /* Register bar under bob, but set resolved address to alice */
+        set_next_caller(default_accounts.bob);
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name3.clone(), 1, None, None, false),
+            Ok(())
+        );
+        assert_eq!(
+            contract.set_address(name3.clone(), default_accounts.alice),
+            Ok(())
+        );
+
+        /* Now all three names resolve to alice's address */
+        /* getting all resolving names should return all three names */
+        assert_eq!(
+            contract.get_resolving_names_of_address(default_accounts.alice),
+            vec![name.clone(), name2.clone(), name3.clone()]
+        );
+
+        /* Remove the pointer to alice */
+        assert_eq!(contract.set_address(name3, default_accounts.bob), Ok(()));
+
+        /* getting all resolving names should return first two names */
+        assert_eq!(
+            contract.get_resolving_names_of_address(default_accounts.alice),
+            vec![name, name2]
+        );
+    }
+
+    #[ink::test]
+    fn set_primary_name_works() {
+        let default_accounts = default_accounts();
+        let name = String::from("test");
+        let name2 = String::from("foo");
+        let name3 = String::from("bar");
+
+        set_next_caller(default_accounts.alice);
+        let mut contract = get_test_name_service();
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name.clone(), 1, None, None, false),
+            Ok(())
+        );
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(contract.register(name2, 1, None, None, false), Ok(()));
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(contract.register(name3, 1, None, None, false), Ok(()));
+
+        /* Now alice owns three names */
+        /* Set the primary name for alice's address to name 1 */
+        contract.set_primary_name(Some(name.clone())).unwrap();
+
+        /* Now the primary name should resolve to alice's address */
+        assert_eq!(
+            contract.get_primary_name(default_accounts.alice),
+            Ok(name.clone())
+        );
+
+        /* Change the resolved address of the first name to bob, invalidating the primary name claim */
+        contract
+            .set_address(name.clone(), default_accounts.bob)
+            .unwrap();
+
+        /* Now the primary name should not resolve to anything */
+        assert_eq!(
+            contract.get_primary_name(default_accounts.alice),
+            Err(Error::NoResolvedAddress)
+        );
+
+        /* Set bob's primary name */
+        set_next_caller(default_accounts.bob);
+        contract.set_primary_name(Some(name.clone())).unwrap();
+
+        /* Now the primary name should not resolve to anything */
+        assert_eq!(contract.get_primary_name(default_accounts.bob), Ok(name));
+    }
+
+    #[ink::test]
+    fn register_works() {
+        let default_accounts = default_accounts();
+        let name = String::from("test");
+
+        set_next_caller(default_accounts.alice);
+        let mut contract = get_test_name_service();
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name.clone(), 1, None, None, false),
+            Ok(())
+        );
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.get_owned_names_of_address(default_accounts.alice),
+            Vec::from([name.clone()])
+        );
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name, 1, None, None, false),
+            Err(Error::NameAlreadyExists)
+        );
+
+        // Reserved names cannot be registered
+        let reserved_name = String::from("AlephZero");
+        let reserved_list = vec![(reserved_name.clone(), Some(default_accounts.alice))];
+        contract
+            .add_reserved_names(reserved_list, false)
+            .expect("Failed to reserve name");
+
+        assert_eq!(
+            contract.register(reserved_name, 1, None, None, false),
+            Err(Error::CannotBuyReservedName)
+        );
+    }
+
+    #[ink::test]
+    fn register_with_set_primary_name_works() {
+        let default_accounts = default_accounts();
+        let name = String::from("test");
+
+        set_next_caller(default_accounts.alice);
+        let mut contract = get_test_name_service();
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(contract.register(name.clone(), 1, None, None, true), Ok(()));
+
+        assert_eq!(contract.get_primary_name(default_accounts.alice), Ok(name));
+    }
+
+    #[ink::test]
+    fn register_excess_fee_works() {
+        let default_accounts = default_accounts();
+        let name = String::from("test");
+
+        set_next_caller(default_accounts.alice);
+        let mut contract = get_test_name_service();
+        let contract_addr = contract.env().account_id();
+
+        set_account_balance::<DefaultEnvironment>(default_accounts.alice, 2000);
+        transfer_in::<DefaultEnvironment>(1234);
+        assert_eq!(contract.register(name.clone(), 1, None, None, true), Ok(()));
+
+        assert_eq!(
+            get_account_balance::<DefaultEnvironment>(default_accounts.alice),
+            Ok(1000)
+        );
+
+        assert_eq!(
+            get_account_balance::<DefaultEnvironment>(contract_addr),
+            Ok(1000)
+        );
+    }
+
+    #[ink::test]
+    fn renew_works() {
+        let default_accounts = default_accounts();
+        let name = String::from("test");
+
+        set_next_caller(default_accounts.alice);
+        let mut contract = get_test_name_service();
+
+        // Try renewing a non-existant name
+        assert_eq!(
+            contract.renew(name.clone(), 2, None),
+            Err(Error::NameDoesntExist)
+        );
+
+        let origin_time = GRACE_TIMESTAMP;
+        set_block_timestamp::<DefaultEnvironment>(origin_time);
+
+        // Register a name
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name.clone(), 1, None, None, false),
+            Ok(())
+        );
+
+        // Get current expiry
+        assert_eq!(
+            contract.get_registration_period(name.clone()),
+            Ok((origin_time, origin_time + 60))
+        );
+
+        // Renew the name
+        assert_eq!(contract.renew(name.clone(), 2, None), Ok(1000));
+
+        // Get new expiry
+        assert_eq!(
+            contract.get_registration_period(name.clone()),
+            Ok((origin_time, origin_time + 180))
+        );
+    }
+
+    #[ink::test]
+    fn renew_excess_fee_works() {
+        let default_accounts = default_accounts();
+        let name = String::from("test");
+
+        set_next_caller(default_accounts.alice);
+        let mut contract = get_test_name_service();
+        let contract_addr = contract.env().account_id();
+
+        set_account_balance::<DefaultEnvironment>(default_accounts.alice, 3000);
+
+        transfer_in::<DefaultEnvironment>(1000);
+        assert_eq!(contract.register(name.clone(), 1, None, None, true), Ok(()));
+        assert_eq!(
+            get_account_balance::<DefaultEnvironment>(default_accounts.alice),
+            Ok(2000)
+        );
+
+        // Transfer extra fee
+        transfer_in::<DefaultEnvironment>(1234);
+        assert_eq!(contract.renew(name.clone(), 1, None), Ok(1000));
+
+        assert_eq!(
+            get_account_balance::<DefaultEnvironment>(default_accounts.alice),
+            Ok(1000)
+        );
+
+        assert_eq!(
+            get_account_balance::<DefaultEnvironment>(contract_addr),
+            Ok(2000)
+        );
+    }
+
+    #[ink::test]
+    fn withdraw_works() {
+        let default_accounts = default_accounts();
+        let name = String::from("test");
+
+        // Alice deploys the contract
+        set_next_caller(default_accounts.alice);
+        let mut contract = get_test_name_service();
+
+        // Bob registers
+        let fees = 1000;
+        set_next_caller(default_accounts.bob);
+        set_account_balance::<DefaultEnvironment>(default_accounts.bob, fees);
+        transfer_in::<DefaultEnvironment>(fees);
+        assert_eq!(contract.register(name, 1, None, None, false), Ok(()));
+
+        // Alice (admin) withdraws the funds
+        set_next_caller(default_accounts.alice);
+
+        let balance_before =
+            get_account_balance::<DefaultEnvironment>(default_accounts.alice).unwrap();
+        assert_eq!(contract.withdraw(None, Some(fees)), Ok(()));
+        let balance_after =
+            get_account_balance::<DefaultEnvironment>(default_accounts.alice).unwrap();
+
+        assert_eq!(balance_after, balance_before + fees);
+    }
+
+    #[ink::test]
+    fn withdraw_only_owner() {
+        let default_accounts = default_accounts();
+        let name = String::from("test");
+
+        set_next_caller(default_accounts.alice);
+        let mut contract = get_test_name_service();
+
+        let _acc_balance_before_transfer: Balance =
+            get_account_balance::<DefaultEnvironment>(default_accounts.alice).unwrap();
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(contract.register(name, 1, None, None, false), Ok(()));
+
+        set_next_caller(default_accounts.bob);
+        assert_eq!(
+            contract.withdraw(None, None),
+            Err(Error::MissingRole(Role::Treasurer))
+        );
+    }
+
+    #[ink::test]
+    fn reverse_search_works() {
+        let default_accounts = default_accounts();
+        let name = String::from("test");
+        let name2 = String::from("test2");
+
+        set_next_caller(default_accounts.alice);
+        let mut contract = get_test_name_service();
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(contract.register(name, 1, None, None, false), Ok(()));
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(contract.register(name2, 1, None, None, false), Ok(()));
+        assert!(contract
+            .get_owned_names_of_address(default_accounts.alice)
+            .contains(&String::from("test")));
+        assert!(contract
+            .get_owned_names_of_address(default_accounts.alice)
+            .contains(&String::from("test2")));
+    }
+
+    #[ink::test]
+    fn register_empty_reverts() {
+        let default_accounts = default_accounts();
+        let name = String::from("");
+
+        set_next_caller(default_accounts.alice);
+        let mut contract = get_test_name_service();
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name, 1, None, None, false),
+            Err(Error::NameNotAllowed)
+        );
+    }
+
+    // TODO: enable this test once we get cross-contract testing working
+    // #[ink::test]
+    // fn register_disallowed_reverts() {
+    //     let default_accounts = default_accounts();
+    //     let name = String::from("ýáěšžčřýáěščžá");
+    //
+    //     set_next_caller(default_accounts.alice);
+    //     let mut contract = get_test_name_service();
+    //
+    //     set_value_transferred::<DefaultEnvironment>(160_u128 * 10_u128.pow(12));
+    //     assert_eq!(contract.register(name, None), Err(NameNotAllowed, false));
+    // }
+
+    #[ink::test]
+    fn register_with_fee_works() {
+        let default_accounts = default_accounts();
+        let name = String::from("test");
+
+        set_next_caller(default_accounts.alice);
+        let mut contract = get_test_name_service();
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name.clone(), 1, None, None, false),
+            Ok(())
+        );
+        assert_eq!(
+            contract.register(name, 1, None, None, false),
+            Err(Error::NameAlreadyExists)
+        );
+    }
+
+    #[ink::test]
+    fn register_without_fee_reverts() {
+        let default_accounts = default_accounts();
+        let name = String::from("test");
+
+        set_next_caller(default_accounts.alice);
+        let mut contract = get_test_name_service();
+
+        assert_eq!(
+            contract.register(name, 1, None, None, false),
+            Err(Error::FeeNotPaid)
+        );
+    }
+
+    #[ink::test]
+    fn release_works() {
+        let default_accounts = default_accounts();
+        let name = String::from("test");
+
+        set_next_caller(default_accounts.alice);
+        let mut contract = get_test_name_service();
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name.clone(), 1, None, None, false),
+            Ok(())
+        );
+        assert_eq!(
+            contract.set_address(name.clone(), default_accounts.alice),
+            Ok(())
+        );
+        assert_eq!(contract.get_owner(name.clone()), Ok(default_accounts.alice));
+        assert_eq!(
+            contract.get_address(name.clone()),
+            Ok(default_accounts.alice)
+        );
+
+        assert_eq!(
+            contract.get_owned_names_of_address(default_accounts.alice),
+            Vec::from([name.clone()])
+        );
+        assert_eq!(
+            contract.get_controlled_names_of_address(default_accounts.alice),
+            Vec::from([name.clone()])
+        );
+        assert_eq!(
+            contract.get_resolving_names_of_address(default_accounts.alice),
+            Vec::from([name.clone()])
+        );
+
+        assert_eq!(contract.release(name.clone()), Ok(()));
+        assert_eq!(
+            contract.get_owner(name.clone()),
+            Err(Error::NameDoesntExist)
+        );
+        assert_eq!(
+            contract.get_address(name.clone()),
+            Err(Error::NameDoesntExist)
+        );
+
+        assert_eq!(
+            contract.get_owned_names_of_address(default_accounts.alice),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            contract.get_controlled_names_of_address(default_accounts.alice),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            contract.get_resolving_names_of_address(default_accounts.alice),
+            Vec::<String>::new()
+        );
+
+        /* Another account can register again*/
+        set_next_caller(default_accounts.bob);
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name.clone(), 1, None, None, false),
+            Ok(())
+        );
+        assert_eq!(
+            contract.set_address(name.clone(), default_accounts.bob),
+            Ok(())
+        );
+        assert_eq!(contract.get_owner(name.clone()), Ok(default_accounts.bob));
+        assert_eq!(contract.get_address(name.clone()), Ok(default_accounts.bob));
+        assert_eq!(contract.release(name.clone()), Ok(()));
+        assert_eq!(
+            contract.get_owner(name.clone()),
+            Err(Error::NameDoesntExist)
+        );
+        assert_eq!(contract.get_address(name), Err(Error::NameDoesntExist));
+    }
+
+    #[ink::test]
+    fn controller_separation_works() {
+        let accounts = default_accounts();
+        let name = String::from("test");
+
+        set_next_caller(accounts.alice);
+
+        let mut contract = get_test_name_service();
+        set_value_transferred::<DefaultEnvironment>(1000);
+        contract
+            .register(name.clone(), 1, None, None, false)
+            .unwrap();
+
+        // Caller is not controller, `set_address` should fail.
+        set_next_caller(accounts.bob);
+        assert_eq!(
+            contract.set_address(name.clone(), accounts.bob),
+            Err(Error::CallerIsNotController)
+        );
+
+        /* Caller is not controller, `update_records` should fail */
+        set_next_caller(accounts.bob);
+        assert_eq!(
+            contract.update_records(
+                name.clone(),
+                Vec::from([("twitter".to_string(), None)]),
+                false,
+            ),
+            Err(Error::CallerIsNotController)
+        );
+
+        // Caller is controller, `update_records` should pass
+        set_next_caller(accounts.alice);
+        assert_eq!(
+            contract.update_records(name, Vec::from([("twitter".to_string(), None)]), false),
+            Ok(())
+        );
+    }
+
+    #[ink::test]
+    fn set_address_works() {
+        let accounts = default_accounts();
+        let name = String::from("test");
+
+        set_next_caller(accounts.alice);
+
+        let mut contract = get_test_name_service();
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name.clone(), 1, None, None, false),
+            Ok(())
+        );
+
+        // Caller is not controller, `set_address` should fail.
+        set_next_caller(accounts.bob);
+        assert_eq!(
+            contract.set_address(name.clone(), accounts.bob),
+            Err(Error::CallerIsNotController)
+        );
+
+        // Caller is controller, set_address will be successful
+        set_next_caller(accounts.alice);
+        assert_eq!(contract.set_address(name.clone(), accounts.bob), Ok(()));
+        assert_eq!(contract.get_address(name), Ok(accounts.bob));
+    }
+
+    #[ink::test]
+    fn transfer_works() {
+        let accounts = default_accounts();
+        let name = String::from("test");
+
+        set_next_caller(accounts.alice);
+
+        let mut contract = get_test_name_service();
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name.clone(), 1, None, None, false),
+            Ok(())
+        );
+
+        // Test transfer of owner.
+        assert_eq!(
+            contract.transfer(accounts.bob, name.clone(), false, false, false, vec![]),
+            Ok(())
+        );
+
+        assert_eq!(
+            contract.get_owned_names_of_address(accounts.alice),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            contract.get_owned_names_of_address(accounts.bob),
+            Vec::from([name.clone()])
+        );
+
+        // Alice is not the controller anymore
+        assert_eq!(
+            contract.set_controller(name.clone(), accounts.bob),
+            Err(Error::CallerIsNotController)
+        );
+
+        // Controller is bob, alice `set_address` should fail.
+        assert_eq!(
+            contract.set_address(name.clone(), accounts.bob),
+            Err(Error::CallerIsNotController)
+        );
+
+        set_next_caller(accounts.bob);
+        // Now owner is bob, `set_address` should be successful.
+        assert_eq!(contract.set_address(name.clone(), accounts.eve), Ok(()));
+        assert_eq!(contract.get_address(name), Ok(accounts.eve));
+    }
+
+    #[ink::test]
+    fn lock_nft_works() {
+        // Setup
+        let accounts = default_accounts();
+        let name = String::from("alice");
+        let mut contract = get_test_name_service();
+
+        set_next_caller(accounts.alice);
+        set_value_transferred::<DefaultEnvironment>(1000);
+        contract
+            .register(name.clone(), 1, None, None, false)
+            .unwrap();
+
+        let id: Id = name.clone().into();
+        contract
+            .approve(accounts.bob, Some(id.clone()), true)
+            .unwrap();
+
+        // Lock
+        let lock_msg = "azero.id-lock".as_bytes().to_vec();
+        set_next_caller(accounts.bob);
+        assert_eq!(
+            PSP34::transfer(&mut contract, accounts.bob, id.clone(), lock_msg),
+            Ok(())
+        );
+
+        // Verify address-dict didn't changed
+        let address_dict = contract.get_address_dict(name.clone());
+        assert_eq!(
+            address_dict,
+            Ok(AddressDict {
+                owner: accounts.alice,
+                controller: accounts.alice,
+                resolved: accounts.alice
+            })
+        );
+
+        // Verify lock is assigned
+        assert_eq!(contract.get_lock_info(name.clone()), Some(accounts.bob));
+        assert_eq!(
+            contract.get_name_status(vec![name.clone()]),
+            vec![NameStatus::Registered(
+                address_dict.unwrap(),
+                Some(accounts.bob)
+            )]
+        );
+
+        // Verify others cannot transfer it
+        set_next_caller(accounts.alice);
+        assert_eq!(
+            PSP34::transfer(&mut contract, accounts.charlie, id.clone(), vec![]),
+            Err(PSP34Error::Custom("Name is locked".to_string()))
+        )
+    }
+
+    #[ink::test]
+    fn lock_and_release_works() {
+        // Setup
+        let accounts = default_accounts();
+        let name = String::from("alice");
+        let mut contract = get_test_name_service();
+
+        set_next_caller(accounts.alice);
+        set_value_transferred::<DefaultEnvironment>(1000);
+        contract
+            .register(name.clone(), 1, None, None, false)
+            .unwrap();
+
+        let id: Id = name.clone().into();
+        contract
+            .approve(accounts.bob, Some(id.clone()), true)
+            .unwrap();
+
+        // Lock
+        let lock_msg = "azero.id-lock".as_bytes().to_vec();
+        set_next_caller(accounts.bob);
+        PSP34::transfer(&mut contract, accounts.bob, id.clone(), lock_msg).unwrap();
+
+        // Update resolver while in lock
+        set_next_caller(accounts.alice);
+        contract.set_address(name.clone(), accounts.eve).unwrap();
+
+        // Unlock
+        set_next_caller(accounts.bob);
+        assert_eq!(
+            PSP34::transfer(&mut contract, accounts.alice, id.clone(), vec![]),
+            Ok(())
+        );
+
+        // Verify
+        assert_eq!(contract.get_lock_info(name.clone()), None);
+        assert_eq!(
+            contract.get_address_dict(name.clone()),
+            Ok(AddressDict {
+                owner: accounts.alice,
+                controller: accounts.alice,
+                resolved: accounts.eve
+            })
+        );
+    }
+
+    #[ink::test]
+    fn lock_and_transfer_works() {
+        // Setup
+        let accounts = default_accounts();
+        let name = String::from("alice");
+        let mut contract = get_test_name_service();
+
+        set_next_caller(accounts.alice);
+        set_value_transferred::<DefaultEnvironment>(1000);
+        contract
+            .register(name.clone(), 1, None, None, false)
+            .unwrap();
+
+        let id: Id = name.clone().into();
+        contract
+            .approve(accounts.bob, Some(id.clone()), true)
+            .unwrap();
+
+        // Lock
+        let lock_msg = "azero.id-lock".as_bytes().to_vec();
+        set_next_caller(accounts.bob);
+        PSP34::transfer(&mut contract, accounts.bob, id.clone(), lock_msg).unwrap();
+
+        // Unlock
+        set_next_caller(accounts.bob);
+        assert_eq!(
+            PSP34::transfer(&mut contract, accounts.charlie, id.clone(), vec![]),
+            Ok(())
+        );
+
+        // Verify
+        assert_eq!(contract.get_lock_info(name.clone()), None);
+        assert_eq!(
+            contract.get_address_dict(name.clone()),
+            Ok(AddressDict {
+                owner: accounts.charlie,
+                controller: accounts.charlie,
+                resolved: accounts.charlie
+            })
+        );
+    }
+
+    #[ink::test]
+    fn records_works() {
+        let accounts = default_accounts();
+        let key = String::from("twitter");
+        let value = String::from("@test");
+        let records = Vec::from([(key.clone(), Some(value.clone()))]);
+
+        let name_name = "test".to_string();
+
+        set_next_caller(accounts.alice);
+        let mut contract = get_test_name_service();
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name_name.clone(), 1, None, None, false),
+            Ok(())
+        );
+
+        assert_eq!(
+            contract.update_records(name_name.clone(), records.clone(), false),
+            Ok(())
+        );
+        assert_eq!(
+            contract.get_record(name_name.clone(), key.clone()).unwrap(),
+            value
+        );
+
+        /* Confirm idempotency */
+        assert_eq!(
+            contract.update_records(name_name.clone(), records, true),
+            Ok(())
+        );
+        assert_eq!(contract.get_record(name_name.clone(), key).unwrap(), value);
+
+        /* Confirm overwriting */
+        assert_eq!(
+            contract.update_records(
+                name_name.clone(),
+                Vec::from([("twitter".to_string(), Some("@newtest".to_string()))]),
+                false,
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            contract.get_all_records(name_name),
+            Vec::from([("twitter".to_string(), "@newtest".to_string())])
+        );
+    }
+
+    #[ink::test]
+    fn set_record_works() {
+        let accounts = default_accounts();
+        let key = String::from("twitter");
+        let value = String::from("@test");
+
+        let name_name = "test".to_string();
+
+        set_next_caller(accounts.alice);
+        let mut contract = get_test_name_service();
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name_name.clone(), 1, None, None, false),
+            Ok(())
+        );
+
+        assert_eq!(
+            contract.update_records(
+                name_name.clone(),
+                vec![(key.clone(), Some(value.clone()))],
+                false,
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            contract.get_record(name_name.clone(), key.clone()).unwrap(),
+            value
+        );
+
+        /* Confirm idempotency */
+        assert_eq!(
+            contract.update_records(
+                name_name.clone(),
+                vec![(key.clone(), Some(value.clone()))],
+                false,
+            ),
+            Ok(())
+        );
+        assert_eq!(contract.get_record(name_name.clone(), key).unwrap(), value);
+
+        /* Confirm overwriting */
+        assert_eq!(
+            contract.update_records(
+                name_name.clone(),
+                vec![("twitter".to_string(), Some("@newtest".to_string()))],
+                false,
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            contract.get_all_records(name_name),
+            Vec::from([("twitter".to_string(), "@newtest".to_string())])
+        );
+    }
+
+    #[ink::test]
+    fn update_records_works() {
+        let name = "test".to_string();
+        let mut contract = get_test_name_service();
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        contract
+            .register(name.clone(), 1, None, None, false)
+            .unwrap();
+
+        // add initial records values
+        assert_eq!(
+            contract.update_records(
+                name.clone(),
+                vec![
+                    ("@facebook".to_string(), Some("alice_zuk".to_string())),
+                    ("@instagram".to_string(), Some("alice_zuk".to_string())),
+                    ("@twitter".to_string(), Some("alice_musk".to_string())),
+                ],
+                true
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            contract.get_all_records(name.clone()),
+            vec![
+                ("@facebook".to_string(), "alice_zuk".to_string()),
+                ("@instagram".to_string(), "alice_zuk".to_string()),
+                ("@twitter".to_string(), "alice_musk".to_string()),
+            ]
+        );
+
+        // add 1 new record
+        // remove 1 existing record
+        // update 1 existing record
+        assert_eq!(
+            contract.update_records(
+                name.clone(),
+                vec![
+                    ("@reddit".to_string(), Some("alice_tut".to_string())),
+                    ("@instagram".to_string(), None),
+                    ("@twitter".to_string(), Some("elon_musk".to_string()))
+                ],
+                false,
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            contract.get_all_records(name.clone()),
+            vec![
+                ("@facebook".to_string(), "alice_zuk".to_string()),
+                ("@reddit".to_string(), "alice_tut".to_string()),
+                ("@twitter".to_string(), "elon_musk".to_string()),
+            ]
+        );
+
+        // add a record with flag: remove_rest
+        assert_eq!(
+            contract.update_records(
+                name.clone(),
+                vec![("@field".to_string(), Some("alice_tut".to_string()))],
+                true,
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            contract.get_all_records(name.clone()),
+            vec![("@field".to_string(), "alice_tut".to_string())],
+        );
+    }
+
+    #[ink::test]
+    fn records_limit_works() {
+        let mut contract = get_test_name_service();
+        let name = "alice".to_string();
+        let records = vec![
+            ("@twitter".to_string(), Some("alice_musk".to_string())),
+            ("@facebook".to_string(), Some("alice_zuk".to_string())),
+            ("@instagram".to_string(), Some("alice_zuk".to_string())),
+        ];
+
+        contract.set_records_size_limit(Some(41)).unwrap();
+        assert_eq!(contract.get_records_size_limit(), Some(41));
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        contract
+            .register(name.clone(), 1, None, None, false)
+            .unwrap();
+
+        // With current input, records cannot be stored simultaneously
+        assert_eq!(
+            contract.update_records(name.clone(), records.clone(), false),
+            Err(Error::RecordsOverflow)
+        );
+
+        // Storing only one works
+        assert_eq!(
+            contract.update_records(name.clone(), records[0..1].to_vec(), true),
+            Ok(())
+        );
+
+        // Adding the second record fails
+        assert_eq!(
+            contract.update_records(name.clone(), records[1..3].to_vec(), false),
+            Err(Error::RecordsOverflow),
+        );
+    }
+
+    #[ink::test]
+    fn add_reserved_names_works() {
+        let accounts = default_accounts();
+        let mut contract = get_test_name_service();
+
+        let reserved_name = String::from("AlephZero");
+        let list = vec![(reserved_name.clone(), Some(accounts.alice))];
+
+        assert!(contract.add_reserved_names(list, false).is_ok());
+
+        assert_eq!(
+            contract.get_name_status(vec![reserved_name]),
+            vec![NameStatus::Reserved(Some(accounts.alice))],
+        );
+
+        // Cannot reserve already registered-name
+        let name = "alice".to_string();
+        set_value_transferred::<DefaultEnvironment>(1000);
+        contract
+            .register(name.clone(), 1, None, None, false)
+            .unwrap();
+        assert_eq!(
+            contract.add_reserved_names(vec![(name, None)], false),
+            Err(Error::NameAlreadyExists)
+        );
+
+        // Invocation from non-admin address fails
+        set_next_caller(accounts.bob);
+        assert_eq!(
+            contract.add_reserved_names(vec![], false),
+            Err(Error::MissingRole(Role::ReservationManager))
+        );
+    }
+
+    #[ink::test]
+    fn remove_reserved_names_works() {
+        let accounts = default_accounts();
+        let mut contract = get_test_name_service();
+
+        let reserved_name = String::from("AlephZero");
+        let list = vec![(reserved_name.clone(), Some(accounts.alice))];
+        assert!(contract.add_reserved_names(list, false).is_ok());
+
+        assert_eq!(
+            contract.get_name_status(vec![reserved_name.clone()]),
+            vec![NameStatus::Reserved(Some(accounts.alice))],
+        );
+
+        assert!(contract
+            .remove_reserved_name(vec![reserved_name.clone()])
+            .is_ok());
+
+        assert_ne!(
+            contract.get_name_status(vec![reserved_name]),
+            vec![NameStatus::Reserved(Some(accounts.alice))],
+        );
+
+        // Invocation from non-admin address fails
+        set_next_caller(accounts.bob);
+        assert_eq!(
+            contract.remove_reserved_name(vec![]),
+            Err(Error::MissingRole(Role::ReservationManager))
+        );
+    }
+
+    #[ink::test]
+    fn claim_reserved_name_works() {
+        let accounts = default_accounts();
+        let mut contract = get_test_name_service();
+
+        let name = String::from("bob");
+        let reserved_list = vec![(name.clone(), Some(accounts.bob))];
+        contract
+            .add_reserved_names(reserved_list, false)
+            .expect("Failed to add reserved name");
+
+        // Non-reserved name cannot be claimed
+        assert_eq!(
+            contract.claim_reserved_name("abcd".to_string(), false),
+            Err(Error::NotReservedName),
+        );
+
+        // Non-authorised user cannot claim reserved name
+        assert_eq!(
+            contract.claim_reserved_name(name.clone(), false),
+            Err(Error::NotAuthorised),
+        );
+
+        // Authorised user can claim name reserved for them
+        set_next_caller(accounts.bob);
+        assert!(contract.claim_reserved_name(name.clone(), false).is_ok());
+
+        let address_dict = AddressDict::new(accounts.bob);
+        assert_eq!(
+            contract.get_name_status(vec![name]),
+            vec![NameStatus::Registered(address_dict, None)],
+        );
+    }
+
+    #[ink::test]
+    fn get_name_status_works() {
+        let accounts = default_accounts();
+        let reserved_list = vec![("bob".to_string(), Some(accounts.bob))];
+
+        let mut contract = Registry::new(
+            default_accounts().alice,
+            None,
+            None,
+            "azero".to_string(),
+            "ipfs://05121999/".to_string(),
+        );
+
+        contract.add_reserved_names(reserved_list, false).unwrap();
+
+        set_value_transferred::<DefaultEnvironment>(1000);
+        contract
+            .register("alice".to_string(), 1, None, None, false)
+            .expect("failed to register name");
+
+        let address_dict = AddressDict::new(accounts.alice);
+        assert_eq!(
+            contract.get_name_status(vec!["alice".to_string()]),
+            vec![NameStatus::Registered(address_dict, None)]
+        );
+
+        assert_eq!(
+            contract.get_name_status(vec!["bob".to_string()]),
+            vec![NameStatus::Reserved(Some(accounts.bob))]
+        );
+
+        assert_eq!(
+            contract.get_name_status(vec!["david".to_string()]),
+            vec![NameStatus::Available]
+        );
+
+        assert_eq!(
+            contract.get_name_status(vec!["".to_string()]),
+            vec![NameStatus::Unavailable]
+        );
+    }
+
+    #[ink::test]
+    fn batch_get_name_price_works() {
+        let contract = get_test_name_service();
+        let inp = vec![
+            ("alice".to_string(), default_accounts().alice, 1, None),
+            ("bob".to_string(), default_accounts().bob, 2, None),
+        ];
+
+        assert_eq!(
+            contract.batch_get_name_price(inp),
+            vec![Ok((1000, 0, 0, None)), Ok((1000, 0, 0, None))]
+        )
+    }
+
+    #[ink::test]
+    fn referral_system_works() {
+        let default_accounts = default_accounts();
+        let mut contract = get_test_name_service();
+
+        set_callee::<DefaultEnvironment>(default_accounts.eve);
+        assert_eq!(contract.env().account_id(), default_accounts.eve);
+
+        let alice = "alice".to_string();
+        let bob = "bob".to_string();
+
+        // 1. Invalid referrer name gives no discount
+        let fees = 1000;
+        set_next_caller(default_accounts.alice);
+        set_account_balance::<DefaultEnvironment>(default_accounts.alice, fees);
+        set_callee::<DefaultEnvironment>(contract.env().account_id());
+        transfer_in::<DefaultEnvironment>(fees);
+        assert_eq!(
+            contract.register(alice.clone(), 1, Some(bob.clone()), None, false),
+            Ok(())
+        );
+
+        let alice_balance =
+            get_account_balance::<DefaultEnvironment>(default_accounts.alice).unwrap();
+
+        // Initial Balance(alice): 1000
+        // Fee without discount: 1000
+        assert_eq!(alice_balance, 0);
+
+        // 2. Discount works
+        let discount = 49;
+        set_next_caller(default_accounts.bob);
+        set_account_balance::<DefaultEnvironment>(default_accounts.bob, fees);
+        transfer_in::<DefaultEnvironment>(fees - discount);
+        assert_eq!(contract.register(bob, 1, Some(alice), None, false), Ok(()));
+
+        let alice_balance =
+            get_account_balance::<DefaultEnvironment>(default_accounts.alice).unwrap();
+        let bob_balance = get_account_balance::<DefaultEnvironment>(default_accounts.bob).unwrap();
+
+        // Initial Balance (bob): 1000
+        // Fee after discount: 9951
+        assert_eq!(bob_balance, 49);
+
+        // Affiliation payment to alice is credited, not pushed
+        assert_eq!(alice_balance, 0);
+        assert_eq!(
+            contract.get_referral_rewards(default_accounts.alice, None),
+            49
+        );
+
+        set_next_caller(default_accounts.alice);
+        assert_eq!(contract.claim_referral_rewards(None), Ok(49));
+        let alice_balance =
+            get_account_balance::<DefaultEnvironment>(default_accounts.alice).unwrap();
+        assert_eq!(alice_balance, 49);
+    }
+
+    #[ink::test]
+    fn self_referral_not_allowed() {
+        let default_accounts = default_accounts();
+        let mut contract = get_test_name_service();
+
+        set_callee::<DefaultEnvironment>(default_accounts.eve);
+        assert_eq!(contract.env().account_id(), default_accounts.eve);
+
+        let alice = "alice".to_string();
+        let wonderland = "wonderland".to_string();
+
+        // 1. Register first name without referrer
+        let fees = 1000;
+        set_next_caller(default_accounts.alice);
+        set_account_balance::<DefaultEnvironment>(default_accounts.alice, fees);
+        set_callee::<DefaultEnvironment>(contract.env().account_id());
+        transfer_in::<DefaultEnvironment>(fees);
+        assert_eq!(
+            contract.register(alice.clone(), 1, None, None, false),
+            Ok(())
+        );
+
+        // 2. Self-referral doesn't work
+        set_account_balance::<DefaultEnvironment>(default_accounts.alice, fees);
+        transfer_in::<DefaultEnvironment>(fees);
+        assert_eq!(
+            contract.register(wonderland, 1, Some(alice), None, false),
+            Ok(())
+        );
+
+        let alice_balance =
+            get_account_balance::<DefaultEnvironment>(default_accounts.alice).unwrap();
+
+        // No bonus received by alice
+        assert_eq!(alice_balance, 0);
+    }
+
+    #[ink::test]
+    fn validate_referrer_works() {
+        let default_accounts = default_accounts();
+        let mut contract = get_test_name_service();
+
+        let name = "alice".to_string();
+
+        // Invalid name -> fails
+        assert!(!contract.validate_referrer(default_accounts.alice, name.clone()));
+
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register(name.clone(), 1, None, None, false)
+            .unwrap();
+        contract
+            .set_controller(name.clone(), default_accounts.bob)
+            .unwrap();
+        contract
+            .set_address(name.clone(), default_accounts.eve)
+            .unwrap();
+
+        // owner: fails
+        assert!(!contract.validate_referrer(default_accounts.alice, name.clone()));
+
+        // controller: fails
+        assert!(!contract.validate_referrer(default_accounts.bob, name.clone()));
+
+        // resolved: fails
+        assert!(!contract.validate_referrer(default_accounts.eve, name.clone()));
+
+        // A new user: pass
+        assert!(contract.validate_referrer(default_accounts.charlie, name.clone()));
+    }
+
+    #[ink::test]
+    fn name_expiry_works() {
+        let mut contract = get_test_name_service();
+
+        let name1 = "one-year".to_string();
+        let name2 = "two-year".to_string();
+
+        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP - YEAR);
+
+        // Register name1 for one year
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register(name1.clone(), 1, None, None, true)
+            .unwrap();
 
-#[cfg(test)]
-mod tests {
-    use super::azns_registry::*;
-    use crate::address_dict::AddressDict;
-    use ink::codegen::Env;
-    use ink::env::test::*;
-    use ink::env::DefaultEnvironment;
-    use ink::prelude::string::{String, ToString};
-    use ink::prelude::vec::Vec;
-    use ink::primitives::AccountId;
-    use interfaces::psp34_standard::*;
+        // Register name2 for two years
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register(name2.clone(), 2, None, None, false)
+            .unwrap();
 
-    type Balance = u128;
+        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP);
 
-    fn default_accounts() -> DefaultAccounts<DefaultEnvironment> {
-        ink::env::test::default_accounts::<DefaultEnvironment>()
-    }
+        let address_dict = AddressDict::new(default_accounts().alice);
+        assert_eq!(
+            contract.get_name_status(vec![name1.clone(), name2.clone()]),
+            vec![
+                NameStatus::Available,
+                NameStatus::Registered(address_dict, None)
+            ]
+        );
 
-    fn set_next_caller(caller: AccountId) {
-        set_caller::<DefaultEnvironment>(caller);
-    }
+        assert_eq!(
+            contract.get_primary_name(default_accounts().alice),
+            Err(Error::NoResolvedAddress)
+        );
 
-    fn get_test_name_service() -> Registry {
-        let contract_addr: AccountId = AccountId::from([0xFF as u8; 32]);
-        set_callee::<DefaultEnvironment>(contract_addr);
-        Registry::new(
-            default_accounts().alice,
-            None,
-            None,
-            "azero".to_string(),
-            "ipfs://05121999/".to_string(),
-        )
+        assert_eq!(contract.get_all_records(name1.clone()), vec![]);
+
+        // Reverse mapping implicitly excludes expired names
+        assert_eq!(
+            contract.get_names_of_address(default_accounts().alice),
+            vec![name2.clone()]
+        );
     }
 
     #[ink::test]
-    fn owner_to_names_works() {
-        let default_accounts = default_accounts();
-        let name = String::from("test");
-        let name2 = String::from("foo");
-        let name3 = String::from("bar");
-
-        set_next_caller(default_accounts.alice);
+    fn clear_expired_names_works() {
         let mut contract = get_test_name_service();
 
-        set_value_transferred::<DefaultEnvironment>(1000);
+        let name1 = "one-year".to_string();
+        let name2 = "two-year".to_string();
+
+        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP - YEAR);
+
+        // Register name1 for one year
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register(name1.clone(), 1, None, None, true)
+            .unwrap();
+
+        // Register name2 for two years
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register(name2.clone(), 2, None, None, false)
+            .unwrap();
+
+        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP);
+
+        // Only the expired names are cleared
         assert_eq!(
-            contract.register(name.clone(), 1, None, None, false),
-            Ok(())
+            contract.clear_expired_names(vec![name1.clone(), name2.clone()]),
+            Ok(1)
         );
 
-        set_value_transferred::<DefaultEnvironment>(1000);
+        let address_dict = AddressDict::new(default_accounts().alice);
         assert_eq!(
-            contract.register(name2.clone(), 1, None, None, false),
-            Ok(())
+            contract.get_name_status(vec![name1.clone(), name2.clone()]),
+            vec![
+                NameStatus::Available,
+                NameStatus::Registered(address_dict, None)
+            ]
         );
+    }
 
-        set_value_transferred::<DefaultEnvironment>(1000);
+    #[ink::test]
+    fn register_expired_names_works() {
+        let mut contract = get_test_name_service();
+
+        let name1 = "one-year".to_string();
+        let name2 = "two-year".to_string();
+
+        // Register name1 for one year
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register(name1.clone(), 1, None, None, true)
+            .unwrap();
+
+        // Register name2 for two years
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register(name2.clone(), 2, None, None, false)
+            .unwrap();
+
+        // Registering an active name causes error
+        set_next_caller(default_accounts().bob);
         assert_eq!(
-            contract.register(name3.clone(), 1, None, None, false),
-            Ok(())
+            contract.register(name1.clone(), 1, None, None, false),
+            Err(Error::NameAlreadyExists)
         );
 
-        /* Now alice owns three names */
-        /* getting all owned names should return all three */
+        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP);
+
+        // Registering an expired name works
         assert_eq!(
-            contract.get_owned_names_of_address(default_accounts.alice),
-            vec![name, name2, name3]
+            contract.register(name1.clone(), 1, None, None, false),
+            Ok(())
         );
     }
 
     #[ink::test]
-    fn controller_to_names_works() {
-        let default_accounts = default_accounts();
-        let name = String::from("test");
-        let name2 = String::from("foo");
-        let name3 = String::from("bar");
+    fn ownable_2_step_works() {
+        let accounts = default_accounts();
+        let mut contract = get_test_name_service();
 
-        set_next_caller(default_accounts.alice);
+        assert_eq!(contract.get_admin(), accounts.alice);
+        contract.transfer_ownership(Some(accounts.bob)).unwrap();
+
+        assert_eq!(contract.get_admin(), accounts.alice);
+
+        set_caller::<DefaultEnvironment>(accounts.bob);
+        contract.accept_ownership().unwrap();
+        assert_eq!(contract.get_admin(), accounts.bob);
+    }
+
+    #[ink::test]
+    fn grace_period_works() {
         let mut contract = get_test_name_service();
+        let name1 = "one-year".to_string();
 
-        set_value_transferred::<DefaultEnvironment>(1000);
+        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP - YEAR - 10);
+
+        // Register name1 for one year
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register(name1.clone(), 1, None, None, true)
+            .unwrap();
+
+        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP - 1);
+        let address_dict = AddressDict::new(default_accounts().alice);
         assert_eq!(
-            contract.register(name.clone(), 1, None, None, false),
-            Ok(())
+            contract.get_name_status(vec![name1.clone()]),
+            vec![NameStatus::Registered(address_dict, None)]
         );
 
-        set_value_transferred::<DefaultEnvironment>(1000);
+        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP);
         assert_eq!(
-            contract.register(name2.clone(), 1, None, None, false),
-            Ok(())
+            contract.get_name_status(vec![name1.clone()]),
+            vec![NameStatus::Available]
         );
+    }
+
+    #[ink::test]
+    fn redeem_bonus_name_works_with_register_works() {
+        let accounts = default_accounts();
+        let mut contract = get_test_name_service();
+
+        let name1 = "name-1".to_string();
+        let name2 = "name-2".to_string();
+        let bonus_name = "name-3".to_string();
+
+        set_next_caller(default_accounts().alice);
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register_v2(name1.clone(), 1, None, Some(bonus_name.clone()), false)
+            .unwrap();
 
-        /* Register bar under bob, but set controller to alice */
-        set_next_caller(default_accounts.bob);
-        set_value_transferred::<DefaultEnvironment>(1000);
         assert_eq!(
-            contract.register(name3.clone(), 1, None, None, false),
-            Ok(())
+            contract.get_owner(bonus_name.clone()),
+            Err(Error::NameDoesntExist)
         );
+
+        contract
+            .register_v2(name2, 10, None, Some(bonus_name.clone()), false)
+            .unwrap();
+        assert_eq!(contract.get_owner(bonus_name), Ok(accounts.alice))
+    }
+
+    #[ink::test]
+    fn redeem_bonus_name_works_with_renewal_works() {
+        let accounts = default_accounts();
+        let mut contract = get_test_name_service();
+
+        let name1 = "name-1".to_string();
+        let name2 = "name-2".to_string();
+
+        set_next_caller(default_accounts().alice);
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register(name1.clone(), 1, None, None, true)
+            .unwrap();
+
+        contract
+            .renew(name1.clone(), 9, Some(name2.clone()))
+            .unwrap();
         assert_eq!(
-            contract.set_controller(name3.clone(), default_accounts.alice),
-            Ok(())
+            contract.get_owner(name2.clone()),
+            Err(Error::NameDoesntExist)
+        );
+
+        contract.renew(name1, 10, Some(name2.clone())).unwrap();
+        assert_eq!(contract.get_owner(name2), Ok(accounts.alice))
+    }
+
+    #[ink::test]
+    fn redeem_bonus_name_fails_on_invalid_name() {
+        let mut contract = get_test_name_service();
+        let name1 = "name-1".to_string();
+        let name2 = "name-2".to_string();
+
+        set_next_caller(default_accounts().alice);
+        transfer_in::<DefaultEnvironment>(1000);
+
+        // Bonus names not passing the name-checker fails
+        let empty_name = String::new();
+        assert_eq!(
+            contract.register_v2(name1.clone(), 10, None, Some(empty_name.clone()), false),
+            Err(Error::NameNotAllowed)
         );
 
-        /* Now alice owns three names */
-        /* getting all owned names should return all three */
+        // Reserved names cannot be redeemed as bonus
+        let reserved_name = String::from("bobby");
+        let reserved_list = vec![(reserved_name.clone(), None)];
+        contract
+            .add_reserved_names(reserved_list, false)
+            .expect("Failed to add reserved name");
+
         assert_eq!(
-            contract.get_controlled_names_of_address(default_accounts.alice),
-            vec![name, name2, name3]
+            contract.register_v2(name2.clone(), 10, None, Some(reserved_name.clone()), false),
+            Err(Error::CannotBuyReservedName)
         );
     }
 
     #[ink::test]
-    fn get_names_of_address_works() {
-        let default_accounts = default_accounts();
-        let name = String::from("test");
-        let name2 = String::from("foo");
-        let name3 = String::from("bar");
-
-        set_next_caller(default_accounts.alice);
+    fn register_subname_works() {
+        let accounts = default_accounts();
         let mut contract = get_test_name_service();
 
-        set_value_transferred::<DefaultEnvironment>(1000);
-        assert_eq!(
-            contract.register(name.clone(), 1, None, None, false),
-            Ok(())
-        );
+        let parent = "alice".to_string();
+        let subname = "pay.alice".to_string();
 
-        set_next_caller(default_accounts.charlie);
-        set_value_transferred::<DefaultEnvironment>(1000);
+        set_next_caller(accounts.alice);
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register(parent.clone(), 1, None, None, false)
+            .unwrap();
+        let (_, parent_expiry) = contract.get_registration_period(parent.clone()).unwrap();
+
+        // Only the owner/controller of the parent can issue subnames
+        set_next_caller(accounts.bob);
         assert_eq!(
-            contract.register(name2.clone(), 1, None, None, false),
-            Ok(())
+            contract.register_subname(parent.clone(), "pay".to_string(), accounts.bob, None),
+            Err(Error::CallerIsNotController)
         );
 
-        /* getting all names should return first only */
+        // Subname cannot outlive the parent
+        set_next_caller(accounts.alice);
         assert_eq!(
-            contract.get_names_of_address(default_accounts.alice),
-            vec![name.clone()]
+            contract.register_subname(
+                parent.clone(),
+                "pay".to_string(),
+                accounts.bob,
+                Some(parent_expiry + 1)
+            ),
+            Err(Error::ExpiryExceedsParent)
         );
 
-        /* Register bar under bob, but set resolved address to alice */
-        set_next_caller(default_accounts.bob);
-        set_value_transferred::<DefaultEnvironment>(1000);
         assert_eq!(
-            contract.register(name3.clone(), 1, None, None, false),
+            contract.register_subname(parent.clone(), "pay".to_string(), accounts.bob, None),
             Ok(())
         );
         assert_eq!(
-            contract.set_address(name3.clone(), default_accounts.alice),
-            Ok(())
+            contract.register_subname(parent.clone(), "pay".to_string(), accounts.bob, None),
+            Err(Error::NameAlreadyExists)
         );
 
-        /* getting all names should return all three */
+        assert_eq!(contract.get_address(subname.clone()), Ok(accounts.bob));
         assert_eq!(
-            contract.get_names_of_address(default_accounts.alice),
-            vec![name3.clone(), name.clone()]
+            contract
+                .get_registration_period(subname.clone())
+                .map(|x| x.1),
+            Ok(parent_expiry)
+        );
+        assert_eq!(
+            contract.get_name_status(vec![subname.clone()]),
+            vec![NameStatus::Registered(AddressDict::new(accounts.bob), None)]
+        );
+        assert_eq!(
+            contract.get_owned_names_of_address(accounts.bob),
+            vec![subname.clone()]
         );
+        assert_eq!(contract.get_subnames(parent.clone()), vec![subname.clone()]);
 
-        set_next_caller(default_accounts.charlie);
+        // Subnames cannot be renewed directly
+        transfer_in::<DefaultEnvironment>(1000);
         assert_eq!(
-            contract.set_controller(name2.clone(), default_accounts.alice),
-            Ok(())
+            contract.renew(subname.clone(), 1, None),
+            Err(Error::NotSupportedForSubnames)
         );
 
-        /* getting all names should return all three */
+        // Top-level registration of a dotted name fails
         assert_eq!(
-            contract.get_names_of_address(default_accounts.alice),
-            vec![name3, name2, name]
+            contract.register("foo.alice".to_string(), 1, None, None, false),
+            Err(Error::NameNotAllowed)
         );
     }
 
     #[ink::test]
-    fn resolving_to_names_works() {
-        let default_accounts = default_accounts();
-        let name = String::from("test");
-        let name2 = String::from("foo");
-        let name3 = String::from("bar");
-
-        set_next_caller(default_accounts.alice);
+    fn subname_management_works() {
+        let accounts = default_accounts();
         let mut contract = get_test_name_service();
 
-        set_value_transferred::<DefaultEnvironment>(1000);
+        let parent = "alice".to_string();
+        let subname = "pay.alice".to_string();
+
+        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP);
+        set_next_caller(accounts.alice);
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register(parent.clone(), 1, None, None, false)
+            .unwrap();
+        contract
+            .register_subname(parent.clone(), "pay".to_string(), accounts.bob, None)
+            .unwrap();
+
+        // Parent owner can transfer the subname
         assert_eq!(
-            contract.register(name.clone(), 1, None, None, false),
+            contract.transfer(
+                accounts.charlie,
+                subname.clone(),
+                false,
+                false,
+                false,
+                vec![]
+            ),
             Ok(())
         );
+        assert_eq!(contract.get_owner(subname.clone()), Ok(accounts.charlie));
 
-        set_value_transferred::<DefaultEnvironment>(1000);
+        // Parent owner can shorten the expiry
+        let (registration, expiry) = contract.get_registration_period(subname.clone()).unwrap();
         assert_eq!(
-            contract.register(name2.clone(), 1, None, None, false),
+            contract.set_subname_expiry(subname.clone(), expiry - 1),
             Ok(())
         );
-
-        /* getting all names should return first two */
         assert_eq!(
-            contract.get_resolving_names_of_address(default_accounts.alice),
-            vec![name.clone(), name2.clone()]
+            contract.get_registration_period(subname.clone()),
+            Ok((registration, expiry - 1))
         );
 
-        /* Register bar under bob, but set resolved address to alice */
-        set_next_caller(default_accounts.bob);
-        set_value_transferred::<DefaultEnvironment>(1000);
+        // Subname owner cannot revoke it, parent owner can
+        set_next_caller(accounts.charlie);
         assert_eq!(
-            contract.register(name3.clone(), 1, None, None, false),
-            Ok(())
+            contract.revoke_subname(subname.clone()),
+            Err(Error::CallerIsNotController)
         );
+        set_next_caller(accounts.alice);
+        assert_eq!(contract.revoke_subname(subname.clone()), Ok(()));
         assert_eq!(
-            contract.set_address(name3.clone(), default_accounts.alice),
-            Ok(())
+            contract.get_owner(subname.clone()),
+            Err(Error::NameDoesntExist)
         );
-
-        /* Now all three names resolve to alice's address */
-        /* getting all resolving names should return all three names */
+        assert_eq!(contract.get_subnames(parent.clone()), Vec::<String>::new());
         assert_eq!(
-            contract.get_resolving_names_of_address(default_accounts.alice),
-            vec![name.clone(), name2.clone(), name3.clone()]
+            contract.revoke_subname(parent.clone()),
+            Err(Error::NotASubname)
         );
 
-        /* Remove the pointer to alice */
-        assert_eq!(contract.set_address(name3, default_accounts.bob), Ok(()));
-
-        /* getting all resolving names should return first two names */
+        // Releasing the parent removes its subnames as well
+        contract
+            .register_subname(parent.clone(), "pay".to_string(), accounts.bob, None)
+            .unwrap();
+        assert_eq!(contract.total_supply(), 2);
+        assert_eq!(contract.release(parent), Ok(()));
+        assert_eq!(contract.get_owner(subname), Err(Error::NameDoesntExist));
         assert_eq!(
-            contract.get_resolving_names_of_address(default_accounts.alice),
-            vec![name, name2]
+            contract.get_owned_names_of_address(accounts.bob),
+            Vec::<String>::new()
         );
+        assert_eq!(contract.total_supply(), 0);
     }
 
     #[ink::test]
-    fn set_primary_name_works() {
-        let default_accounts = default_accounts();
-        let name = String::from("test");
-        let name2 = String::from("foo");
-        let name3 = String::from("bar");
-
-        set_next_caller(default_accounts.alice);
+    fn subname_management_by_controller_works() {
+        let accounts = default_accounts();
         let mut contract = get_test_name_service();
 
-        set_value_transferred::<DefaultEnvironment>(1000);
-        assert_eq!(
-            contract.register(name.clone(), 1, None, None, false),
-            Ok(())
-        );
-
-        set_value_transferred::<DefaultEnvironment>(1000);
-        assert_eq!(contract.register(name2, 1, None, None, false), Ok(()));
-
-        set_value_transferred::<DefaultEnvironment>(1000);
-        assert_eq!(contract.register(name3, 1, None, None, false), Ok(()));
-
-        /* Now alice owns three names */
-        /* Set the primary name for alice's address to name 1 */
-        contract.set_primary_name(Some(name.clone())).unwrap();
-
-        /* Now the primary name should resolve to alice's address */
-        assert_eq!(
-            contract.get_primary_name(default_accounts.alice),
-            Ok(name.clone())
-        );
+        let parent = "alice".to_string();
+        let subname = "pay.alice".to_string();
 
-        /* Change the resolved address of the first name to bob, invalidating the primary name claim */
+        set_next_caller(accounts.alice);
+        transfer_in::<DefaultEnvironment>(1000);
         contract
-            .set_address(name.clone(), default_accounts.bob)
+            .register(parent.clone(), 1, None, None, false)
+            .unwrap();
+        contract
+            .set_controller(parent.clone(), accounts.bob)
             .unwrap();
 
-        /* Now the primary name should not resolve to anything */
+        // The controller of the parent can issue & revoke subnames
+        set_next_caller(accounts.bob);
         assert_eq!(
-            contract.get_primary_name(default_accounts.alice),
-            Err(Error::NoResolvedAddress)
+            contract.register_subname(parent.clone(), "pay".to_string(), accounts.charlie, None),
+            Ok(())
         );
+        assert_eq!(contract.get_owner(subname.clone()), Ok(accounts.charlie));
+        assert_eq!(contract.get_subnames(parent.clone()), vec![subname.clone()]);
 
-        /* Set bob's primary name */
-        set_next_caller(default_accounts.bob);
-        contract.set_primary_name(Some(name.clone())).unwrap();
-
-        /* Now the primary name should not resolve to anything */
-        assert_eq!(contract.get_primary_name(default_accounts.bob), Ok(name));
+        assert_eq!(contract.revoke_subname(subname.clone()), Ok(()));
+        assert_eq!(
+            contract.get_owner(subname.clone()),
+            Err(Error::NameDoesntExist)
+        );
+        assert_eq!(contract.get_subnames(parent), Vec::<String>::new());
     }
 
     #[ink::test]
-    fn register_works() {
-        let default_accounts = default_accounts();
-        let name = String::from("test");
+    fn chain_address_works() {
+        use crate::chain_address::*;
 
-        set_next_caller(default_accounts.alice);
+        let accounts = default_accounts();
         let mut contract = get_test_name_service();
+        let name = "alice".to_string();
 
-        set_value_transferred::<DefaultEnvironment>(1000);
+        set_next_caller(accounts.alice);
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register(name.clone(), 1, None, None, false)
+            .unwrap();
+
+        let evm_address = vec![0xAB; 20];
+        let dot_address = vec![0xCD; 32];
+        let moonbeam = EVM_COIN_TYPE_FLAG | 1284;
+
+        // Length of the address is validated against the chain type
         assert_eq!(
-            contract.register(name.clone(), 1, None, None, false),
-            Ok(())
+            contract.set_chain_address(name.clone(), COIN_TYPE_ETH, dot_address.clone()),
+            Err(Error::InvalidChainAddress)
         );
-        set_value_transferred::<DefaultEnvironment>(1000);
         assert_eq!(
-            contract.get_owned_names_of_address(default_accounts.alice),
-            Vec::from([name.clone()])
+            contract.set_chain_address(name.clone(), 1234, evm_address.clone()),
+            Err(Error::UnsupportedCoinType)
         );
-        set_value_transferred::<DefaultEnvironment>(1000);
+
+        // Only the controller can set the addresses
+        set_next_caller(accounts.bob);
         assert_eq!(
-            contract.register(name, 1, None, None, false),
-            Err(Error::NameAlreadyExists)
+            contract.set_chain_address(name.clone(), COIN_TYPE_ETH, evm_address.clone()),
+            Err(Error::CallerIsNotController)
         );
 
-        // Reserved names cannot be registered
-        let reserved_name = String::from("AlephZero");
-        let reserved_list = vec![(reserved_name.clone(), Some(default_accounts.alice))];
+        set_next_caller(accounts.alice);
         contract
-            .add_reserved_names(reserved_list, false)
-            .expect("Failed to reserve name");
+            .set_chain_address(name.clone(), COIN_TYPE_ETH, evm_address.clone())
+            .unwrap();
+        contract
+            .set_chain_address(name.clone(), moonbeam, evm_address.clone())
+            .unwrap();
+        contract
+            .set_chain_address(name.clone(), COIN_TYPE_DOT, dot_address.clone())
+            .unwrap();
 
         assert_eq!(
-            contract.register(reserved_name, 1, None, None, false),
-            Err(Error::CannotBuyReservedName)
+            contract.get_chain_addresses(name.clone(), vec![COIN_TYPE_DOT, COIN_TYPE_BTC]),
+            vec![Some(dot_address.clone()), None]
         );
-    }
-
-    #[ink::test]
-    fn register_with_set_primary_name_works() {
-        let default_accounts = default_accounts();
-        let name = String::from("test");
 
-        set_next_caller(default_accounts.alice);
-        let mut contract = get_test_name_service();
+        // Native address is unaffected
+        assert_eq!(contract.get_address(name.clone()), Ok(accounts.alice));
 
-        set_value_transferred::<DefaultEnvironment>(1000);
-        assert_eq!(contract.register(name.clone(), 1, None, None, true), Ok(()));
+        contract
+            .clear_chain_address(name.clone(), COIN_TYPE_ETH)
+            .unwrap();
+        assert_eq!(
+            contract.get_all_chain_addresses(name.clone()),
+            vec![(moonbeam, evm_address), (COIN_TYPE_DOT, dot_address)]
+        );
 
-        assert_eq!(contract.get_primary_name(default_accounts.alice), Ok(name));
+        // Addresses are cleared upon transfer unless the resolving address is kept
+        contract
+            .transfer(accounts.bob, name.clone(), false, false, false, vec![])
+            .unwrap();
+        assert_eq!(contract.get_all_chain_addresses(name), vec![]);
     }
 
     #[ink::test]
-    fn register_excess_fee_works() {
-        let default_accounts = default_accounts();
-        let name = String::from("test");
-
-        set_next_caller(default_accounts.alice);
+    fn commit_reveal_works() {
+        let accounts = default_accounts();
         let mut contract = get_test_name_service();
-        let contract_addr = contract.env().account_id();
-
-        set_account_balance::<DefaultEnvironment>(default_accounts.alice, 2000);
-        transfer_in::<DefaultEnvironment>(1234);
-        assert_eq!(contract.register(name.clone(), 1, None, None, true), Ok(()));
+        let name = "alice".to_string();
+        let secret = [0x42; 32];
+        let (min_age, max_age) = contract.get_commitment_age();
 
-        assert_eq!(
-            get_account_balance::<DefaultEnvironment>(default_accounts.alice),
-            Ok(1000)
-        );
+        set_next_caller(accounts.alice);
+        let commitment = contract.make_commitment(name.clone(), accounts.alice, secret);
 
+        // Revealing without a commitment fails
+        transfer_in::<DefaultEnvironment>(1000);
         assert_eq!(
-            get_account_balance::<DefaultEnvironment>(contract_addr),
-            Ok(1000)
-        );
-    }
-
-    #[ink::test]
-    fn renew_works() {
-        let default_accounts = default_accounts();
-        let name = String::from("test");
-
-        set_next_caller(default_accounts.alice);
-        let mut contract = get_test_name_service();
+            contract.reveal_and_register(name.clone(), accounts.alice, 1, None, None, secret),
+            Err(Error::CommitmentNotFound)
+        );
 
-        // Try renewing a non-existant name
+        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP);
+        assert_eq!(contract.commit(commitment), Ok(()));
         assert_eq!(
-            contract.renew(name.clone(), 2, None),
-            Err(Error::NameDoesntExist)
+            contract.commit(commitment),
+            Err(Error::CommitmentAlreadyExists)
         );
+        assert_eq!(contract.get_commitment(commitment), Some(GRACE_TIMESTAMP));
 
-        let origin_time = GRACE_TIMESTAMP;
-        set_block_timestamp::<DefaultEnvironment>(origin_time);
+        // Revealing too early fails
+        assert_eq!(
+            contract.reveal_and_register(name.clone(), accounts.alice, 1, None, None, secret),
+            Err(Error::CommitmentTooNew)
+        );
 
-        // Register a name
-        set_value_transferred::<DefaultEnvironment>(1000);
+        // Revealing with a different recipient fails
+        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP + min_age);
         assert_eq!(
-            contract.register(name.clone(), 1, None, None, false),
-            Ok(())
+            contract.reveal_and_register(name.clone(), accounts.bob, 1, None, None, secret),
+            Err(Error::CommitmentNotFound)
         );
 
-        // Get current expiry
         assert_eq!(
-            contract.get_registration_period(name.clone()),
-            Ok((origin_time, origin_time + 60))
+            contract.reveal_and_register(name.clone(), accounts.alice, 1, None, None, secret),
+            Ok(())
         );
+        assert_eq!(contract.get_owner(name), Ok(accounts.alice));
+        assert_eq!(contract.get_commitment(commitment), None);
 
-        // Renew the name
-        assert_eq!(contract.renew(name.clone(), 2, None), Ok(1000));
+        // Stale commitments expire
+        let name2 = "bob".to_string();
+        let commitment2 = contract.make_commitment(name2.clone(), accounts.alice, secret);
+        contract.commit(commitment2).unwrap();
+        assert_eq!(contract.clear_expired_commitments(vec![commitment2]), Ok(0));
 
-        // Get new expiry
+        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP + min_age + max_age + 1);
         assert_eq!(
-            contract.get_registration_period(name.clone()),
-            Ok((origin_time, origin_time + 180))
+            contract.reveal_and_register(name2, accounts.alice, 1, None, None, secret),
+            Err(Error::CommitmentExpired)
         );
+
+        contract.commit(commitment2).unwrap();
+        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP + 2 * (min_age + max_age));
+        assert_eq!(contract.clear_expired_commitments(vec![commitment2]), Ok(1));
     }
 
     #[ink::test]
-    fn renew_excess_fee_works() {
-        let default_accounts = default_accounts();
-        let name = String::from("test");
-
-        set_next_caller(default_accounts.alice);
+    fn commitment_required_works() {
+        let accounts = default_accounts();
         let mut contract = get_test_name_service();
-        let contract_addr = contract.env().account_id();
 
-        set_account_balance::<DefaultEnvironment>(default_accounts.alice, 3000);
+        // The commit-reveal flow is opt-in
+        assert!(!contract.get_commitment_required());
 
-        transfer_in::<DefaultEnvironment>(1000);
-        assert_eq!(contract.register(name.clone(), 1, None, None, true), Ok(()));
         assert_eq!(
-            get_account_balance::<DefaultEnvironment>(default_accounts.alice),
-            Ok(2000)
+            contract.set_commitment_age(10, 5),
+            Err(Error::InvalidCommitmentAge)
         );
+        assert_eq!(contract.set_commitment_age(0, 5), Ok(()));
+        assert_eq!(contract.set_commitment_required(true), Ok(()));
 
-        // Transfer extra fee
-        transfer_in::<DefaultEnvironment>(1234);
-        assert_eq!(contract.renew(name.clone(), 1, None), Ok(1000));
+        transfer_in::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register("alice".to_string(), 1, None, None, false),
+            Err(Error::CommitmentRequired)
+        );
 
+        let commitment = contract.make_commitment("alice".to_string(), accounts.alice, [0; 32]);
+        contract.commit(commitment).unwrap();
         assert_eq!(
-            get_account_balance::<DefaultEnvironment>(default_accounts.alice),
-            Ok(1000)
+            contract.reveal_and_register(
+                "alice".to_string(),
+                accounts.alice,
+                1,
+                None,
+                None,
+                [0; 32]
+            ),
+            Ok(())
         );
 
+        // Invocation from non-admin address fails
+        set_next_caller(accounts.bob);
         assert_eq!(
-            get_account_balance::<DefaultEnvironment>(contract_addr),
-            Ok(2000)
+            contract.set_commitment_required(false),
+            Err(Error::MissingRole(Role::ConfigManager))
         );
     }
 
     #[ink::test]
-    fn withdraw_works() {
-        let default_accounts = default_accounts();
-        let name = String::from("test");
-
-        // Alice deploys the contract
-        set_next_caller(default_accounts.alice);
+    fn redemption_period_works() {
+        let accounts = default_accounts();
         let mut contract = get_test_name_service();
+        let name = "alice".to_string();
+        let redemption_period = 30;
 
-        // Bob registers
-        let fees = 1000;
-        set_next_caller(default_accounts.bob);
-        set_account_balance::<DefaultEnvironment>(default_accounts.bob, fees);
-        transfer_in::<DefaultEnvironment>(fees);
-        assert_eq!(contract.register(name, 1, None, None, false), Ok(()));
+        assert_eq!(contract.set_redemption_period(redemption_period), Ok(()));
+        assert_eq!(contract.get_redemption_period(), redemption_period);
 
-        // Alice (admin) withdraws the funds
-        set_next_caller(default_accounts.alice);
+        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP);
+        set_next_caller(accounts.alice);
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register(name.clone(), 1, None, None, false)
+            .unwrap();
+        let expiry = GRACE_TIMESTAMP + YEAR;
 
-        let balance_before =
-            get_account_balance::<DefaultEnvironment>(default_accounts.alice).unwrap();
-        assert_eq!(contract.withdraw(None, Some(fees)), Ok(()));
-        let balance_after =
-            get_account_balance::<DefaultEnvironment>(default_accounts.alice).unwrap();
+        // Expired name enters the redemption period
+        set_block_timestamp::<DefaultEnvironment>(expiry);
+        assert_eq!(
+            contract.get_name_status(vec![name.clone()]),
+            vec![NameStatus::Redemption(
+                accounts.alice,
+                expiry + redemption_period
+            )]
+        );
+        assert_eq!(
+            contract.get_address(name.clone()),
+            Err(Error::NameDoesntExist)
+        );
+        assert_eq!(contract.clear_expired_names(vec![name.clone()]), Ok(0));
 
-        assert_eq!(balance_after, balance_before + fees);
-    }
+        // Others can neither register nor renew it
+        set_next_caller(accounts.bob);
+        assert_eq!(
+            contract.register(name.clone(), 1, None, None, false),
+            Err(Error::NameInRedemptionPeriod)
+        );
+        assert_eq!(
+            contract.renew(name.clone(), 1, None),
+            Err(Error::CallerIsNotOwner)
+        );
 
-    #[ink::test]
-    fn withdraw_only_owner() {
-        let default_accounts = default_accounts();
-        let name = String::from("test");
+        // Previous owner can renew
+        set_next_caller(accounts.alice);
+        assert_eq!(contract.renew(name.clone(), 1, None), Ok(1000));
+        assert_eq!(
+            contract.get_registration_period(name.clone()),
+            Ok((GRACE_TIMESTAMP, expiry + YEAR))
+        );
+        assert_eq!(contract.get_owner(name.clone()), Ok(accounts.alice));
 
-        set_next_caller(default_accounts.alice);
-        let mut contract = get_test_name_service();
+        // Once the redemption period is over, anyone can register it
+        set_block_timestamp::<DefaultEnvironment>(expiry + YEAR + redemption_period);
+        assert_eq!(
+            contract.get_name_status(vec![name.clone()]),
+            vec![NameStatus::Available]
+        );
 
-        let _acc_balance_before_transfer: Balance =
-            get_account_balance::<DefaultEnvironment>(default_accounts.alice).unwrap();
-        set_value_transferred::<DefaultEnvironment>(1000);
-        assert_eq!(contract.register(name, 1, None, None, false), Ok(()));
+        set_next_caller(accounts.bob);
+        assert_eq!(
+            contract.register(name.clone(), 1, None, None, false),
+            Ok(())
+        );
 
-        set_next_caller(default_accounts.bob);
-        assert_eq!(contract.withdraw(None, None), Err(Error::NotAdmin));
+        // Previous owner's state is wiped
+        assert_eq!(
+            contract.get_owned_names_of_address(accounts.bob),
+            vec![name.clone()]
+        );
+        assert_eq!(
+            contract.get_owned_names_of_address(accounts.alice),
+            Vec::<String>::new()
+        );
+        assert_eq!(contract.get_owner_to_name_count(accounts.alice), 0);
     }
 
     #[ink::test]
-    fn reverse_search_works() {
-        let default_accounts = default_accounts();
-        let name = String::from("test");
-        let name2 = String::from("test2");
-
-        set_next_caller(default_accounts.alice);
+    fn paginated_names_works() {
+        let accounts = default_accounts();
         let mut contract = get_test_name_service();
+        let names = ["a", "b", "c", "d", "e"].map(String::from);
 
-        set_value_transferred::<DefaultEnvironment>(1000);
-        assert_eq!(contract.register(name, 1, None, None, false), Ok(()));
-        set_value_transferred::<DefaultEnvironment>(1000);
-        assert_eq!(contract.register(name2, 1, None, None, false), Ok(()));
-        assert!(contract
-            .get_owned_names_of_address(default_accounts.alice)
-            .contains(&String::from("test")));
-        assert!(contract
-            .get_owned_names_of_address(default_accounts.alice)
-            .contains(&String::from("test2")));
-    }
-
-    #[ink::test]
-    fn register_empty_reverts() {
-        let default_accounts = default_accounts();
-        let name = String::from("");
-
-        set_next_caller(default_accounts.alice);
-        let mut contract = get_test_name_service();
+        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP);
+        set_next_caller(accounts.alice);
+        for (years, name) in [3, 1, 2, 1, 1].iter().zip(names.iter()) {
+            transfer_in::<DefaultEnvironment>(1000);
+            contract
+                .register(name.clone(), *years, None, None, false)
+                .unwrap();
+        }
 
-        set_value_transferred::<DefaultEnvironment>(1000);
         assert_eq!(
-            contract.register(name, 1, None, None, false),
-            Err(Error::NameNotAllowed)
+            contract.get_owned_names_of_address_paginated(accounts.alice, 0, 2),
+            (vec![names[0].clone(), names[1].clone()], Some(2))
+        );
+        assert_eq!(
+            contract.get_owned_names_of_address_paginated(accounts.alice, 2, 2),
+            (vec![names[2].clone(), names[3].clone()], Some(4))
+        );
+        assert_eq!(
+            contract.get_controlled_names_of_address_paginated(accounts.alice, 4, 2),
+            (vec![names[4].clone()], None)
+        );
+        assert_eq!(
+            contract.get_resolving_names_of_address_paginated(accounts.alice, 5, 2),
+            (vec![], None)
+        );
+        assert_eq!(
+            contract.get_names_of_address_paginated(accounts.alice, (NameCollection::Owned, 3), 1),
+            (vec![names[3].clone()], Some((NameCollection::Owned, 4)))
         );
-    }
 
-    // TODO: enable this test once we get cross-contract testing working
-    // #[ink::test]
-    // fn register_disallowed_reverts() {
-    //     let default_accounts = default_accounts();
-    //     let name = String::from("ýáěšžčřýáěščžá");
-    //
-    //     set_next_caller(default_accounts.alice);
-    //     let mut contract = get_test_name_service();
-    //
-    //     set_value_transferred::<DefaultEnvironment>(160_u128 * 10_u128.pow(12));
-    //     assert_eq!(contract.register(name, None), Err(NameNotAllowed, false));
-    // }
+        // Names related to an address in several ways are listed once
+        contract
+            .set_controller(names[4].clone(), accounts.bob)
+            .unwrap();
+        assert_eq!(
+            contract.get_names_of_address_paginated(accounts.alice, (NameCollection::Owned, 3), 5),
+            (vec![names[3].clone(), names[4].clone()], None)
+        );
+        assert_eq!(
+            contract.get_names_of_address_paginated(accounts.bob, (NameCollection::Owned, 0), 5),
+            (vec![names[4].clone()], None)
+        );
 
-    #[ink::test]
-    fn register_with_fee_works() {
-        let default_accounts = default_accounts();
-        let name = String::from("test");
+        // Removing a name leaves the slots of the other names unchanged
+        contract.release(names[1].clone()).unwrap();
+        assert_eq!(contract.get_owner_to_name_count(accounts.alice), 4);
+        assert_eq!(contract.get_owner_to_name_slots(accounts.alice), 5);
+        assert_eq!(
+            contract.get_owned_names_of_address_paginated(accounts.alice, 2, 2),
+            (vec![names[2].clone(), names[3].clone()], Some(4))
+        );
 
-        set_next_caller(default_accounts.alice);
-        let mut contract = get_test_name_service();
+        // Expired names are skipped, but still advance the cursor
+        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP + YEAR);
+        assert_eq!(
+            contract.get_owned_names_of_address_paginated(accounts.alice, 0, 2),
+            (vec![names[0].clone(), names[2].clone()], Some(3))
+        );
 
-        set_value_transferred::<DefaultEnvironment>(1000);
+        let period = |years: u64| (GRACE_TIMESTAMP, GRACE_TIMESTAMP + years * YEAR);
         assert_eq!(
-            contract.register(name.clone(), 1, None, None, false),
-            Ok(())
+            contract.get_owned_names_with_period(accounts.alice, 0, 1),
+            (vec![(names[0].clone(), period(3))], Some(1))
         );
         assert_eq!(
-            contract.register(name, 1, None, None, false),
-            Err(Error::NameAlreadyExists)
+            contract.get_owned_names_with_period(accounts.alice, 1, 5),
+            (vec![(names[2].clone(), period(2))], None)
         );
     }
 
     #[ink::test]
-    fn register_without_fee_reverts() {
-        let default_accounts = default_accounts();
-        let name = String::from("test");
-
-        set_next_caller(default_accounts.alice);
+    fn name_slots_are_reused() {
+        let accounts = default_accounts();
         let mut contract = get_test_name_service();
+        let names = ["a", "b", "c", "d"].map(String::from);
+
+        set_next_caller(accounts.alice);
+        for name in names.iter().take(3) {
+            transfer_in::<DefaultEnvironment>(1000);
+            contract
+                .register(name.clone(), 1, None, None, false)
+                .unwrap();
+        }
 
+        // A freed slot is taken by the next added name
+        contract.release(names[0].clone()).unwrap();
+        assert_eq!(contract.get_owner_to_name_slots(accounts.alice), 3);
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register(names[3].clone(), 1, None, None, false)
+            .unwrap();
+        assert_eq!(contract.get_owner_to_name_slots(accounts.alice), 3);
         assert_eq!(
-            contract.register(name, 1, None, None, false),
-            Err(Error::FeeNotPaid)
+            contract.get_owned_names_of_address(accounts.alice),
+            vec![names[3].clone(), names[1].clone(), names[2].clone()]
+        );
+        assert_eq!(contract.balance_of(accounts.alice), 3);
+        assert_eq!(
+            contract.owners_token_by_index(accounts.alice, 2),
+            Ok(names[2].clone().into())
+        );
+        assert_eq!(
+            contract.owners_token_by_index(accounts.alice, 3),
+            Err(PSP34Error::TokenNotExists)
+        );
+
+        // Churning names doesn't grow the slots beyond the most names held at once
+        for _ in 0..5 {
+            contract
+                .transfer(accounts.bob, names[1].clone(), false, false, false, vec![])
+                .unwrap();
+            set_next_caller(accounts.bob);
+            contract
+                .transfer(
+                    accounts.alice,
+                    names[1].clone(),
+                    false,
+                    false,
+                    false,
+                    vec![],
+                )
+                .unwrap();
+            set_next_caller(accounts.alice);
+        }
+        assert_eq!(contract.get_owner_to_name_slots(accounts.alice), 3);
+        assert_eq!(contract.get_controller_to_name_slots(accounts.alice), 3);
+        assert_eq!(contract.get_resolving_to_name_slots(accounts.alice), 3);
+        assert_eq!(contract.balance_of(accounts.alice), 3);
+
+        // The last slot is dropped once freed
+        contract.release(names[2].clone()).unwrap();
+        assert_eq!(contract.get_owner_to_name_slots(accounts.alice), 2);
+        contract.release(names[3].clone()).unwrap();
+        contract.release(names[1].clone()).unwrap();
+        assert_eq!(contract.get_owner_to_name_slots(accounts.alice), 1);
+        assert_eq!(contract.balance_of(accounts.alice), 0);
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register(names[0].clone(), 1, None, None, false)
+            .unwrap();
+        assert_eq!(contract.get_owner_to_name_slots(accounts.alice), 1);
+        assert_eq!(
+            contract.owners_token_by_index(accounts.alice, 0),
+            Ok(names[0].clone().into())
         );
     }
 
     #[ink::test]
-    fn release_works() {
-        let default_accounts = default_accounts();
-        let name = String::from("test");
-
-        set_next_caller(default_accounts.alice);
+    fn owned_names_with_period_sorted_by_expiry() {
+        let accounts = default_accounts();
         let mut contract = get_test_name_service();
+        let names = ["a", "b", "c", "d", "e"].map(String::from);
 
-        set_value_transferred::<DefaultEnvironment>(1000);
+        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP);
+        set_next_caller(accounts.alice);
+        for (years, name) in [3, 1, 2, 4, 1].iter().zip(names.iter()) {
+            transfer_in::<DefaultEnvironment>(1000);
+            contract
+                .register(name.clone(), *years, None, None, false)
+                .unwrap();
+        }
+
+        let with_period = |idx: usize, years: u64| {
+            let period = (GRACE_TIMESTAMP, GRACE_TIMESTAMP + years * YEAR);
+            (names[idx].clone(), period)
+        };
+
+        // A single page returns all the names sorted by expiry (& then by name)
         assert_eq!(
-            contract.register(name.clone(), 1, None, None, false),
-            Ok(())
+            contract.get_owned_names_with_period(accounts.alice, 0, 5),
+            (
+                vec![
+                    with_period(1, 1),
+                    with_period(4, 1),
+                    with_period(2, 2),
+                    with_period(0, 3),
+                    with_period(3, 4),
+                ],
+                None
+            )
         );
+
+        // Smaller pages are sorted each
         assert_eq!(
-            contract.set_address(name.clone(), default_accounts.alice),
-            Ok(())
+            contract.get_owned_names_with_period(accounts.alice, 0, 3),
+            (
+                vec![with_period(1, 1), with_period(2, 2), with_period(0, 3)],
+                Some(3)
+            )
         );
-        assert_eq!(contract.get_owner(name.clone()), Ok(default_accounts.alice));
         assert_eq!(
-            contract.get_address(name.clone()),
-            Ok(default_accounts.alice)
+            contract.get_owned_names_with_period(accounts.alice, 3, 3),
+            (vec![with_period(4, 1), with_period(3, 4)], None)
         );
 
+        // Renewing a name moves it accordingly
+        transfer_in::<DefaultEnvironment>(1000);
+        contract.renew(names[1].clone(), 4, None).unwrap();
         assert_eq!(
-            contract.get_owned_names_of_address(default_accounts.alice),
-            Vec::from([name.clone()])
+            contract
+                .get_owned_names_with_period(accounts.alice, 0, 5)
+                .0
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>(),
+            [4, 2, 0, 3, 1].map(|idx| names[idx].clone())
         );
+    }
+
+    #[ink::test]
+    fn global_name_index_works() {
+        let accounts = default_accounts();
+        let mut contract = get_test_name_service();
+        let names = ["a", "b", "c"].map(String::from);
+
+        set_next_caller(accounts.alice);
+        for name in names.iter() {
+            transfer_in::<DefaultEnvironment>(1000);
+            contract
+                .register(name.clone(), 1, None, None, false)
+                .unwrap();
+        }
+
+        assert_eq!(contract.total_supply(), 3);
+        assert_eq!(contract.token_by_index(1), Ok(names[1].clone().into()));
+        assert_eq!(contract.token_by_index(3), Err(PSP34Error::TokenNotExists));
         assert_eq!(
-            contract.get_controlled_names_of_address(default_accounts.alice),
-            Vec::from([name.clone()])
+            contract.get_all_names(0, 2),
+            (vec![names[0].clone(), names[1].clone()], Some(2))
         );
+
+        // Removing a name swaps the last one into its slot
+        contract.release(names[0].clone()).unwrap();
+        assert_eq!(contract.total_supply(), 2);
+        assert_eq!(contract.token_by_index(0), Ok(names[2].clone().into()));
         assert_eq!(
-            contract.get_resolving_names_of_address(default_accounts.alice),
-            Vec::from([name.clone()])
+            contract.get_all_names(0, 5),
+            (vec![names[2].clone(), names[1].clone()], None)
         );
 
-        assert_eq!(contract.release(name.clone()), Ok(()));
+        // Transfers don't affect the global index
+        contract
+            .transfer(accounts.bob, names[1].clone(), false, false, false, vec![])
+            .unwrap();
+        assert_eq!(contract.token_by_index(1), Ok(names[1].clone().into()));
+
+        // Only unindexed names are backfilled
+        assert_eq!(contract.get_all_names_count(), contract.total_supply());
+        set_next_caller(accounts.bob);
         assert_eq!(
-            contract.get_owner(name.clone()),
-            Err(Error::NameDoesntExist)
+            contract.backfill_global_index(vec![names[1].clone()]),
+            Err(Error::NotAdmin)
         );
+        set_next_caller(accounts.alice);
         assert_eq!(
-            contract.get_address(name.clone()),
-            Err(Error::NameDoesntExist)
+            contract.backfill_global_index(vec![names[0].clone(), names[1].clone()]),
+            Ok(0)
         );
+        assert_eq!(contract.get_all_names_count(), 2);
+    }
+
+    #[ink::test]
+    fn marketplace_works() {
+        let accounts = default_accounts();
+        let mut contract = get_test_name_service();
+        let name = "alice".to_string();
+
+        set_next_caller(accounts.alice);
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register(name.clone(), 1, None, None, false)
+            .unwrap();
+        contract
+            .update_records(
+                name.clone(),
+                vec![("twitter".to_string(), Some("@alice".to_string()))],
+                true,
+            )
+            .unwrap();
 
+        // Only the owner can list a name
+        set_next_caller(accounts.bob);
         assert_eq!(
-            contract.get_owned_names_of_address(default_accounts.alice),
-            Vec::<String>::new()
+            contract.list_for_sale(name.clone(), 500, 10),
+            Err(Error::CallerIsNotOwner)
         );
+        set_next_caller(accounts.alice);
         assert_eq!(
-            contract.get_controlled_names_of_address(default_accounts.alice),
-            Vec::<String>::new()
+            contract.list_for_sale(name.clone(), 500, 0),
+            Err(Error::InvalidListingExpiry)
         );
+        assert_eq!(contract.list_for_sale(name.clone(), 500, 10), Ok(()));
         assert_eq!(
-            contract.get_resolving_names_of_address(default_accounts.alice),
-            Vec::<String>::new()
+            contract.get_listing(name.clone()),
+            Some(Listing::new(accounts.alice, 500, 10))
         );
 
-        /* Another account can register again*/
-        set_next_caller(default_accounts.bob);
-        set_value_transferred::<DefaultEnvironment>(1000);
+        // Protocol fee is capped at 100%
+        set_next_caller(accounts.alice);
         assert_eq!(
-            contract.register(name.clone(), 1, None, None, false),
-            Ok(())
+            contract.set_marketplace_fee(MAX_BPS + 1),
+            Err(Error::InvalidMarketplaceFee)
         );
+        assert_eq!(contract.set_marketplace_fee(1000), Ok(()));
+
+        // Buyer must pay the listed price
+        set_account_balance::<DefaultEnvironment>(accounts.bob, 10_000);
+        set_next_caller(accounts.bob);
+        transfer_in::<DefaultEnvironment>(499);
+        assert_eq!(contract.buy(name.clone()), Err(Error::FeeNotPaid));
+
+        let seller_balance = get_account_balance::<DefaultEnvironment>(accounts.alice).unwrap();
+        transfer_in::<DefaultEnvironment>(600);
+        assert_eq!(contract.buy(name.clone()), Ok(()));
+        assert_eq!(contract.get_owner(name.clone()), Ok(accounts.bob));
+        assert_eq!(contract.get_address(name.clone()), Ok(accounts.bob));
+        assert_eq!(contract.get_all_records(name.clone()), vec![]);
         assert_eq!(
-            contract.set_address(name.clone(), default_accounts.bob),
-            Ok(())
+            get_account_balance::<DefaultEnvironment>(accounts.alice).unwrap(),
+            seller_balance + 450
         );
-        assert_eq!(contract.get_owner(name.clone()), Ok(default_accounts.bob));
-        assert_eq!(contract.get_address(name.clone()), Ok(default_accounts.bob));
+
+        // Listing is cleared upon sale
+        assert_eq!(contract.get_listing(name.clone()), None);
+        transfer_in::<DefaultEnvironment>(500);
+        assert_eq!(contract.buy(name.clone()), Err(Error::NotListed));
+
+        // Listing is cleared upon transfer
+        assert_eq!(contract.list_for_sale(name.clone(), 500, 10), Ok(()));
+        contract
+            .transfer(accounts.charlie, name.clone(), false, false, false, vec![])
+            .unwrap();
+        assert_eq!(contract.get_listing(name.clone()), None);
+
+        // Listing is void once expired
+        set_next_caller(accounts.charlie);
+        assert_eq!(contract.list_for_sale(name.clone(), 500, 10), Ok(()));
+        set_block_timestamp::<DefaultEnvironment>(11);
+        assert_eq!(contract.get_listing(name.clone()), None);
+
+        // Listing can be cancelled by the owner
+        assert_eq!(contract.list_for_sale(name.clone(), 500, 20), Ok(()));
+        assert_eq!(contract.cancel_listing(name.clone()), Ok(()));
+        assert_eq!(contract.cancel_listing(name.clone()), Err(Error::NotListed));
+
+        // Listing is cleared upon release
+        assert_eq!(contract.list_for_sale(name.clone(), 500, 20), Ok(()));
         assert_eq!(contract.release(name.clone()), Ok(()));
-        assert_eq!(
-            contract.get_owner(name.clone()),
-            Err(Error::NameDoesntExist)
-        );
-        assert_eq!(contract.get_address(name), Err(Error::NameDoesntExist));
+        set_next_caller(accounts.alice);
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register(name.clone(), 1, None, None, false)
+            .unwrap();
+        assert_eq!(contract.get_listing(name), None);
     }
 
     #[ink::test]
-    fn controller_separation_works() {
+    fn offers_works() {
         let accounts = default_accounts();
-        let name = String::from("test");
+        let mut contract = get_test_name_service();
+        let name = "alice".to_string();
 
         set_next_caller(accounts.alice);
-
-        let mut contract = get_test_name_service();
-        set_value_transferred::<DefaultEnvironment>(1000);
+        transfer_in::<DefaultEnvironment>(1000);
         contract
             .register(name.clone(), 1, None, None, false)
             .unwrap();
 
-        // Caller is not controller, `set_address` should fail.
+        set_account_balance::<DefaultEnvironment>(accounts.bob, 10_000);
+        set_account_balance::<DefaultEnvironment>(accounts.charlie, 10_000);
+
+        // Offers can only be made on registered names
         set_next_caller(accounts.bob);
+        transfer_in::<DefaultEnvironment>(100);
         assert_eq!(
-            contract.set_address(name.clone(), accounts.bob),
-            Err(Error::CallerIsNotController)
+            contract.make_offer("bob".to_string(), 10),
+            Err(Error::NameDoesntExist)
         );
-
-        /* Caller is not controller, `update_records` should fail */
-        set_next_caller(accounts.bob);
         assert_eq!(
-            contract.update_records(
-                name.clone(),
-                Vec::from([("twitter".to_string(), None)]),
-                false,
-            ),
-            Err(Error::CallerIsNotController)
+            contract.make_offer(name.clone(), 0),
+            Err(Error::InvalidOfferExpiry)
         );
 
-        // Caller is controller, `update_records` should pass
-        set_next_caller(accounts.alice);
+        transfer_in::<DefaultEnvironment>(100);
+        assert_eq!(contract.make_offer(name.clone(), 10), Ok(()));
+        transfer_in::<DefaultEnvironment>(50);
+        assert_eq!(contract.make_offer(name.clone(), 20), Ok(()));
         assert_eq!(
-            contract.update_records(name, Vec::from([("twitter".to_string(), None)]), false),
-            Ok(())
+            contract.get_offer(name.clone(), accounts.bob),
+            Some(Offer::new(accounts.alice, 0, 150, 20))
         );
-    }
 
-    #[ink::test]
-    fn set_address_works() {
-        let accounts = default_accounts();
-        let name = String::from("test");
+        set_next_caller(accounts.charlie);
+        transfer_in::<DefaultEnvironment>(300);
+        assert_eq!(contract.make_offer(name.clone(), 10), Ok(()));
+        assert_eq!(contract.get_offers(name.clone()).len(), 2);
+        assert_eq!(contract.get_escrowed_balance(), 450);
 
+        // Escrowed funds cannot be withdrawn by the admin
         set_next_caller(accounts.alice);
-
-        let mut contract = get_test_name_service();
-        set_value_transferred::<DefaultEnvironment>(1000);
         assert_eq!(
-            contract.register(name.clone(), 1, None, None, false),
-            Ok(())
+            contract.withdraw(None, Some(1000 + 450)),
+            Err(Error::InsufficientBalance)
         );
 
-        // Caller is not controller, `set_address` should fail.
+        // Bidder can withdraw the offer at any time
         set_next_caller(accounts.bob);
+        let balance = get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+        assert_eq!(contract.withdraw_offer(name.clone()), Ok(()));
         assert_eq!(
-            contract.set_address(name.clone(), accounts.bob),
-            Err(Error::CallerIsNotController)
+            get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap(),
+            balance + 150
         );
+        assert_eq!(
+            contract.withdraw_offer(name.clone()),
+            Err(Error::OfferNotFound)
+        );
+        assert_eq!(contract.get_offers_of_address(accounts.bob), vec![]);
 
-        // Caller is controller, set_address will be successful
-        set_next_caller(accounts.alice);
-        assert_eq!(contract.set_address(name.clone(), accounts.bob), Ok(()));
-        assert_eq!(contract.get_address(name), Ok(accounts.bob));
-    }
-
-    #[ink::test]
-    fn transfer_works() {
-        let accounts = default_accounts();
-        let name = String::from("test");
+        // Only the owner can accept an offer
+        assert_eq!(
+            contract.accept_offer(name.clone(), accounts.charlie),
+            Err(Error::CallerIsNotOwner)
+        );
 
         set_next_caller(accounts.alice);
-
-        let mut contract = get_test_name_service();
-        set_value_transferred::<DefaultEnvironment>(1000);
+        let balance = get_account_balance::<DefaultEnvironment>(accounts.alice).unwrap();
         assert_eq!(
-            contract.register(name.clone(), 1, None, None, false),
+            contract.accept_offer(name.clone(), accounts.charlie),
             Ok(())
         );
-
-        // Test transfer of owner.
+        assert_eq!(contract.get_owner(name.clone()), Ok(accounts.charlie));
         assert_eq!(
-            contract.transfer(accounts.bob, name.clone(), false, false, false, vec![]),
-            Ok(())
+            get_account_balance::<DefaultEnvironment>(accounts.alice).unwrap(),
+            balance + 300
         );
+        assert_eq!(contract.get_escrowed_balance(), 0);
+        assert_eq!(contract.get_name_to_bidder_count(name.clone()), 0);
+
+        // Offers made to a previous owner become void, but stay refundable
+        set_next_caller(accounts.bob);
+        transfer_in::<DefaultEnvironment>(100);
+        assert_eq!(contract.make_offer(name.clone(), 10), Ok(()));
+        set_next_caller(accounts.charlie);
+        contract
+            .transfer(accounts.alice, name.clone(), false, false, false, vec![])
+            .unwrap();
+        assert_eq!(contract.get_offers(name.clone()), vec![]);
 
+        set_next_caller(accounts.alice);
         assert_eq!(
-            contract.get_owned_names_of_address(accounts.alice),
-            Vec::<String>::new()
+            contract.accept_offer(name.clone(), accounts.bob),
+            Err(Error::OfferExpired)
         );
         assert_eq!(
-            contract.get_owned_names_of_address(accounts.bob),
-            Vec::from([name.clone()])
+            contract.get_offers_of_address(accounts.bob),
+            vec![(name.clone(), Offer::new(accounts.charlie, 0, 100, 10))]
         );
 
-        // Alice is not the controller anymore
+        // A void offer cannot be topped up (and thereby retargeted to the current owner)
+        set_next_caller(accounts.bob);
+        transfer_in::<DefaultEnvironment>(50);
         assert_eq!(
-            contract.set_controller(name.clone(), accounts.bob),
-            Err(Error::CallerIsNotController)
+            contract.make_offer(name.clone(), 20),
+            Err(Error::OfferExpired)
         );
+        assert_eq!(contract.withdraw_offer(name.clone()), Ok(()));
 
-        // Controller is bob, alice `set_address` should fail.
+        // Offers become void once the name expires, even if the same owner registers it again
+        transfer_in::<DefaultEnvironment>(100);
+        assert_eq!(contract.make_offer(name.clone(), u64::MAX), Ok(()));
+
+        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP + 1);
+        set_next_caller(accounts.alice);
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register(name.clone(), 1, None, None, false)
+            .unwrap();
+        assert_eq!(contract.get_offers(name.clone()), vec![]);
         assert_eq!(
-            contract.set_address(name.clone(), accounts.bob),
-            Err(Error::CallerIsNotController)
+            contract.accept_offer(name.clone(), accounts.bob),
+            Err(Error::OfferExpired)
         );
 
         set_next_caller(accounts.bob);
-        // Now owner is bob, `set_address` should be successful.
-        assert_eq!(contract.set_address(name.clone(), accounts.eve), Ok(()));
-        assert_eq!(contract.get_address(name), Ok(accounts.eve));
+        let balance = get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+        assert_eq!(contract.withdraw_offer(name), Ok(()));
+        assert_eq!(
+            get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap(),
+            balance + 100
+        );
+        assert_eq!(contract.get_escrowed_balance(), 0);
     }
 
     #[ink::test]
-    fn lock_nft_works() {
-        // Setup
+    fn auction_works() {
         let accounts = default_accounts();
-        let name = String::from("alice");
         let mut contract = get_test_name_service();
+        let name = "premium".to_string();
 
         set_next_caller(accounts.alice);
-        set_value_transferred::<DefaultEnvironment>(1000);
+        transfer_in::<DefaultEnvironment>(1000);
         contract
-            .register(name.clone(), 1, None, None, false)
+            .register("alice".to_string(), 1, None, None, false)
             .unwrap();
-
-        let id: Id = name.clone().into();
         contract
-            .approve(accounts.bob, Some(id.clone()), true)
+            .add_reserved_names(vec![(name.clone(), None)], false)
             .unwrap();
 
-        // Lock
-        let lock_msg = "azero.id-lock".as_bytes().to_vec();
+        // Only reserved (unassigned) or premium names can be auctioned
+        assert_eq!(
+            contract.start_auction("alice".to_string(), 100, 10, 50, 10, 1),
+            Err(Error::NotAuctionable)
+        );
+        assert_eq!(
+            contract.start_auction(name.clone(), 100, 0, 50, 10, 1),
+            Err(Error::InvalidAuctionConfig)
+        );
         set_next_caller(accounts.bob);
         assert_eq!(
-            PSP34::transfer(&mut contract, accounts.bob, id.clone(), lock_msg),
+            contract.start_auction(name.clone(), 100, 10, 50, 10, 1),
+            Err(Error::MissingRole(Role::ReservationManager))
+        );
+        set_next_caller(accounts.alice);
+        assert_eq!(
+            contract.start_auction(name.clone(), 100, 10, 50, 10, 1),
             Ok(())
         );
-
-        // Verify address-dict didn't changed
-        let address_dict = contract.get_address_dict(name.clone());
         assert_eq!(
-            address_dict,
-            Ok(AddressDict {
-                owner: accounts.alice,
-                controller: accounts.alice,
-                resolved: accounts.alice
-            })
+            contract.start_auction(name.clone(), 100, 10, 50, 10, 1),
+            Err(Error::AuctionAlreadyExists)
         );
 
-        // Verify lock is assigned
-        assert_eq!(contract.get_lock_info(name.clone()), Some(accounts.bob));
+        set_account_balance::<DefaultEnvironment>(accounts.bob, 10_000);
+        set_account_balance::<DefaultEnvironment>(accounts.charlie, 10_000);
+
+        // Bids must match the start price, then the minimum increment
+        set_next_caller(accounts.bob);
+        transfer_in::<DefaultEnvironment>(99);
+        assert_eq!(contract.bid(name.clone()), Err(Error::BidTooLow));
+        transfer_in::<DefaultEnvironment>(100);
+        assert_eq!(contract.bid(name.clone()), Ok(()));
+
+        set_next_caller(accounts.charlie);
+        transfer_in::<DefaultEnvironment>(105);
+        assert_eq!(contract.bid(name.clone()), Err(Error::BidTooLow));
+
+        // Outbid bidder gets refunded
+        let balance = get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+        transfer_in::<DefaultEnvironment>(110);
+        assert_eq!(contract.bid(name.clone()), Ok(()));
         assert_eq!(
-            contract.get_name_status(vec![name.clone()]),
-            vec![NameStatus::Registered(
-                address_dict.unwrap(),
-                Some(accounts.bob)
-            )]
+            get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap(),
+            balance + 100
         );
+        assert_eq!(contract.get_escrowed_balance(), 110);
 
-        // Verify others cannot transfer it
-        set_next_caller(accounts.alice);
+        // Auction cannot be settled before its end
         assert_eq!(
-            PSP34::transfer(&mut contract, accounts.charlie, id.clone(), vec![]),
-            Err(PSP34Error::Custom("Name is locked".to_string()))
-        )
-    }
-
-    #[ink::test]
-    fn lock_and_release_works() {
-        // Setup
-        let accounts = default_accounts();
-        let name = String::from("alice");
-        let mut contract = get_test_name_service();
+            contract.settle_auction(name.clone()),
+            Err(Error::AuctionNotEnded)
+        );
 
-        set_next_caller(accounts.alice);
-        set_value_transferred::<DefaultEnvironment>(1000);
-        contract
-            .register(name.clone(), 1, None, None, false)
-            .unwrap();
+        // Late bids extend the auction
+        set_block_timestamp::<DefaultEnvironment>(45);
+        set_next_caller(accounts.bob);
+        transfer_in::<DefaultEnvironment>(120);
+        assert_eq!(contract.bid(name.clone()), Ok(()));
+        assert_eq!(contract.get_auction(name.clone()).unwrap().end_time, 55);
 
-        let id: Id = name.clone().into();
-        contract
-            .approve(accounts.bob, Some(id.clone()), true)
-            .unwrap();
+        set_block_timestamp::<DefaultEnvironment>(56);
+        set_next_caller(accounts.charlie);
+        transfer_in::<DefaultEnvironment>(200);
+        assert_eq!(contract.bid(name.clone()), Err(Error::AuctionEnded));
 
-        // Lock
-        let lock_msg = "azero.id-lock".as_bytes().to_vec();
-        set_next_caller(accounts.bob);
-        PSP34::transfer(&mut contract, accounts.bob, id.clone(), lock_msg).unwrap();
+        // Anyone can settle the auction
+        assert_eq!(contract.settle_auction(name.clone()), Ok(()));
+        assert_eq!(contract.get_owner(name.clone()), Ok(accounts.bob));
+        assert_eq!(contract.get_auction(name.clone()), None);
+        assert_eq!(contract.get_escrowed_balance(), 0);
+        assert_eq!(
+            contract.get_name_status(vec![name.clone()])[0],
+            NameStatus::Registered(AddressDict::new(accounts.bob), None)
+        );
+        assert_eq!(
+            contract.settle_auction(name.clone()),
+            Err(Error::AuctionNotFound)
+        );
 
-        // Update resolver while in lock
+        // Auctions without bids can be cancelled by the admin
         set_next_caller(accounts.alice);
-        contract.set_address(name.clone(), accounts.eve).unwrap();
-
-        // Unlock
-        set_next_caller(accounts.bob);
+        contract
+            .add_reserved_names(vec![("reserved".to_string(), None)], false)
+            .unwrap();
         assert_eq!(
-            PSP34::transfer(&mut contract, accounts.alice, id.clone(), vec![]),
+            contract.start_auction("reserved".to_string(), 100, 10, 50, 10, 1),
             Ok(())
         );
-
-        // Verify
-        assert_eq!(contract.get_lock_info(name.clone()), None);
+        assert_eq!(contract.cancel_auction("reserved".to_string()), Ok(()));
         assert_eq!(
-            contract.get_address_dict(name.clone()),
-            Ok(AddressDict {
-                owner: accounts.alice,
-                controller: accounts.alice,
-                resolved: accounts.eve
-            })
+            contract.get_auction("reserved".to_string()),
+            None::<Auction>
         );
-    }
-
-    #[ink::test]
-    fn lock_and_transfer_works() {
-        // Setup
-        let accounts = default_accounts();
-        let name = String::from("alice");
-        let mut contract = get_test_name_service();
 
-        set_next_caller(accounts.alice);
-        set_value_transferred::<DefaultEnvironment>(1000);
+        // Cancelling an auction refunds its highest bid
         contract
-            .register(name.clone(), 1, None, None, false)
+            .start_auction("reserved".to_string(), 100, 10, 50, 10, 1)
             .unwrap();
+        set_next_caller(accounts.bob);
+        let balance = get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+        transfer_in::<DefaultEnvironment>(100);
+        contract.bid("reserved".to_string()).unwrap();
+        set_next_caller(accounts.alice);
+        assert_eq!(contract.cancel_auction("reserved".to_string()), Ok(()));
+        assert_eq!(
+            get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap(),
+            balance
+        );
+        assert_eq!(contract.get_escrowed_balance(), 0);
 
-        let id: Id = name.clone().into();
+        // Names no longer auctionable reject bids, and refund the winner upon settlement
         contract
-            .approve(accounts.bob, Some(id.clone()), true)
+            .start_auction("reserved".to_string(), 100, 10, 50, 10, 1)
             .unwrap();
-
-        // Lock
-        let lock_msg = "azero.id-lock".as_bytes().to_vec();
         set_next_caller(accounts.bob);
-        PSP34::transfer(&mut contract, accounts.bob, id.clone(), lock_msg).unwrap();
+        transfer_in::<DefaultEnvironment>(100);
+        contract.bid("reserved".to_string()).unwrap();
 
-        // Unlock
-        set_next_caller(accounts.bob);
+        set_next_caller(accounts.alice);
+        contract
+            .add_reserved_names(
+                vec![("reserved".to_string(), Some(accounts.charlie))],
+                false,
+            )
+            .unwrap();
+        set_next_caller(accounts.charlie);
+        transfer_in::<DefaultEnvironment>(110);
         assert_eq!(
-            PSP34::transfer(&mut contract, accounts.charlie, id.clone(), vec![]),
-            Ok(())
+            contract.bid("reserved".to_string()),
+            Err(Error::NotAuctionable)
         );
 
-        // Verify
-        assert_eq!(contract.get_lock_info(name.clone()), None);
+        set_block_timestamp::<DefaultEnvironment>(200);
+        assert_eq!(contract.settle_auction("reserved".to_string()), Ok(()));
         assert_eq!(
-            contract.get_address_dict(name.clone()),
-            Ok(AddressDict {
-                owner: accounts.charlie,
-                controller: accounts.charlie,
-                resolved: accounts.charlie
-            })
+            get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap(),
+            balance
+        );
+        assert_eq!(contract.get_escrowed_balance(), 0);
+        assert_eq!(
+            contract.get_owner("reserved".to_string()),
+            Err(Error::NameDoesntExist)
         );
     }
 
     #[ink::test]
-    fn records_works() {
+    fn token_payment_works() {
         let accounts = default_accounts();
-        let key = String::from("twitter");
-        let value = String::from("@test");
-        let records = Vec::from([(key.clone(), Some(value.clone()))]);
-
-        let name_name = "test".to_string();
+        let mut contract = get_test_name_service();
+        let token = accounts.django;
+        let name = "alice".to_string();
 
+        // Only pricing managers can accept tokens, at a valid rate
+        set_next_caller(accounts.bob);
+        assert_eq!(
+            contract.set_token_rate(token, Some((1, 2))),
+            Err(Error::MissingRole(Role::PricingManager))
+        );
         set_next_caller(accounts.alice);
-        let mut contract = get_test_name_service();
+        assert_eq!(
+            contract.set_token_rate(token, Some((1, 0))),
+            Err(Error::InvalidTokenRate)
+        );
 
-        set_value_transferred::<DefaultEnvironment>(1000);
         assert_eq!(
-            contract.register(name_name.clone(), 1, None, None, false),
-            Ok(())
+            contract.register_with_token(name.clone(), accounts.alice, 1, None, None, token),
+            Err(Error::TokenNotAccepted)
         );
 
+        assert_eq!(contract.set_token_rate(token, Some((1, 2))), Ok(()));
+        assert_eq!(contract.get_token_rate(token), Some((1, 2)));
+        assert_eq!(contract.get_price_in_token(token, 1000), Ok(500));
+
+        // The fee must be approved & covered by the payer's balance
+        let registry = contract.env().account_id();
         assert_eq!(
-            contract.update_records(name_name.clone(), records.clone(), false),
-            Ok(())
+            contract.register_with_token(name.clone(), accounts.alice, 1, None, None, token),
+            Err(Error::InsufficientAllowance)
         );
+        mock_psp22::approve(token, accounts.alice, registry, 10_000);
         assert_eq!(
-            contract.get_record(name_name.clone(), key.clone()).unwrap(),
-            value
+            contract.register_with_token(name.clone(), accounts.alice, 1, None, None, token),
+            Err(Error::TokenTransferFailed(PSP22Error::InsufficientBalance))
         );
+        mock_psp22::mint(token, accounts.alice, 10_000);
 
-        /* Confirm idempotency */
         assert_eq!(
-            contract.update_records(name_name.clone(), records, true),
+            contract.register_with_token(name.clone(), accounts.alice, 1, None, None, token),
             Ok(())
         );
-        assert_eq!(contract.get_record(name_name.clone(), key).unwrap(), value);
+        assert_eq!(mock_psp22::balance_of(token, registry), 500);
+        assert_eq!(contract.get_owner(name.clone()), Ok(accounts.alice));
 
-        /* Confirm overwriting */
         assert_eq!(
-            contract.update_records(
-                name_name.clone(),
-                Vec::from([("twitter".to_string(), Some("@newtest".to_string()))]),
-                false,
+            contract.renew_with_token(name.clone(), 2, None, token),
+            Ok(500)
+        );
+        assert_eq!(
+            contract.batch_renew_with_token(
+                vec![(name.clone(), 1, None), (name.clone(), 1, None)],
+                token
             ),
-            Ok(())
+            Ok(1000)
         );
+
+        // Token is no longer accepted once removed
+        assert_eq!(contract.set_token_rate(token, None), Ok(()));
         assert_eq!(
-            contract.get_all_records(name_name),
-            Vec::from([("twitter".to_string(), "@newtest".to_string())])
+            contract.renew_with_token(name.clone(), 1, None, token),
+            Err(Error::TokenNotAccepted)
+        );
+
+        // Only treasurers can withdraw tokens
+        set_next_caller(accounts.bob);
+        assert_eq!(
+            contract.withdraw_token(token, None, Some(100)),
+            Err(Error::MissingRole(Role::Treasurer))
         );
+        set_next_caller(accounts.alice);
+        assert_eq!(contract.withdraw_token(token, None, Some(100)), Ok(()));
+        assert_eq!(mock_psp22::balance_of(token, registry), 1900);
     }
 
     #[ink::test]
-    fn set_record_works() {
+    fn referral_rate_works() {
         let accounts = default_accounts();
-        let key = String::from("twitter");
-        let value = String::from("@test");
-
-        let name_name = "test".to_string();
+        let mut contract = get_test_name_service();
+        let referrer = "alice".to_string();
 
         set_next_caller(accounts.alice);
-        let mut contract = get_test_name_service();
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register(referrer.clone(), 1, None, None, false)
+            .unwrap();
 
-        set_value_transferred::<DefaultEnvironment>(1000);
+        // Invalid configs are rejected
         assert_eq!(
-            contract.register(name_name.clone(), 1, None, None, false),
-            Ok(())
+            contract.set_referral_rate(Some(MAX_BPS + 1), vec![]),
+            Err(Error::InvalidReferralConfig)
         );
-
         assert_eq!(
-            contract.update_records(
-                name_name.clone(),
-                vec![(key.clone(), Some(value.clone()))],
-                false,
-            ),
-            Ok(())
+            contract.set_referral_rate(Some(1000), vec![(2, 2000), (1, 1500)]),
+            Err(Error::InvalidReferralConfig)
         );
+        set_next_caller(accounts.bob);
         assert_eq!(
-            contract.get_record(name_name.clone(), key.clone()).unwrap(),
-            value
+            contract.set_referral_rate(Some(1000), vec![]),
+            Err(Error::MissingRole(Role::PricingManager))
         );
 
-        /* Confirm idempotency */
+        set_next_caller(accounts.alice);
         assert_eq!(
-            contract.update_records(
-                name_name.clone(),
-                vec![(key.clone(), Some(value.clone()))],
-                false,
-            ),
+            contract.set_referral_rate(Some(1000), vec![(2, 2000)]),
             Ok(())
         );
-        assert_eq!(contract.get_record(name_name.clone(), key).unwrap(), value);
+        assert_eq!(contract.get_referral_rate(referrer.clone()), Some(1000));
 
-        /* Confirm overwriting */
+        // Rewards & stats accrue per referrer
+        set_account_balance::<DefaultEnvironment>(accounts.bob, 10_000);
+        set_next_caller(accounts.bob);
+        for name in ["bob-1", "bob-2"] {
+            transfer_in::<DefaultEnvironment>(900);
+            contract
+                .register(name.to_string(), 1, Some(referrer.clone()), None, false)
+                .unwrap();
+        }
+        assert_eq!(contract.get_referrer_stats(referrer.clone()), (2, 200));
+        assert_eq!(contract.get_referral_rewards(accounts.alice, None), 200);
+        assert_eq!(contract.get_escrowed_balance(), 200);
+
+        // Referrer qualifies for the higher tier
+        assert_eq!(contract.get_referral_rate(referrer.clone()), Some(2000));
         assert_eq!(
-            contract.update_records(
-                name_name.clone(),
-                vec![("twitter".to_string(), Some("@newtest".to_string()))],
-                false,
-            ),
-            Ok(())
+            contract.get_name_price("bob-3".to_string(), accounts.bob, 1, Some(referrer.clone())),
+            Ok((1000, 0, 200, Some(accounts.alice)))
         );
+
+        // Rewards are claimable once
         assert_eq!(
-            contract.get_all_records(name_name),
-            Vec::from([("twitter".to_string(), "@newtest".to_string())])
+            contract.claim_referral_rewards(None),
+            Err(Error::NothingToClaim)
+        );
+        set_next_caller(accounts.alice);
+        assert_eq!(contract.claim_referral_rewards(None), Ok(200));
+        assert_eq!(
+            contract.claim_referral_rewards(None),
+            Err(Error::NothingToClaim)
         );
+        assert_eq!(contract.get_escrowed_balance(), 0);
+
+        // Legacy rate is restored when unset
+        assert_eq!(contract.set_referral_rate(None, vec![]), Ok(()));
+        assert_eq!(contract.get_referral_rate(referrer), None);
     }
 
     #[ink::test]
-    fn update_records_works() {
-        let name = "test".to_string();
+    fn roles_works() {
+        let accounts = default_accounts();
         let mut contract = get_test_name_service();
 
-        set_value_transferred::<DefaultEnvironment>(1000);
-        contract
-            .register(name.clone(), 1, None, None, false)
-            .unwrap();
+        // Admin holds all the roles initially
+        assert!(Role::ALL
+            .iter()
+            .all(|role| contract.has_role(*role, accounts.alice)));
 
-        // add initial records values
+        // Only admin can grant & revoke roles
+        set_next_caller(accounts.bob);
         assert_eq!(
-            contract.update_records(
-                name.clone(),
-                vec![
-                    ("@facebook".to_string(), Some("alice_zuk".to_string())),
-                    ("@instagram".to_string(), Some("alice_zuk".to_string())),
-                    ("@twitter".to_string(), Some("alice_musk".to_string())),
-                ],
-                true
-            ),
-            Ok(())
+            contract.grant_role(Role::ConfigManager, accounts.bob),
+            Err(Error::NotAdmin)
         );
         assert_eq!(
-            contract.get_all_records(name.clone()),
-            vec![
-                ("@facebook".to_string(), "alice_zuk".to_string()),
-                ("@instagram".to_string(), "alice_zuk".to_string()),
-                ("@twitter".to_string(), "alice_musk".to_string()),
-            ]
+            contract.upgrade_contract([0u8; 32]),
+            Err(Error::MissingRole(Role::Upgrader))
         );
 
-        // add 1 new record
-        // remove 1 existing record
-        // update 1 existing record
+        set_next_caller(accounts.alice);
         assert_eq!(
-            contract.update_records(
-                name.clone(),
-                vec![
-                    ("@reddit".to_string(), Some("alice_tut".to_string())),
-                    ("@instagram".to_string(), None),
-                    ("@twitter".to_string(), Some("elon_musk".to_string()))
-                ],
-                false,
-            ),
+            contract.grant_role(Role::ConfigManager, accounts.bob),
             Ok(())
         );
+
+        // Admin holds all the roles implicitly
+        assert_eq!(contract.renounce_role(Role::ConfigManager), Ok(()));
+        assert!(contract.has_role(Role::ConfigManager, accounts.alice));
+        assert_eq!(contract.set_records_size_limit(None), Ok(()));
+
+        // Roles are independent of each other
+        set_next_caller(accounts.bob);
+        assert_eq!(contract.set_records_size_limit(None), Ok(()));
         assert_eq!(
-            contract.get_all_records(name.clone()),
-            vec![
-                ("@facebook".to_string(), "alice_zuk".to_string()),
-                ("@reddit".to_string(), "alice_tut".to_string()),
-                ("@twitter".to_string(), "elon_musk".to_string()),
-            ]
+            contract.withdraw(None, None),
+            Err(Error::MissingRole(Role::Treasurer))
         );
 
-        // add a record with flag: remove_rest
+        set_next_caller(accounts.alice);
         assert_eq!(
-            contract.update_records(
-                name.clone(),
-                vec![("@field".to_string(), Some("alice_tut".to_string()))],
-                true,
-            ),
+            contract.revoke_role(Role::ConfigManager, accounts.bob),
             Ok(())
         );
-        assert_eq!(
-            contract.get_all_records(name.clone()),
-            vec![("@field".to_string(), "alice_tut".to_string())],
-        );
+        assert!(!contract.has_role(Role::ConfigManager, accounts.bob));
+
+        // Roles follow the admin on ownership transfer
+        contract.transfer_ownership(Some(accounts.bob)).unwrap();
+        set_next_caller(accounts.bob);
+        contract.accept_ownership().unwrap();
+        assert!(Role::ALL
+            .iter()
+            .all(|role| contract.has_role(*role, accounts.bob)));
+        assert!(!contract.has_role(Role::Upgrader, accounts.alice));
     }
 
     #[ink::test]
-    fn records_limit_works() {
+    fn pause_works() {
+        let accounts = default_accounts();
         let mut contract = get_test_name_service();
         let name = "alice".to_string();
-        let records = vec![
-            ("@twitter".to_string(), Some("alice_musk".to_string())),
-            ("@facebook".to_string(), Some("alice_zuk".to_string())),
-            ("@instagram".to_string(), Some("alice_zuk".to_string())),
-        ];
-
-        contract.set_records_size_limit(Some(41)).unwrap();
-        assert_eq!(contract.get_records_size_limit(), Some(41));
 
-        set_value_transferred::<DefaultEnvironment>(1000);
+        set_next_caller(accounts.alice);
+        transfer_in::<DefaultEnvironment>(1000);
         contract
             .register(name.clone(), 1, None, None, false)
             .unwrap();
 
-        // With current input, records cannot be stored simultaneously
+        // Only pausers can pause
+        set_next_caller(accounts.bob);
         assert_eq!(
-            contract.update_records(name.clone(), records.clone(), false),
-            Err(Error::RecordsOverflow)
+            contract.pause(PauseGroup::Records),
+            Err(Error::MissingRole(Role::Pauser))
         );
 
-        // Storing only one works
+        set_next_caller(accounts.alice);
+        assert_eq!(contract.pause(PauseGroup::Records), Ok(()));
+        assert_eq!(contract.pause(PauseGroup::Transfers), Ok(()));
+        assert!(contract.is_paused(PauseGroup::Records));
+        assert!(!contract.is_paused(PauseGroup::Registration));
+
         assert_eq!(
-            contract.update_records(name.clone(), records[0..1].to_vec(), true),
-            Ok(())
+            contract.set_address(name.clone(), accounts.bob),
+            Err(Error::GroupPaused(PauseGroup::Records))
+        );
+        assert_eq!(
+            contract.update_records(name.clone(), vec![], true),
+            Err(Error::GroupPaused(PauseGroup::Records))
+        );
+        assert_eq!(
+            contract.transfer(accounts.bob, name.clone(), false, false, false, vec![]),
+            Err(PSP34Error::Custom("Transfers are paused".to_string()))
         );
 
-        // Adding the second record fails
+        // Other groups & read-only resolution keep working
+        transfer_in::<DefaultEnvironment>(1000);
+        assert!(contract.renew(name.clone(), 1, None).is_ok());
+        assert_eq!(contract.get_address(name.clone()), Ok(accounts.alice));
+
+        assert_eq!(contract.pause(PauseGroup::Registration), Ok(()));
+        transfer_in::<DefaultEnvironment>(1000);
         assert_eq!(
-            contract.update_records(name.clone(), records[1..3].to_vec(), false),
-            Err(Error::RecordsOverflow),
+            contract.register("bob".to_string(), 1, None, None, false),
+            Err(Error::GroupPaused(PauseGroup::Registration))
         );
+
+        assert_eq!(contract.unpause(PauseGroup::Records), Ok(()));
+        assert!(!contract.is_paused(PauseGroup::Records));
+        assert_eq!(contract.set_address(name.clone(), accounts.bob), Ok(()));
     }
 
     #[ink::test]
-    fn add_reserved_names_works() {
+    fn revenue_split_works() {
         let accounts = default_accounts();
         let mut contract = get_test_name_service();
+        let referrer = "alice".to_string();
 
-        let reserved_name = String::from("AlephZero");
-        let list = vec![(reserved_name.clone(), Some(accounts.alice))];
+        // Revenue is left to the treasury until the shares are configured
+        set_next_caller(accounts.alice);
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register(referrer.clone(), 1, None, None, false)
+            .unwrap();
+        assert_eq!(contract.get_lifetime_revenue(), 1000);
 
-        assert!(contract.add_reserved_names(list, false).is_ok());
+        // Invalid shares are rejected
+        assert_eq!(
+            contract.set_revenue_shares(vec![(accounts.charlie, 6000), (accounts.django, 5000)]),
+            Err(Error::InvalidRevenueShares)
+        );
+        assert_eq!(
+            contract.set_revenue_shares(vec![(accounts.charlie, 10), (accounts.charlie, 10)]),
+            Err(Error::InvalidRevenueShares)
+        );
+        set_next_caller(accounts.bob);
+        assert_eq!(
+            contract.set_revenue_shares(vec![]),
+            Err(Error::MissingRole(Role::Treasurer))
+        );
+
+        set_next_caller(accounts.alice);
+        let shares = vec![(accounts.charlie, 6000), (accounts.django, 3000)];
+        assert_eq!(contract.set_revenue_shares(shares.clone()), Ok(()));
+        assert_eq!(contract.get_revenue_shares(), shares);
+        contract.set_referral_rate(Some(1000), vec![]).unwrap();
+
+        // Revenue (net of the referral reward) is split as it arrives
+        set_account_balance::<DefaultEnvironment>(accounts.bob, 10_000);
+        set_next_caller(accounts.bob);
+        transfer_in::<DefaultEnvironment>(900);
+        contract
+            .register("bob".to_string(), 1, Some(referrer), None, false)
+            .unwrap();
+
+        assert_eq!(contract.get_lifetime_revenue(), 1800);
+        assert_eq!(contract.get_referral_payouts(), 100);
+        assert_eq!(contract.get_claimable_revenue(accounts.charlie), 480);
+        assert_eq!(contract.get_claimable_revenue(accounts.django), 240);
+
+        // Beneficiaries pull their share
+        set_next_caller(accounts.charlie);
+        let balance_before = get_account_balance::<DefaultEnvironment>(accounts.charlie).unwrap();
+        assert_eq!(contract.claim(), Ok(480));
+        let balance_after = get_account_balance::<DefaultEnvironment>(accounts.charlie).unwrap();
+        assert_eq!(balance_after, balance_before + 480);
+        assert_eq!(contract.claim(), Err(Error::NothingToClaim));
+        assert_eq!(contract.get_withdrawn_revenue(accounts.charlie), 480);
+
+        // Treasury withdrawals are accounted as well
+        set_next_caller(accounts.alice);
+        assert_eq!(contract.withdraw(None, Some(100)), Ok(()));
+        assert_eq!(contract.get_withdrawn_revenue(accounts.alice), 100);
+        assert_eq!(contract.get_total_withdrawn_revenue(), 580);
+        assert_eq!(contract.get_claimable_revenue(accounts.django), 240);
+    }
+
+    #[ink::test]
+    fn release_refund_works() {
+        let accounts = default_accounts();
+        let mut contract = get_test_name_service();
+        let policy = RefundPolicy {
+            share: MAX_BPS / 2,
+            min_holding_period: YEAR / 2,
+            as_credit: false,
+        };
+
+        set_next_caller(accounts.alice);
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register("alice".to_string(), 3, None, None, false)
+            .unwrap();
+
+        // No refunds without a policy
+        set_block_timestamp::<DefaultEnvironment>(YEAR);
+        assert_eq!(contract.get_release_refund("alice".to_string()), 0);
 
+        let invalid_policy = RefundPolicy {
+            share: MAX_BPS + 1,
+            ..policy.clone()
+        };
         assert_eq!(
-            contract.get_name_status(vec![reserved_name]),
-            vec![NameStatus::Reserved(Some(accounts.alice))],
+            contract.set_refund_policy(Some(invalid_policy)),
+            Err(Error::InvalidRefundPolicy)
         );
+        assert_eq!(contract.set_refund_policy(Some(policy.clone())), Ok(()));
+        assert_eq!(contract.get_refund_policy(), Some(policy.clone()));
 
-        // Cannot reserve already registered-name
-        let name = "alice".to_string();
-        set_value_transferred::<DefaultEnvironment>(1000);
+        // Half of the fee paid for the unused 2 (out of 3) years is refunded
+        assert_eq!(contract.get_release_refund("alice".to_string()), 333);
+        let balance_before = get_account_balance::<DefaultEnvironment>(accounts.alice).unwrap();
+        assert_eq!(contract.release("alice".to_string()), Ok(()));
+        let balance_after = get_account_balance::<DefaultEnvironment>(accounts.alice).unwrap();
+        assert_eq!(balance_after, balance_before + 333);
+
+        // Names obtained for free are not refunded
         contract
-            .register(name.clone(), 1, None, None, false)
+            .add_reserved_names(vec![("reserved".to_string(), Some(accounts.alice))], false)
             .unwrap();
-        assert_eq!(
-            contract.add_reserved_names(vec![(name, None)], false),
-            Err(Error::NameAlreadyExists)
-        );
+        contract
+            .claim_reserved_name("reserved".to_string(), false)
+            .unwrap();
+        set_block_timestamp::<DefaultEnvironment>(YEAR + YEAR / 2);
+        assert_eq!(contract.get_release_refund("reserved".to_string()), 0);
+        set_block_timestamp::<DefaultEnvironment>(YEAR);
 
-        // Invocation from non-admin address fails
+        // Names must be held for the minimum period
+        set_account_balance::<DefaultEnvironment>(accounts.bob, 10_000);
         set_next_caller(accounts.bob);
-        assert_eq!(
-            contract.add_reserved_names(vec![], false),
-            Err(Error::NotAdmin)
-        );
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register("bob".to_string(), 2, None, None, false)
+            .unwrap();
+        assert_eq!(contract.get_release_refund("bob".to_string()), 0);
+
+        // Refunds can be credited towards future registrations instead
+        set_next_caller(accounts.alice);
+        let credit_policy = RefundPolicy {
+            share: MAX_BPS,
+            as_credit: true,
+            ..policy
+        };
+        contract.set_refund_policy(Some(credit_policy)).unwrap();
+
+        set_block_timestamp::<DefaultEnvironment>(2 * YEAR);
+        set_next_caller(accounts.bob);
+        assert_eq!(contract.release("bob".to_string()), Ok(()));
+        assert_eq!(contract.get_registration_credit(accounts.bob), 500);
+
+        // The credit doesn't cover the referral reward
+        set_next_caller(accounts.alice);
+        contract.set_referral_rate(Some(4000), vec![]).unwrap();
+        set_next_caller(accounts.bob);
+        transfer_in::<DefaultEnvironment>(400);
+        contract
+            .register(
+                "bob-2".to_string(),
+                1,
+                Some("reserved".to_string()),
+                None,
+                false,
+            )
+            .unwrap();
+        assert_eq!(contract.get_registration_credit(accounts.bob), 300);
+        assert_eq!(contract.get_referral_rewards(accounts.alice, None), 400);
     }
 
     #[ink::test]
-    fn remove_reserved_names_works() {
+    fn release_refund_excludes_credit_and_token_payments() {
         let accounts = default_accounts();
         let mut contract = get_test_name_service();
+        let token = accounts.django;
+        let registry = contract.env().account_id();
+        let policy = RefundPolicy {
+            share: MAX_BPS,
+            min_holding_period: 0,
+            as_credit: true,
+        };
+        contract.set_refund_policy(Some(policy.clone())).unwrap();
 
-        let reserved_name = String::from("AlephZero");
-        let list = vec![(reserved_name.clone(), Some(accounts.alice))];
-        assert!(contract.add_reserved_names(list, false).is_ok());
+        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP);
+        set_next_caller(accounts.alice);
+        transfer_in::<DefaultEnvironment>(1000);
+        contract
+            .register("alice-1".to_string(), 1, None, None, false)
+            .unwrap();
+        contract.release("alice-1".to_string()).unwrap();
+        assert_eq!(contract.get_registration_credit(accounts.alice), 1000);
 
-        assert_eq!(
-            contract.get_name_status(vec![reserved_name.clone()]),
-            vec![NameStatus::Reserved(Some(accounts.alice))],
-        );
+        // Fees covered by the registration credit are not refunded
+        transfer_in::<DefaultEnvironment>(0);
+        contract
+            .register("alice-2".to_string(), 1, None, None, false)
+            .unwrap();
+        assert_eq!(contract.get_registration_credit(accounts.alice), 0);
+        assert_eq!(contract.get_release_refund("alice-2".to_string()), 0);
+
+        let native_policy = RefundPolicy {
+            as_credit: false,
+            ..policy
+        };
+        contract.set_refund_policy(Some(native_policy)).unwrap();
+
+        // Fees paid in tokens are not refunded either, but the time they paid for dilutes the refund
+        contract.set_token_rate(token, Some((1, 2))).unwrap();
+        mock_psp22::approve(token, accounts.alice, registry, 10_000);
+        mock_psp22::mint(token, accounts.alice, 10_000);
+        contract
+            .register_with_token("alice-3".to_string(), accounts.alice, 2, None, None, token)
+            .unwrap();
+        assert_eq!(contract.get_release_refund("alice-3".to_string()), 0);
 
-        assert!(contract
-            .remove_reserved_name(vec![reserved_name.clone()])
-            .is_ok());
+        transfer_in::<DefaultEnvironment>(1000);
+        contract.renew("alice-3".to_string(), 1, None).unwrap();
+        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP + YEAR);
+        assert_eq!(contract.get_release_refund("alice-3".to_string()), 666);
 
-        assert_ne!(
-            contract.get_name_status(vec![reserved_name]),
-            vec![NameStatus::Reserved(Some(accounts.alice))],
-        );
+        contract
+            .renew_with_token("alice-3".to_string(), 3, None, token)
+            .unwrap();
+        assert_eq!(contract.get_release_refund("alice-3".to_string()), 833);
 
-        // Invocation from non-admin address fails
-        set_next_caller(accounts.bob);
-        assert_eq!(contract.remove_reserved_name(vec![]), Err(Error::NotAdmin));
+        let balance_before = get_account_balance::<DefaultEnvironment>(accounts.alice).unwrap();
+        assert_eq!(contract.release("alice-3".to_string()), Ok(()));
+        let balance_after = get_account_balance::<DefaultEnvironment>(accounts.alice).unwrap();
+        assert_eq!(balance_after, balance_before + 833);
     }
 
     #[ink::test]
-    fn claim_reserved_name_works() {
+    fn batch_register_works() {
         let accounts = default_accounts();
         let mut contract = get_test_name_service();
 
-        let name = String::from("bob");
-        let reserved_list = vec![(name.clone(), Some(accounts.bob))];
+        set_next_caller(accounts.alice);
+        transfer_in::<DefaultEnvironment>(1000);
         contract
-            .add_reserved_names(reserved_list, false)
-            .expect("Failed to add reserved name");
+            .register("taken".to_string(), 1, None, None, false)
+            .unwrap();
+        contract
+            .add_reserved_names(vec![("reserved".to_string(), None)], false)
+            .unwrap();
 
-        // Non-reserved name cannot be claimed
+        let entries = vec![
+            ("bob-1".to_string(), accounts.bob, 1, None),
+            ("taken".to_string(), accounts.bob, 1, None),
+            ("reserved".to_string(), accounts.bob, 1, None),
+            ("bob-2".to_string(), accounts.charlie, 2, None),
+        ];
+
+        // All-or-nothing mode fails on the first invalid entry
+        set_account_balance::<DefaultEnvironment>(accounts.bob, 10_000);
+        set_next_caller(accounts.bob);
+        transfer_in::<DefaultEnvironment>(4000);
         assert_eq!(
-            contract.claim_reserved_name("abcd".to_string(), false),
-            Err(Error::NotReservedName),
+            contract.batch_register(entries.clone(), true),
+            Err(Error::CannotBuyReservedName)
         );
+        assert_eq!(contract.get_lifetime_revenue(), 1000);
 
-        // Non-authorised user cannot claim reserved name
+        // Best-effort mode skips the failed entries & charges only the registered ones
+        transfer_in::<DefaultEnvironment>(4000);
         assert_eq!(
-            contract.claim_reserved_name(name.clone(), false),
-            Err(Error::NotAuthorised),
+            contract.batch_register(entries, false),
+            Ok(vec![
+                Ok(()),
+                Err(Error::NameAlreadyExists),
+                Err(Error::CannotBuyReservedName),
+                Ok(()),
+            ])
+        );
+        assert_eq!(contract.get_lifetime_revenue(), 3000);
+        assert_eq!(contract.get_owner("bob-1".to_string()), Ok(accounts.bob));
+        assert_eq!(
+            contract.get_owner("bob-2".to_string()),
+            Ok(accounts.charlie)
         );
 
-        // Authorised user can claim name reserved for them
-        set_next_caller(accounts.bob);
-        assert!(contract.claim_reserved_name(name.clone(), false).is_ok());
-
-        let address_dict = AddressDict::new(accounts.bob);
+        // The payment must cover all the registered names
+        transfer_in::<DefaultEnvironment>(500);
         assert_eq!(
-            contract.get_name_status(vec![name]),
-            vec![NameStatus::Registered(address_dict, None)],
+            contract.batch_register(vec![("bob-3".to_string(), accounts.bob, 1, None)], true),
+            Err(Error::FeeNotPaid)
         );
     }
 
     #[ink::test]
-    fn get_name_status_works() {
+    fn lease_works() {
         let accounts = default_accounts();
-        let reserved_list = vec![("bob".to_string(), Some(accounts.bob))];
-
-        let mut contract = Registry::new(
-            default_accounts().alice,
-            None,
-            None,
-            "azero".to_string(),
-            "ipfs://05121999/".to_string(),
-        );
-
-        contract.add_reserved_names(reserved_list, false).unwrap();
+        let mut contract = get_test_name_service();
+        let name = "alice".to_string();
 
-        set_value_transferred::<DefaultEnvironment>(1000);
+        set_next_caller(accounts.alice);
+        transfer_in::<DefaultEnvironment>(1000);
         contract
-            .register("alice".to_string(), 1, None, None, false)
-            .expect("failed to register name");
+            .register(name.clone(), 2, None, None, false)
+            .unwrap();
+        let records = vec![("twitter".to_string(), "@alice".to_string())];
+        contract
+            .update_records(
+                name.clone(),
+                vec![("twitter".to_string(), Some("@alice".to_string()))],
+                true,
+            )
+            .unwrap();
+        contract
+            .set_controller(name.clone(), accounts.django)
+            .unwrap();
 
-        let address_dict = AddressDict::new(accounts.alice);
+        // Only the owner can lease a name, until at most its expiry
+        set_next_caller(accounts.bob);
         assert_eq!(
-            contract.get_name_status(vec!["alice".to_string()]),
-            vec![NameStatus::Registered(address_dict, None)]
+            contract.lease(name.clone(), accounts.bob, YEAR, 100),
+            Err(Error::CallerIsNotOwner)
         );
-
+        set_next_caller(accounts.alice);
         assert_eq!(
-            contract.get_name_status(vec!["bob".to_string()]),
-            vec![NameStatus::Reserved(Some(accounts.bob))]
+            contract.lease(name.clone(), accounts.bob, u64::MAX, 100),
+            Err(Error::InvalidLeaseExpiry)
         );
-
         assert_eq!(
-            contract.get_name_status(vec!["david".to_string()]),
-            vec![NameStatus::Available]
+            contract.lease(name.clone(), accounts.bob, YEAR, 100),
+            Ok(())
         );
-
         assert_eq!(
-            contract.get_name_status(vec!["".to_string()]),
-            vec![NameStatus::Unavailable]
+            contract.get_lease(name.clone()),
+            Some(Lease::new(accounts.bob, YEAR, 100))
         );
-    }
-
-    #[ink::test]
-    fn batch_get_name_price_works() {
-        let contract = get_test_name_service();
-        let inp = vec![
-            ("alice".to_string(), default_accounts().alice, 1, None),
-            ("bob".to_string(), default_accounts().bob, 2, None),
-        ];
 
+        // Only the tenant can accept the lease
+        set_next_caller(accounts.charlie);
         assert_eq!(
-            contract.batch_get_name_price(inp),
-            vec![Ok((1000, 0, 0, None)), Ok((1000, 0, 0, None))]
-        )
-    }
-
-    #[ink::test]
-    fn referral_system_works() {
-        let default_accounts = default_accounts();
-        let mut contract = get_test_name_service();
+            contract.accept_lease(name.clone()),
+            Err(Error::LeaseNotFound)
+        );
 
-        set_callee::<DefaultEnvironment>(default_accounts.eve);
-        assert_eq!(contract.env().account_id(), default_accounts.eve);
+        set_account_balance::<DefaultEnvironment>(accounts.bob, 10_000);
+        set_next_caller(accounts.bob);
+        transfer_in::<DefaultEnvironment>(100);
+        assert_eq!(contract.accept_lease(name.clone()), Ok(()));
+        assert_eq!(contract.get_controller(name.clone()), Ok(accounts.bob));
+        assert_eq!(contract.get_address(name.clone()), Ok(accounts.bob));
 
-        let alice = "alice".to_string();
-        let bob = "bob".to_string();
+        let address_dict = contract.get_address_dict(name.clone()).unwrap();
+        let lease = contract.get_lease(name.clone()).unwrap();
+        assert!(lease.started);
+        assert_eq!(
+            contract.get_name_status(vec![name.clone()]),
+            vec![NameStatus::Leased(address_dict, lease)]
+        );
 
-        // 1. Invalid referrer name gives no discount
-        let fees = 1000;
-        set_next_caller(default_accounts.alice);
-        set_account_balance::<DefaultEnvironment>(default_accounts.alice, fees);
-        set_callee::<DefaultEnvironment>(contract.env().account_id());
-        transfer_in::<DefaultEnvironment>(fees);
+        // The tenant controls the name, the owner cannot take it back
         assert_eq!(
-            contract.register(alice.clone(), 1, Some(bob.clone()), None, false),
+            contract.update_records(
+                name.clone(),
+                vec![("twitter".to_string(), Some("@bob".to_string()))],
+                true
+            ),
             Ok(())
         );
+        assert_eq!(
+            contract.register_subname(name.clone(), "pay".to_string(), accounts.bob, None),
+            Err(Error::NameLeased)
+        );
+        set_next_caller(accounts.alice);
+        assert_eq!(
+            contract.set_controller(name.clone(), accounts.alice),
+            Err(Error::CallerIsNotController)
+        );
+        assert_eq!(contract.release(name.clone()), Err(Error::NameLeased));
+        assert_eq!(
+            contract.transfer(accounts.charlie, name.clone(), false, false, false, vec![]),
+            Err(PSP34Error::Custom("Name is leased".to_string()))
+        );
+        assert_eq!(contract.end_lease(name.clone()), Err(Error::NameLeased));
 
-        let alice_balance =
-            get_account_balance::<DefaultEnvironment>(default_accounts.alice).unwrap();
-
-        // Initial Balance(alice): 1000
-        // Fee without discount: 1000
-        assert_eq!(alice_balance, 0);
-
-        // 2. Discount works
-        let discount = 49;
-        set_next_caller(default_accounts.bob);
-        set_account_balance::<DefaultEnvironment>(default_accounts.bob, fees);
-        transfer_in::<DefaultEnvironment>(fees - discount);
-        assert_eq!(contract.register(bob, 1, Some(alice), None, false), Ok(()));
-
-        let alice_balance =
-            get_account_balance::<DefaultEnvironment>(default_accounts.alice).unwrap();
-        let bob_balance = get_account_balance::<DefaultEnvironment>(default_accounts.bob).unwrap();
-
-        // Initial Balance (bob): 1000
-        // Fee after discount: 9951
-        assert_eq!(bob_balance, 49);
-
-        // Affiliation payment to alice
-        assert_eq!(alice_balance, 49);
-    }
-
-    #[ink::test]
-    fn self_referral_not_allowed() {
-        let default_accounts = default_accounts();
-        let mut contract = get_test_name_service();
-
-        set_callee::<DefaultEnvironment>(default_accounts.eve);
-        assert_eq!(contract.env().account_id(), default_accounts.eve);
-
-        let alice = "alice".to_string();
-        let wonderland = "wonderland".to_string();
+        // The control & records are restored once the lease is over
+        set_block_timestamp::<DefaultEnvironment>(YEAR + 1);
+        assert_eq!(contract.get_controller(name.clone()), Ok(accounts.django));
+        assert_eq!(contract.get_address(name.clone()), Ok(accounts.alice));
+        assert_eq!(contract.get_all_records(name.clone()), records);
 
-        // 1. Register first name without referrer
-        let fees = 1000;
-        set_next_caller(default_accounts.alice);
-        set_account_balance::<DefaultEnvironment>(default_accounts.alice, fees);
-        set_callee::<DefaultEnvironment>(contract.env().account_id());
-        transfer_in::<DefaultEnvironment>(fees);
+        set_next_caller(accounts.charlie);
+        assert_eq!(contract.end_lease(name.clone()), Ok(()));
+        assert_eq!(contract.get_lease(name.clone()), None);
+        assert_eq!(contract.get_all_records(name.clone()), records);
         assert_eq!(
-            contract.register(alice.clone(), 1, None, None, false),
-            Ok(())
+            contract.get_controlled_names_of_address(accounts.django),
+            vec![name.clone()]
+        );
+        assert_eq!(
+            contract.get_resolving_names_of_address(accounts.bob),
+            Vec::<String>::new()
         );
 
-        // 2. Self-referral doesn't work
-        set_account_balance::<DefaultEnvironment>(default_accounts.alice, fees);
-        transfer_in::<DefaultEnvironment>(fees);
+        set_next_caller(accounts.alice);
         assert_eq!(
-            contract.register(wonderland, 1, Some(alice), None, false),
+            contract.transfer(accounts.charlie, name.clone(), false, false, false, vec![]),
             Ok(())
         );
-
-        let alice_balance =
-            get_account_balance::<DefaultEnvironment>(default_accounts.alice).unwrap();
-
-        // No bonus received by alice
-        assert_eq!(alice_balance, 0);
     }
 
     #[ink::test]
-    fn validate_referrer_works() {
-        let default_accounts = default_accounts();
-        let mut contract = get_test_name_service();
+    fn voucher_works() {
+        use secp256k1::{Message, SecretKey, SECP256K1};
 
-        let name = "alice".to_string();
+        let accounts = default_accounts();
+        let mut contract = get_test_name_service();
 
-        // Invalid name -> fails
+        let secret_key = SecretKey::from_slice(&[7; 32]).unwrap();
+        let mut signer = [0u8; 32];
+        ink::env::hash_bytes::<ink::env::hash::Blake2x256>(
+            &secret_key.public_key(SECP256K1).serialize(),
+            &mut signer,
+        );
+        let signer = AccountId::from(signer);
+        let sign = |contract: &Registry, voucher: &Voucher| {
+            let hash = contract.get_voucher_hash(voucher.clone());
+            let (rec_id, sig) = SECP256K1
+                .sign_ecdsa_recoverable(&Message::from_slice(&hash).unwrap(), &secret_key)
+                .serialize_compact();
+            let mut signature = [0u8; 65];
+            signature[..64].copy_from_slice(&sig);
+            signature[64] = rec_id.to_i32() as u8;
+            signature
+        };
+
+        let voucher = Voucher {
+            recipient: accounts.bob,
+            name_length: (3, 5),
+            discount: 2500,
+            expiry: YEAR,
+            nonce: 1,
+        };
+        let signature = sign(&contract, &voucher);
+
+        // Vouchers are disabled until a signer is set
+        set_next_caller(accounts.alice);
         assert_eq!(
-            contract.validate_referrer(default_accounts.alice, name.clone()),
-            false
+            contract.register_with_voucher("bob".to_string(), 1, voucher.clone(), signature),
+            Err(Error::InvalidVoucher)
         );
+        assert_eq!(contract.set_voucher_signer(Some(signer)), Ok(()));
 
-        transfer_in::<DefaultEnvironment>(1000);
-        contract
-            .register(name.clone(), 1, None, None, false)
-            .unwrap();
-        contract
-            .set_controller(name.clone(), default_accounts.bob)
-            .unwrap();
-        contract
-            .set_address(name.clone(), default_accounts.eve)
-            .unwrap();
+        // Only the recipient can redeem the voucher
+        assert_eq!(
+            contract.register_with_voucher("bob".to_string(), 1, voucher.clone(), signature),
+            Err(Error::InvalidVoucher)
+        );
 
-        // owner: fails
+        // Names outside of the voucher's length range are rejected
+        set_account_balance::<DefaultEnvironment>(accounts.bob, 10_000);
+        set_next_caller(accounts.bob);
         assert_eq!(
-            contract.validate_referrer(default_accounts.alice, name.clone()),
-            false
+            contract.register_with_voucher("charlie".to_string(), 1, voucher.clone(), signature),
+            Err(Error::InvalidVoucher)
         );
 
-        // controller: fails
+        // Tampered vouchers are rejected
+        let tampered = Voucher {
+            discount: MAX_BPS,
+            ..voucher.clone()
+        };
         assert_eq!(
-            contract.validate_referrer(default_accounts.bob, name.clone()),
-            false
+            contract.register_with_voucher("bob".to_string(), 1, tampered, signature),
+            Err(Error::InvalidVoucher)
         );
 
-        // resolved: fails
+        transfer_in::<DefaultEnvironment>(750);
         assert_eq!(
-            contract.validate_referrer(default_accounts.eve, name.clone()),
-            false
+            contract.register_with_voucher("bob".to_string(), 1, voucher.clone(), signature),
+            Ok(())
         );
+        assert_eq!(contract.get_owner("bob".to_string()), Ok(accounts.bob));
+        assert!(contract.is_voucher_used(1));
 
-        // A new user: pass
+        // A voucher can only be used once
         assert_eq!(
-            contract.validate_referrer(default_accounts.charlie, name.clone()),
-            true
+            contract.register_with_voucher("eve".to_string(), 1, voucher, signature),
+            Err(Error::VoucherAlreadyUsed)
+        );
+
+        // Expired vouchers are rejected
+        let voucher = Voucher {
+            recipient: accounts.bob,
+            name_length: (3, 5),
+            discount: 2500,
+            expiry: YEAR,
+            nonce: 2,
+        };
+        let signature = sign(&contract, &voucher);
+        set_block_timestamp::<DefaultEnvironment>(YEAR + 1);
+        assert_eq!(
+            contract.register_with_voucher("eve".to_string(), 1, voucher, signature),
+            Err(Error::VoucherExpired)
         );
     }
 
     #[ink::test]
-    fn name_expiry_works() {
-        let mut contract = get_test_name_service();
+    fn meta_tx_works() {
+        use secp256k1::{Message, SecretKey, SECP256K1};
 
-        let name1 = "one-year".to_string();
-        let name2 = "two-year".to_string();
-
-        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP - YEAR);
-
-        // Register name1 for one year
-        transfer_in::<DefaultEnvironment>(1000);
-        contract
-            .register(name1.clone(), 1, None, None, true)
-            .unwrap();
-
-        // Register name2 for two years
+        let accounts = default_accounts();
+        let mut contract = get_test_name_service();
+        let name = "cold".to_string();
+
+        // A cold wallet identified by its ECDSA key
+        let secret_key = SecretKey::from_slice(&[7; 32]).unwrap();
+        let mut signer = [0u8; 32];
+        ink::env::hash_bytes::<ink::env::hash::Blake2x256>(
+            &secret_key.public_key(SECP256K1).serialize(),
+            &mut signer,
+        );
+        let signer = AccountId::from(signer);
+        let sign = |contract: &Registry, action: &MetaAction, nonce: u128, deadline: u64| {
+            let hash = contract.get_meta_tx_hash(action.clone(), nonce, deadline);
+            let (rec_id, sig) = SECP256K1
+                .sign_ecdsa_recoverable(&Message::from_slice(&hash).unwrap(), &secret_key)
+                .serialize_compact();
+            let mut signature = [0u8; 65];
+            signature[..64].copy_from_slice(&sig);
+            signature[64] = rec_id.to_i32() as u8;
+            signature
+        };
+
+        set_account_balance::<DefaultEnvironment>(signer, 10_000);
+        set_next_caller(signer);
         transfer_in::<DefaultEnvironment>(1000);
         contract
-            .register(name2.clone(), 2, None, None, false)
+            .register(name.clone(), 1, None, None, false)
             .unwrap();
 
-        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP);
+        // A relayer submits the signed actions
+        set_next_caller(accounts.bob);
+        let action = MetaAction::SetAddress {
+            name: name.clone(),
+            address: accounts.alice,
+        };
+        let signature = sign(&contract, &action, 0, YEAR);
+        assert_eq!(
+            contract.execute_meta_tx(action.clone(), 0, YEAR, signature),
+            Ok(())
+        );
+        assert_eq!(contract.get_address(name.clone()), Ok(accounts.alice));
+        assert_eq!(contract.get_meta_tx_nonce(signer), 1);
 
-        let address_dict = AddressDict::new(default_accounts().alice);
+        // Signatures cannot be replayed or reused with other parameters
         assert_eq!(
-            contract.get_name_status(vec![name1.clone(), name2.clone()]),
-            vec![
-                NameStatus::Available,
-                NameStatus::Registered(address_dict, None)
-            ]
+            contract.execute_meta_tx(action.clone(), 0, YEAR, signature),
+            Err(Error::InvalidNonce)
+        );
+        assert_eq!(
+            contract.execute_meta_tx(action, 1, YEAR, signature),
+            Err(Error::InvalidNonce)
         );
 
+        let action = MetaAction::UpdateRecords {
+            name: name.clone(),
+            records: vec![("twitter".to_string(), Some("@cold".to_string()))],
+            remove_rest: false,
+        };
+        let signature = sign(&contract, &action, 1, YEAR);
         assert_eq!(
-            contract.get_primary_name(default_accounts().alice),
-            Err(Error::NoResolvedAddress)
+            contract.execute_meta_tx(action.clone(), 1, YEAR + 1, signature),
+            Err(Error::InvalidNonce)
+        );
+        assert_eq!(
+            contract.execute_meta_tx(action.clone(), 1, YEAR, signature),
+            Ok(())
+        );
+        assert_eq!(
+            contract.get_record(name.clone(), "twitter".to_string()),
+            Ok("@cold".to_string())
         );
 
-        assert_eq!(contract.get_all_records(name1.clone()), vec![]);
+        let action = MetaAction::SetController {
+            name: name.clone(),
+            controller: accounts.charlie,
+        };
+        let signature = sign(&contract, &action, 2, YEAR);
+        set_block_timestamp::<DefaultEnvironment>(YEAR + 1);
+        assert_eq!(
+            contract.execute_meta_tx(action, 2, YEAR, signature),
+            Err(Error::DeadlineExpired)
+        );
 
-        // Reverse mapping implicitly excludes expired names
+        // The signer must control the name
+        let action = MetaAction::SetController {
+            name: "alice".to_string(),
+            controller: signer,
+        };
+        let signature = sign(&contract, &action, 2, 2 * YEAR);
         assert_eq!(
-            contract.get_names_of_address(default_accounts().alice),
-            vec![name2.clone()]
+            contract.execute_meta_tx(action, 2, 2 * YEAR, signature),
+            Err(Error::NameDoesntExist)
         );
     }
 
     #[ink::test]
-    fn clear_expired_names_works() {
-        let mut contract = get_test_name_service();
-
-        let name1 = "one-year".to_string();
-        let name2 = "two-year".to_string();
+    fn permit_works() {
+        use secp256k1::{Message, SecretKey, SECP256K1};
 
-        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP - YEAR);
-
-        // Register name1 for one year
-        transfer_in::<DefaultEnvironment>(1000);
-        contract
-            .register(name1.clone(), 1, None, None, true)
-            .unwrap();
-
-        // Register name2 for two years
+        let accounts = default_accounts();
+        let mut contract = get_test_name_service();
+        let name = "cold".to_string();
+        let id: Option<Id> = Some(name.clone().into());
+
+        let secret_key = SecretKey::from_slice(&[7; 32]).unwrap();
+        let mut owner = [0u8; 32];
+        ink::env::hash_bytes::<ink::env::hash::Blake2x256>(
+            &secret_key.public_key(SECP256K1).serialize(),
+            &mut owner,
+        );
+        let owner = AccountId::from(owner);
+        let sign = |hash: [u8; 32]| {
+            let (rec_id, sig) = SECP256K1
+                .sign_ecdsa_recoverable(&Message::from_slice(&hash).unwrap(), &secret_key)
+                .serialize_compact();
+            let mut signature = [0u8; 65];
+            signature[..64].copy_from_slice(&sig);
+            signature[64] = rec_id.to_i32() as u8;
+            signature
+        };
+
+        set_account_balance::<DefaultEnvironment>(owner, 10_000);
+        set_next_caller(owner);
         transfer_in::<DefaultEnvironment>(1000);
         contract
-            .register(name2.clone(), 2, None, None, false)
+            .register(name.clone(), 1, None, None, false)
             .unwrap();
 
-        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP);
-
-        // Only the expired names are cleared
+        // Anyone can submit the owner's permit
+        set_next_caller(accounts.bob);
+        let signature =
+            sign(contract.get_permit_hash(owner, accounts.charlie, id.clone(), 0, YEAR));
+        assert_eq!(
+            contract.permit(
+                accounts.alice,
+                accounts.charlie,
+                id.clone(),
+                YEAR,
+                signature
+            ),
+            Err(PSP34Error::Custom("Invalid signature".to_string()))
+        );
         assert_eq!(
-            contract.clear_expired_names(vec![name1.clone(), name2.clone()]),
-            Ok(1)
+            contract.permit(owner, accounts.charlie, id.clone(), YEAR, signature),
+            Ok(())
         );
+        assert!(contract.allowance(owner, accounts.charlie, id.clone()));
+        assert_eq!(contract.get_permit_nonce(owner), 1);
 
-        let address_dict = AddressDict::new(default_accounts().alice);
+        // A permit cannot be replayed
         assert_eq!(
-            contract.get_name_status(vec![name1.clone(), name2.clone()]),
-            vec![
-                NameStatus::Available,
-                NameStatus::Registered(address_dict, None)
-            ]
+            contract.permit(owner, accounts.charlie, id.clone(), YEAR, signature),
+            Err(PSP34Error::Custom("Invalid signature".to_string()))
         );
-    }
-
-    #[ink::test]
-    fn register_expired_names_works() {
-        let mut contract = get_test_name_service();
-
-        let name1 = "one-year".to_string();
-        let name2 = "two-year".to_string();
-
-        // Register name1 for one year
-        transfer_in::<DefaultEnvironment>(1000);
-        contract
-            .register(name1.clone(), 1, None, None, true)
-            .unwrap();
-
-        // Register name2 for two years
-        transfer_in::<DefaultEnvironment>(1000);
-        contract
-            .register(name2.clone(), 2, None, None, false)
-            .unwrap();
 
-        // Registering an active name causes error
-        set_next_caller(default_accounts().bob);
+        // The owner must own the token
+        let other_id: Option<Id> = Some("alice".to_string().into());
+        let signature =
+            sign(contract.get_permit_hash(owner, accounts.charlie, other_id.clone(), 1, YEAR));
         assert_eq!(
-            contract.register(name1.clone(), 1, None, None, false),
-            Err(Error::NameAlreadyExists)
+            contract.permit(owner, accounts.charlie, other_id, YEAR, signature),
+            Err(PSP34Error::NotApproved)
         );
 
-        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP);
+        let signature = sign(contract.get_permit_hash(owner, accounts.django, None, 1, YEAR));
+        set_block_timestamp::<DefaultEnvironment>(YEAR + 1);
+        assert_eq!(
+            contract.permit(owner, accounts.django, None, YEAR, signature),
+            Err(PSP34Error::Custom("Permit expired".to_string()))
+        );
 
-        // Registering an expired name works
+        // The operator can act on the permit
+        set_block_timestamp::<DefaultEnvironment>(YEAR / 2);
+        set_next_caller(accounts.charlie);
         assert_eq!(
-            contract.register(name1.clone(), 1, None, None, false),
+            contract.transfer(accounts.django, name.clone(), false, false, false, vec![]),
             Ok(())
         );
+        assert_eq!(contract.get_owner(name), Ok(accounts.django));
     }
 
     #[ink::test]
-    fn ownable_2_step_works() {
+    fn whitelist_phase_works() {
         let accounts = default_accounts();
         let mut contract = get_test_name_service();
+        let name = "alice".to_string();
+        let quota = WhitelistQuota {
+            max_names: 2,
+            max_years: 1,
+            discount: 5000,
+        };
+
+        // Leaves are the sha2 hash of the scale-encoded (account, max_names, max_years, discount)
+        let mut leaf = [0u8; 32];
+        let mut preimage = AsRef::<[u8]>::as_ref(&accounts.alice).to_vec();
+        preimage.extend(2u32.to_le_bytes());
+        preimage.extend([1u8]);
+        preimage.extend(5000u16.to_le_bytes());
+        ink::env::hash_bytes::<ink::env::hash::Sha2x256>(&preimage, &mut leaf);
+        assert_eq!(quota.leaf(accounts.alice), leaf);
+
+        assert!(!contract.is_whitelist_phase());
+        set_next_caller(accounts.bob);
+        assert_eq!(
+            contract.start_whitelist_phase(accounts.django),
+            Err(Error::MissingRole(Role::ReservationManager))
+        );
 
-        assert_eq!(contract.get_admin(), accounts.alice);
-        contract.transfer_ownership(Some(accounts.bob)).unwrap();
+        set_next_caller(accounts.alice);
+        assert_eq!(contract.start_whitelist_phase(accounts.django), Ok(()));
+        assert!(contract.is_whitelist_phase());
+        assert_eq!(
+            contract.start_whitelist_phase(accounts.django),
+            Err(Error::RestrictedDuringWhitelistPhase)
+        );
 
-        assert_eq!(contract.get_admin(), accounts.alice);
+        // Only whitelisted registrations are allowed
+        transfer_in::<DefaultEnvironment>(1000);
+        assert_eq!(
+            contract.register(name.clone(), 1, None, None, false),
+            Err(Error::InvalidMerkleProof)
+        );
+        assert_eq!(
+            contract.register_on_behalf_of(name.clone(), accounts.alice, 1, None, None),
+            Err(Error::RestrictedDuringWhitelistPhase)
+        );
+        assert_eq!(
+            contract.batch_register(vec![(name.clone(), accounts.alice, 1, None)], true),
+            Err(Error::RestrictedDuringWhitelistPhase)
+        );
+        assert_eq!(contract.get_whitelist_usage(accounts.alice), 0);
 
-        set_caller::<DefaultEnvironment>(accounts.bob);
-        contract.accept_ownership().unwrap();
-        assert_eq!(contract.get_admin(), accounts.bob);
+        // Public registrations resume with the public-phase
+        assert_eq!(contract.switch_to_public_phase(), Ok(()));
+        assert!(!contract.is_whitelist_phase());
+        assert_eq!(
+            contract.switch_to_public_phase(),
+            Err(Error::OnlyDuringWhitelistPhase)
+        );
+        assert_eq!(
+            contract.register(name.clone(), 1, None, None, false),
+            Ok(())
+        );
+        assert_eq!(contract.get_owner(name), Ok(accounts.alice));
     }
 
     #[ink::test]
-    fn grace_period_works() {
+    fn launch_schedule_works() {
+        let accounts = default_accounts();
         let mut contract = get_test_name_service();
-        let name1 = "one-year".to_string();
-
-        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP - YEAR - 10);
+        let phase = |phase, start, price_multiplier, allowed| PhaseConfig {
+            phase,
+            start,
+            price_multiplier,
+            allowed,
+        };
+        let schedule = vec![
+            phase(
+                LaunchPhase::ReservedClaim,
+                10,
+                10_000,
+                vec![EntryPoint::ClaimReservedName],
+            ),
+            phase(
+                LaunchPhase::Allowlist,
+                20,
+                20_000,
+                vec![EntryPoint::Register],
+            ),
+            phase(
+                LaunchPhase::PublicSale,
+                30,
+                10_000,
+                vec![EntryPoint::Register, EntryPoint::BatchRegister],
+            ),
+        ];
 
-        // Register name1 for one year
-        transfer_in::<DefaultEnvironment>(1000);
+        set_next_caller(accounts.bob);
+        assert_eq!(
+            contract.set_launch_schedule(schedule.clone()),
+            Err(Error::MissingRole(Role::ConfigManager))
+        );
+        set_next_caller(accounts.alice);
+        assert_eq!(
+            contract.set_launch_schedule(schedule.iter().rev().cloned().collect()),
+            Err(Error::InvalidLaunchSchedule)
+        );
+        assert_eq!(contract.set_launch_schedule(schedule.clone()), Ok(()));
         contract
-            .register(name1.clone(), 1, None, None, true)
+            .add_reserved_names(vec![("bob".to_string(), Some(accounts.alice))], false)
             .unwrap();
 
-        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP - 1);
-        let address_dict = AddressDict::new(default_accounts().alice);
+        // Nothing is open before the first phase
         assert_eq!(
-            contract.get_name_status(vec![name1.clone()]),
-            vec![NameStatus::Registered(address_dict, None)]
+            contract.get_launch_phase(),
+            (None, Some(schedule[0].clone()))
         );
-
-        set_block_timestamp::<DefaultEnvironment>(GRACE_TIMESTAMP);
         assert_eq!(
-            contract.get_name_status(vec![name1.clone()]),
-            vec![NameStatus::Available]
+            contract.claim_reserved_name("bob".to_string(), false),
+            Err(Error::NotAllowedInCurrentPhase)
         );
-    }
 
-    #[ink::test]
-    fn redeem_bonus_name_works_with_register_works() {
-        let accounts = default_accounts();
-        let mut contract = get_test_name_service();
+        set_block_timestamp::<DefaultEnvironment>(10);
+        assert_eq!(
+            contract.get_launch_phase(),
+            (Some(schedule[0].clone()), Some(schedule[1].clone()))
+        );
+        transfer_in::<DefaultEnvironment>(2000);
+        assert_eq!(
+            contract.register("alice".to_string(), 1, None, None, false),
+            Err(Error::NotAllowedInCurrentPhase)
+        );
+        assert_eq!(
+            contract.claim_reserved_name("bob".to_string(), false),
+            Ok(())
+        );
 
-        let name1 = "name-1".to_string();
-        let name2 = "name-2".to_string();
-        let bonus_name = "name-3".to_string();
+        // Prices follow the multiplier of the current phase
+        set_block_timestamp::<DefaultEnvironment>(20);
+        assert_eq!(
+            contract.get_name_price("alice".to_string(), accounts.alice, 1, None),
+            Ok((2000, 0, 0, None))
+        );
+        assert_eq!(
+            contract.batch_register(vec![("alice".to_string(), accounts.alice, 1, None)], true),
+            Err(Error::RestrictedDuringWhitelistPhase)
+        );
 
-        set_next_caller(default_accounts().alice);
-        transfer_in::<DefaultEnvironment>(1000);
-        contract
-            .register_v2(name1.clone(), 1, None, Some(bonus_name.clone()), false)
-            .unwrap();
+        // Only whitelisted accounts can register during the allowlist phase
+        assert!(contract.is_whitelist_phase());
+        assert_eq!(
+            contract.register("alice".to_string(), 1, None, None, false),
+            Err(Error::InvalidMerkleProof)
+        );
 
+        set_block_timestamp::<DefaultEnvironment>(30);
+        assert!(!contract.is_whitelist_phase());
         assert_eq!(
-            contract.get_owner(bonus_name.clone()),
-            Err(Error::NameDoesntExist)
+            contract.register("alice".to_string(), 1, None, None, false),
+            Ok(())
+        );
+        assert_eq!(
+            contract.get_launch_phase(),
+            (Some(schedule[2].clone()), None)
+        );
+        assert_eq!(
+            contract.get_name_price("charlie".to_string(), accounts.alice, 1, None),
+            Ok((1000, 0, 0, None))
         );
 
-        contract
-            .register_v2(name2, 10, None, Some(bonus_name.clone()), false)
-            .unwrap();
-        assert_eq!(contract.get_owner(bonus_name), Ok(accounts.alice))
+        // An empty schedule lifts all the restrictions
+        assert_eq!(contract.set_launch_schedule(vec![]), Ok(()));
+        assert_eq!(contract.get_launch_phase(), (None, None));
     }
 
     #[ink::test]
-    fn redeem_bonus_name_works_with_renewal_works() {
+    fn scheduled_public_sale_ends_whitelist_phase() {
+        type Event = <Registry as ink::reflect::ContractEventBase>::Type;
+
         let accounts = default_accounts();
         let mut contract = get_test_name_service();
+        let phase = |phase, start| PhaseConfig {
+            phase,
+            start,
+            price_multiplier: 10_000,
+            allowed: vec![EntryPoint::Register],
+        };
+        let public_phase_events = || {
+            recorded_events()
+                .filter(|event| {
+                    matches!(
+                        <Event as scale::Decode>::decode(&mut &event.data[..]),
+                        Ok(Event::PublicPhaseActivated(_))
+                    )
+                })
+                .count()
+        };
 
-        let name1 = "name-1".to_string();
-        let name2 = "name-2".to_string();
-
-        set_next_caller(default_accounts().alice);
-        transfer_in::<DefaultEnvironment>(1000);
+        set_next_caller(accounts.alice);
+        contract.start_whitelist_phase(accounts.django).unwrap();
         contract
-            .register(name1.clone(), 1, None, None, true)
+            .set_launch_schedule(vec![
+                phase(LaunchPhase::Allowlist, 10),
+                phase(LaunchPhase::PublicSale, 20),
+            ])
             .unwrap();
 
-        contract
-            .renew(name1.clone(), 9, Some(name2.clone()))
-            .unwrap();
+        set_block_timestamp::<DefaultEnvironment>(10);
+        assert!(contract.is_whitelist_phase());
+        transfer_in::<DefaultEnvironment>(1000);
         assert_eq!(
-            contract.get_owner(name2.clone()),
-            Err(Error::NameDoesntExist)
+            contract.register_on_behalf_of("alice".to_string(), accounts.bob, 1, None, None),
+            Err(Error::RestrictedDuringWhitelistPhase)
         );
 
-        contract.renew(name1, 10, Some(name2.clone())).unwrap();
-        assert_eq!(contract.get_owner(name2), Ok(accounts.alice))
-    }
-
-    #[ink::test]
-    fn redeem_bonus_name_fails_on_invalid_name() {
-        let mut contract = get_test_name_service();
-        let name1 = "name-1".to_string();
-        let name2 = "name-2".to_string();
-
-        set_next_caller(default_accounts().alice);
-        transfer_in::<DefaultEnvironment>(1000);
-
-        // Bonus names not passing the name-checker fails
-        let empty_name = String::new();
+        // The public sale opens on schedule, even though the verifier is still set
+        set_block_timestamp::<DefaultEnvironment>(20);
+        assert!(!contract.is_whitelist_phase());
+        assert_eq!(public_phase_events(), 1);
         assert_eq!(
-            contract.register_v2(name1.clone(), 10, None, Some(empty_name.clone()), false),
-            Err(Error::NameNotAllowed)
+            contract.register_on_behalf_of("alice".to_string(), accounts.bob, 1, None, None),
+            Ok(())
         );
+        assert_eq!(contract.get_owner("alice".to_string()), Ok(accounts.bob));
 
-        // Reserved names cannot be redeemed as bonus
-        let reserved_name = String::from("bobby");
-        let reserved_list = vec![(reserved_name.clone(), None)];
-        contract
-            .add_reserved_names(reserved_list, false)
-            .expect("Failed to add reserved name");
-
+        // The whitelist-phase is ended along the way
+        assert_eq!(public_phase_events(), 2);
         assert_eq!(
-            contract.register_v2(name2.clone(), 10, None, Some(reserved_name.clone()), false),
-            Err(Error::CannotBuyReservedName)
+            contract.switch_to_public_phase(),
+            Err(Error::OnlyDuringWhitelistPhase)
         );
     }
 }
@@ -0,0 +1,32 @@
+use ink::primitives::AccountId;
+
+type Balance = u128;
+
+/// A fixed-price sale offer of a name on the native marketplace
+#[derive(scale::Encode, scale::Decode, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout, Debug)
+)]
+pub struct Listing {
+    /// Owner of the name at the time of listing
+    pub seller: AccountId,
+    /// Asking price (incl. protocol fee)
+    pub price: Balance,
+    /// Timestamp after which the listing can no longer be bought
+    pub expiry: u64,
+}
+
+impl Listing {
+    pub fn new(seller: AccountId, price: Balance, expiry: u64) -> Self {
+        Self {
+            seller,
+            price,
+            expiry,
+        }
+    }
+
+    pub fn is_active(&self, current_time: u64) -> bool {
+        current_time <= self.expiry
+    }
+}
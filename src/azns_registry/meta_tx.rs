@@ -0,0 +1,21 @@
+use ink::prelude::string::String;
+use ink::prelude::vec::Vec;
+use ink::primitives::AccountId;
+
+/// An action relayed on behalf of a name's owner or controller
+#[derive(scale::Encode, scale::Decode, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, Debug))]
+pub enum MetaAction {
+    /// `set_address(name, address)`
+    SetAddress { name: String, address: AccountId },
+    /// `set_controller(name, controller)`
+    SetController { name: String, controller: AccountId },
+    /// `update_records(name, records, remove_rest)`
+    UpdateRecords {
+        name: String,
+        records: Vec<(String, Option<String>)>,
+        remove_rest: bool,
+    },
+    /// `set_primary_name(primary_name)`
+    SetPrimaryName { primary_name: Option<String> },
+}
@@ -0,0 +1,70 @@
+use ink::primitives::AccountId;
+use interfaces::psp22::PSP22Error;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+type Balance = u128;
+
+// In-memory PSP22 ledgers standing in for the token contracts during unit tests,
+// as cross-contract calls are not supported by the off-chain environment.
+// @dev Every test runs on its own thread, hence starts with empty ledgers
+thread_local! {
+    static BALANCES: RefCell<BTreeMap<(AccountId, AccountId), Balance>> = Default::default();
+    static ALLOWANCES: RefCell<BTreeMap<(AccountId, AccountId, AccountId), Balance>> =
+        Default::default();
+}
+
+pub fn balance_of(token: AccountId, owner: AccountId) -> Balance {
+    BALANCES.with(|b| b.borrow().get(&(token, owner)).copied().unwrap_or(0))
+}
+
+pub fn allowance(token: AccountId, owner: AccountId, spender: AccountId) -> Balance {
+    ALLOWANCES.with(|a| {
+        a.borrow()
+            .get(&(token, owner, spender))
+            .copied()
+            .unwrap_or(0)
+    })
+}
+
+pub fn mint(token: AccountId, to: AccountId, value: Balance) {
+    let balance = balance_of(token, to);
+    BALANCES.with(|b| b.borrow_mut().insert((token, to), balance + value));
+}
+
+pub fn approve(token: AccountId, owner: AccountId, spender: AccountId, value: Balance) {
+    ALLOWANCES.with(|a| a.borrow_mut().insert((token, owner, spender), value));
+}
+
+pub fn transfer(
+    token: AccountId,
+    from: AccountId,
+    to: AccountId,
+    value: Balance,
+) -> Result<(), PSP22Error> {
+    let balance = balance_of(token, from);
+    if balance < value {
+        return Err(PSP22Error::InsufficientBalance);
+    }
+
+    BALANCES.with(|b| b.borrow_mut().insert((token, from), balance - value));
+    mint(token, to, value);
+    Ok(())
+}
+
+pub fn transfer_from(
+    token: AccountId,
+    spender: AccountId,
+    from: AccountId,
+    to: AccountId,
+    value: Balance,
+) -> Result<(), PSP22Error> {
+    let allowance = allowance(token, from, spender);
+    if allowance < value {
+        return Err(PSP22Error::InsufficientAllowance);
+    }
+
+    transfer(token, from, to, value)?;
+    approve(token, from, spender, allowance - value);
+    Ok(())
+}
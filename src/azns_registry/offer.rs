@@ -0,0 +1,41 @@
+use ink::primitives::AccountId;
+
+type Balance = u128;
+
+/// An escrowed offer to buy a registered name
+#[derive(scale::Encode, scale::Decode, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout, Debug)
+)]
+pub struct Offer {
+    /// Owner of the name at the time the offer was made
+    pub owner: AccountId,
+    /// Registration time of the name at the time the offer was made
+    pub registered_at: u64,
+    /// Escrowed amount (incl. protocol fee)
+    pub amount: Balance,
+    /// Timestamp after which the offer can no longer be accepted
+    pub expiry: u64,
+}
+
+impl Offer {
+    pub fn new(owner: AccountId, registered_at: u64, amount: Balance, expiry: u64) -> Self {
+        Self {
+            owner,
+            registered_at,
+            amount,
+            expiry,
+        }
+    }
+
+    /// Whether the offer was made to the current owner of the name, since it was registered
+    pub fn targets(&self, owner: &AccountId, registered_at: u64) -> bool {
+        &self.owner == owner && self.registered_at == registered_at
+    }
+
+    /// An offer is void once expired, when the name has changed hands or was registered anew
+    pub fn is_active(&self, owner: &AccountId, registered_at: u64, current_time: u64) -> bool {
+        self.targets(owner, registered_at) && current_time <= self.expiry
+    }
+}
@@ -0,0 +1,21 @@
+/// Policy for refunding the unused registration time of released names
+#[derive(scale::Encode, scale::Decode, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout, Debug)
+)]
+pub struct RefundPolicy {
+    /// Share of the unused time's value that is refunded (in basis points)
+    pub share: u16,
+    /// Minimum time a name must be held before its release is refunded
+    pub min_holding_period: u64,
+    /// Whether the refund is credited towards future registrations instead of paid out
+    pub as_credit: bool,
+}
+
+impl RefundPolicy {
+    /// Whether a name registered at `registration` has been held long enough for a refund
+    pub fn is_eligible(&self, registration: u64, current_time: u64) -> bool {
+        current_time >= registration.saturating_add(self.min_holding_period)
+    }
+}
@@ -0,0 +1,24 @@
+use ink::primitives::AccountId;
+
+/// An off-chain discount voucher signed by the configured voucher signer
+#[derive(scale::Encode, scale::Decode, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, Debug))]
+pub struct Voucher {
+    /// Account redeeming the voucher, to which the discounted name is registered
+    pub recipient: AccountId,
+    /// Inclusive (min, max) length of the names the voucher applies to
+    pub name_length: (u32, u32),
+    /// Discount on the registration price (in basis points)
+    pub discount: u16,
+    /// Timestamp after which the voucher can no longer be used
+    pub expiry: u64,
+    /// Unique number preventing the voucher from being used twice
+    pub nonce: u128,
+}
+
+impl Voucher {
+    pub fn applies_to(&self, name: &str) -> bool {
+        let (min, max) = self.name_length;
+        (min..=max).contains(&(name.chars().count() as u32))
+    }
+}
@@ -0,0 +1,23 @@
+use ink::env::hash::Sha2x256;
+use ink::primitives::AccountId;
+
+/// Registration quota of a whitelisted account, encoded in its merkle leaf
+#[derive(scale::Encode, scale::Decode, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, Debug))]
+pub struct WhitelistQuota {
+    /// Maximum number of names the account can register during the whitelist-phase
+    pub max_names: u32,
+    /// Maximum registration period (in years) of each name
+    pub max_years: u8,
+    /// Discount on the registration price (in basis points)
+    pub discount: u16,
+}
+
+impl WhitelistQuota {
+    /// Returns the merkle leaf of the account, i.e. `sha2_256(scale(account, max_names, max_years, discount))`
+    pub fn leaf(&self, account: AccountId) -> [u8; 32] {
+        let mut leaf = [0u8; 32];
+        ink::env::hash_encoded::<Sha2x256, _>(&(account, self), &mut leaf);
+        leaf
+    }
+}
@@ -3,14 +3,14 @@
 #[zink::coating(Ownable2Step[
     Error = Error::NotAdmin
 ])]
-#[zink::coating(Upgradable)]
 #[ink::contract]
 mod azns_router {
     use ink::prelude::string::{String, ToString};
     use ink::prelude::vec;
     use ink::prelude::vec::Vec;
     use ink::storage::traits::ManualKey;
-    use ink::storage::Mapping;
+    use ink::storage::{Lazy, Mapping};
+    use interfaces::access_control::{self, AccessControl, Role};
 
     pub type Result<T> = core::result::Result<T, Error>;
 
@@ -26,6 +26,42 @@ mod azns_router {
         routes: Mapping<String, AccountId, ManualKey<100>>,
         /// Maps registry-address to its associated TLDs
         associated_tlds: Mapping<AccountId, Vec<String>, ManualKey<101>>,
+        /// Roles granted to accounts
+        roles: Mapping<(Role, AccountId), (), ManualKey<102>>,
+        /// Whether the mutating calls are paused
+        paused: Lazy<bool, ManualKey<103>>,
+    }
+
+    /// Emitted whenever a role is granted to an account
+    #[ink(event)]
+    pub struct RoleGranted {
+        #[ink(topic)]
+        role: Role,
+        #[ink(topic)]
+        account: AccountId,
+        sender: AccountId,
+    }
+
+    /// Emitted whenever a role is revoked from (or renounced by) an account
+    #[ink(event)]
+    pub struct RoleRevoked {
+        #[ink(topic)]
+        role: Role,
+        #[ink(topic)]
+        account: AccountId,
+        sender: AccountId,
+    }
+
+    /// Emitted whenever the mutating calls are paused
+    #[ink(event)]
+    pub struct Paused {
+        sender: AccountId,
+    }
+
+    /// Emitted whenever the mutating calls are unpaused
+    #[ink(event)]
+    pub struct Unpaused {
+        sender: AccountId,
     }
 
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
@@ -44,6 +80,10 @@ mod azns_router {
         /// Domain does not contain valid name and/or tld
         InvalidDomainName,
         EmptyList,
+        /// Caller is missing the role required for the operation
+        MissingRole(Role),
+        /// The mutating calls are paused
+        ContractPaused,
     }
 
     impl Router {
@@ -55,12 +95,15 @@ mod azns_router {
                 routes: Default::default(),
                 registry: Default::default(),
                 associated_tlds: Default::default(),
+                roles: Default::default(),
+                paused: Default::default(),
             }
         }
 
         #[ink(message)]
         pub fn add_registry(&mut self, tld: Vec<String>, registry_addr: AccountId) -> Result<()> {
-            self.ensure_admin()?;
+            self.ensure_role(Role::ConfigManager)?;
+            self.ensure_not_paused()?;
 
             // this is disabled during tests as it is not being supported (tests end up panicking).
             #[cfg(not(test))]
@@ -80,14 +123,14 @@ mod azns_router {
                 }
             };
 
-            for i in 0..tld.len() {
-                if self.routes.contains(&tld[i]) {
-                    return Err(Error::TldAlreadyInUse(tld[i].clone()));
+            for tld in tld {
+                if self.routes.contains(&tld) {
+                    return Err(Error::TldAlreadyInUse(tld));
                 }
-                self.routes.insert(&tld[i], &registry_addr);
-                assoc_tlds.push(tld[i].clone());
+                self.routes.insert(&tld, &registry_addr);
+                assoc_tlds.push(tld);
             }
-            self.associated_tlds.insert(&registry_addr, &assoc_tlds);
+            self.associated_tlds.insert(registry_addr, &assoc_tlds);
 
             Ok(())
         }
@@ -98,7 +141,8 @@ mod azns_router {
             tld: Vec<String>,
             registry_addr: AccountId,
         ) -> Result<()> {
-            self.ensure_admin()?;
+            self.ensure_role(Role::ConfigManager)?;
+            self.ensure_not_paused()?;
 
             // this is disabled during tests as it is not being supported (tests end up panicking).
             #[cfg(not(test))]
@@ -106,15 +150,16 @@ mod azns_router {
                 return Err(Error::InvalidRegistryAddress);
             }
 
-            for i in 0..tld.len() {
-                self.remove_tld(&tld[i])?;
+            for tld in tld.iter() {
+                self.remove_tld(tld)?;
             }
             self.add_registry(tld, registry_addr)
         }
 
         #[ink(message)]
         pub fn remove_registry_address(&mut self, registry_addr: AccountId) -> Result<()> {
-            self.ensure_admin()?;
+            self.ensure_role(Role::ConfigManager)?;
+            self.ensure_not_paused()?;
 
             if let Some(tlds) = self.associated_tlds.get(registry_addr) {
                 tlds.iter().for_each(|tld| self.routes.remove(tld));
@@ -137,8 +182,7 @@ mod azns_router {
         pub fn get_all_tlds(&self) -> Vec<String> {
             self.get_all_registries()
                 .into_iter()
-                .map(|(_, tlds)| tlds)
-                .flatten()
+                .flat_map(|(_, tlds)| tlds)
                 .collect()
         }
 
@@ -234,7 +278,8 @@ mod azns_router {
                     use ink::env::call::{build_call, ExecutionInput, Selector};
 
                     const GET_PRIMARY_DOMAIN_SELECTOR: [u8; 4] = [0xBF, 0x5B, 0x56, 0x77];
-                    let result = build_call::<Environment>()
+
+                    build_call::<Environment>()
                         .call(registry_addr)
                         .exec_input(
                             ExecutionInput::new(Selector::new(GET_PRIMARY_DOMAIN_SELECTOR))
@@ -242,13 +287,114 @@ mod azns_router {
                         )
                         .returns::<Option<String>>()
                         .params()
-                        .invoke();
-
-                    result
+                        .invoke()
                 }
             }
         }
 
+        /// (ADMIN-OPERATION)
+        /// Grants a role to the given account
+        #[ink(message)]
+        pub fn grant_role(&mut self, role: Role, account: AccountId) -> Result<()> {
+            self.ensure_admin()?;
+
+            if self.roles.grant(role, account) {
+                self.env().emit_event(RoleGranted {
+                    role,
+                    account,
+                    sender: self.env().caller(),
+                });
+            }
+            Ok(())
+        }
+
+        /// (ADMIN-OPERATION)
+        /// Revokes a role from the given account
+        #[ink(message)]
+        pub fn revoke_role(&mut self, role: Role, account: AccountId) -> Result<()> {
+            self.ensure_admin()?;
+            self.remove_role(role, account);
+            Ok(())
+        }
+
+        /// Gives up a role held by the caller
+        #[ink(message)]
+        pub fn renounce_role(&mut self, role: Role) -> Result<()> {
+            self.remove_role(role, self.env().caller());
+            Ok(())
+        }
+
+        /// @note the admin implicitly holds every role
+        #[ink(message)]
+        pub fn has_role(&self, role: Role, account: AccountId) -> bool {
+            self.roles.holds(self.admin, role, account)
+        }
+
+        /// Pauses the mutating calls
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<()> {
+            self.ensure_role(Role::Pauser)?;
+
+            if !self.is_paused() {
+                self.paused.set(&true);
+                self.env().emit_event(Paused {
+                    sender: self.env().caller(),
+                });
+            }
+            Ok(())
+        }
+
+        /// Unpauses the mutating calls
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<()> {
+            self.ensure_role(Role::Pauser)?;
+
+            if self.is_paused() {
+                self.paused.set(&false);
+                self.env().emit_event(Unpaused {
+                    sender: self.env().caller(),
+                });
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_paused(&self) -> bool {
+            self.paused.get().unwrap_or_default()
+        }
+
+        /// Upgrades the contract code
+        #[ink(message)]
+        pub fn upgrade_contract(&mut self, code_hash: [u8; 32]) -> Result<()> {
+            self.ensure_role(Role::Upgrader)?;
+            access_control::upgrade_contract(code_hash);
+            Ok(())
+        }
+
+        fn ensure_role(&self, role: Role) -> Result<()> {
+            match self.has_role(role, self.env().caller()) {
+                true => Ok(()),
+                false => Err(Error::MissingRole(role)),
+            }
+        }
+
+        fn ensure_not_paused(&self) -> Result<()> {
+            match self.is_paused() {
+                true => Err(Error::ContractPaused),
+                false => Ok(()),
+            }
+        }
+
+        fn remove_role(&mut self, role: Role, account: AccountId) {
+            if self.roles.revoke(role, account) {
+                self.env().emit_event(RoleRevoked {
+                    role,
+                    account,
+                    sender: self.env().caller(),
+                });
+            }
+        }
+
         fn remove_tld(&mut self, tld: &str) -> Result<()> {
             let registry_addr = match self.routes.get(tld) {
                 Some(addr) => {
@@ -258,16 +404,16 @@ mod azns_router {
                 None => Err(Error::TldNotFound(tld.into()))?,
             };
 
-            self.associated_tlds.get(registry_addr).map(|mut tlds| {
+            if let Some(mut tlds) = self.associated_tlds.get(registry_addr) {
                 tlds.retain(|ele| ele != tld);
 
                 if tlds.is_empty() {
                     self.associated_tlds.remove(registry_addr);
                     self.registry.retain(|&ele| ele != registry_addr);
                 } else {
-                    self.associated_tlds.insert(&registry_addr, &tlds);
+                    self.associated_tlds.insert(registry_addr, &tlds);
                 }
-            });
+            }
 
             Ok(())
         }
@@ -284,7 +430,7 @@ mod azns_router {
         }
 
         fn get_test_router() -> Router {
-            let contract_addr: AccountId = AccountId::from([0xFF as u8; 32]);
+            let contract_addr: AccountId = AccountId::from([0xFF_u8; 32]);
             set_callee::<DefaultEnvironment>(contract_addr);
             Router::new(default_accounts().alice)
         }
@@ -347,6 +493,69 @@ mod azns_router {
             assert_eq!(contract.get_admin(), accounts.bob);
         }
 
+        #[ink::test]
+        fn roles_works() {
+            let accounts = default_accounts();
+            let mut contract = get_test_router();
+            let tld = vec!["azero".to_string()];
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                contract.add_registry(tld.clone(), accounts.bob),
+                Err(Error::MissingRole(Role::ConfigManager))
+            );
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract
+                .grant_role(Role::ConfigManager, accounts.bob)
+                .unwrap();
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.add_registry(tld.clone(), accounts.bob), Ok(()));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract
+                .revoke_role(Role::ConfigManager, accounts.bob)
+                .unwrap();
+            assert!(!contract.has_role(Role::ConfigManager, accounts.bob));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                contract.remove_registry_address(accounts.bob),
+                Err(Error::MissingRole(Role::ConfigManager))
+            );
+        }
+
+        #[ink::test]
+        fn pause_works() {
+            let accounts = default_accounts();
+            let mut contract = get_test_router();
+            let tld = vec!["azero".to_string()];
+
+            // Only pausers can pause
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.pause(), Err(Error::MissingRole(Role::Pauser)));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.pause(), Ok(()));
+            assert!(contract.is_paused());
+            assert_eq!(
+                contract.add_registry(tld.clone(), accounts.bob),
+                Err(Error::ContractPaused)
+            );
+            assert_eq!(
+                contract.remove_registry_address(accounts.bob),
+                Err(Error::ContractPaused)
+            );
+
+            // Read-only calls keep working
+            assert_eq!(contract.get_all_tlds(), Vec::<String>::new());
+
+            assert_eq!(contract.unpause(), Ok(()));
+            assert!(!contract.is_paused());
+            assert_eq!(contract.add_registry(tld.clone(), accounts.bob), Ok(()));
+        }
+
         #[test]
         fn extract_domain_works() {
             assert_eq!(